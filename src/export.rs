@@ -0,0 +1,655 @@
+use std::fmt::Write;
+use std::ops::RangeInclusive;
+
+use itertools::Itertools;
+
+use crate::numerics::distribution::distribution_sum::DistributionSum;
+use crate::numerics::distribution::RenormalizedDistribution;
+use crate::peak::{self, MultipletCascade, PatternNaming, Peak, Peaklet};
+
+/// Lines closer than this (Hz) are considered coincident by [`merged_line_list`]. Unlike
+/// [`crate::numerics::distribution::COINCIDENCE_TOLERANCE`], this is absolute.
+const LINE_COINCIDENCE_HZ: f64 = 1e-6;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+/// How exported intensities are scaled, as different tools expect different conventions.
+pub enum Normalization {
+    /// As computed: the integrations of a peak's lines sum to one, and its spectrum is a density
+    /// (per ppm) whose area is one.
+    #[default]
+    Raw,
+    /// Scaled such that the most intense line or point is one.
+    UnitMaximum,
+    /// Scaled such that the integrations of the lines sum to one, or that the area under the
+    /// spectrum (by the trapezoidal rule over the exported points) is one. For a complete peak,
+    /// this differs from [`Self::Raw`] only by the (small) area outside of the exported range.
+    UnitArea,
+}
+
+impl Normalization {
+    pub const ALL: [Self; 3] = [Self::Raw, Self::UnitMaximum, Self::UnitArea];
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Raw => "Raw",
+            Self::UnitMaximum => "Maximum = 1",
+            Self::UnitArea => "Area = 1",
+        }
+    }
+
+    /// Scale the integrations of a line list.
+    pub fn normalize_lines(self, integrations: &mut [f64]) {
+        let total = match self {
+            Self::Raw => return,
+            Self::UnitMaximum => integrations.iter().copied().fold(0., f64::max),
+            Self::UnitArea => integrations.iter().sum(),
+        };
+        Self::scale(integrations.iter_mut(), total);
+    }
+
+    /// Scale the intensities of a sampled spectrum, given as `[x, y]` in order of increasing `x`.
+    pub fn normalize_spectrum(self, points: &mut [[f64; 2]]) {
+        let total = match self {
+            Self::Raw => return,
+            Self::UnitMaximum => points.iter().map(|[_, y]| *y).fold(0., f64::max),
+            Self::UnitArea => points
+                .array_windows()
+                .map(|[[x0, y0], [x1, y1]]| 0.5 * (x1 - x0) * (y0 + y1))
+                .sum(),
+        };
+        Self::scale(points.iter_mut().map(|[_, y]| y), total);
+    }
+
+    fn scale<'a>(values: impl Iterator<Item = &'a mut f64>, total: f64) {
+        // Leave degenerate data (e.g., an empty spectrum) unscaled.
+        if total > 0. {
+            values.for_each(|value| *value /= total);
+        }
+    }
+}
+
+// TODO: once a reference (TMS) line can be shown, offer to include it in the exports and the
+// summary: by default in images, for calibration, but not in line lists.
+#[must_use]
+/// The peaklets of the final stage of `cascade` (_i.e._, the stick spectrum), in order of
+/// increasing shift.
+pub fn line_list(cascade: &MultipletCascade) -> Vec<Peaklet> {
+    cascade
+        .final_peaklets()
+        .iter()
+        .copied()
+        .sorted_by(|a, b| a.δ.total_cmp(&b.δ))
+        .collect()
+}
+
+#[must_use]
+/// Like [`line_list`], but with coincident lines (as arise from equal coupling constants to
+/// different groups) merged into one whose integration is their sum.
+pub fn merged_line_list(cascade: &MultipletCascade) -> Vec<Peaklet> {
+    line_list(cascade)
+        .into_iter()
+        .coalesce(|a, b| {
+            if approx::abs_diff_eq!(a.δ, b.δ, epsilon = LINE_COINCIDENCE_HZ) {
+                Ok(Peaklet {
+                    integration: a.integration + b.integration,
+                    ..a
+                })
+            } else {
+                Err((a, b))
+            }
+        })
+        .collect()
+}
+
+#[must_use]
+/// The [`line_list`] of `cascade` as CSV, with shifts given in both Hz and ppm. The header records
+/// `label`, the coupling `description`, the field strength, and the `normalization` of the
+/// integrations.
+pub fn line_list_csv(
+    cascade: &MultipletCascade,
+    label: &str,
+    description: &str,
+    field_strength: f64,
+    normalization: Normalization,
+) -> String {
+    let mut csv = format!(
+        "# Peak: {label}\n# Coupling: {description}\n# Field strength: {field_strength} MHz\n\
+            # Normalization: {}\nδ (Hz),δ (ppm),Integration\n",
+        normalization.name(),
+    );
+    let lines = line_list(cascade);
+    let mut integrations = lines
+        .iter()
+        .map(|peaklet| peaklet.integration)
+        .collect_vec();
+    normalization.normalize_lines(&mut integrations);
+    for (peaklet, integration) in lines.iter().zip(integrations) {
+        writeln!(
+            csv,
+            "{:.4},{:.6},{integration:.6}",
+            peaklet.δ,
+            peak::j_to_ppm(peaklet.δ, field_strength),
+        )
+        .unwrap();
+    }
+    csv
+}
+
+/// Integrations further than this from whole multiples of the weakest line are not reduced by
+/// [`integer_ratios`].
+const RATIO_TOLERANCE: f64 = 1e-6;
+
+#[must_use]
+/// The integrations of `lines` as the smallest whole numbers in the same ratio (_e.g._, 1:3:3:1
+/// for a quartet), if there are any.
+///
+/// The lines of a first-order multiplet are products of rows of Pascal's triangle, so their
+/// integrations are whole multiples of that of the outermost lines, which is the weakest. Merging
+/// coincident lines preserves this. Other lists, which have no such ratio, give [`None`].
+pub fn integer_ratios(lines: &[Peaklet]) -> Option<Vec<u64>> {
+    let weakest = lines
+        .iter()
+        .map(|peaklet| peaklet.integration)
+        .reduce(f64::min)
+        .filter(|&weakest| weakest > 0.)?;
+    lines
+        .iter()
+        .map(|peaklet| {
+            let ratio = peaklet.integration / weakest;
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            ((ratio.round() - ratio).abs() <= RATIO_TOLERANCE * ratio)
+                .then_some(ratio.round() as u64)
+        })
+        .collect()
+}
+
+/// Version of the format written by [`canonical_line_list`].
+const CANONICAL_LINE_LIST_VERSION: u32 = 1;
+
+#[must_use]
+#[allow(clippy::doc_markdown)]
+/// The [`merged_line_list`] of `cascade` in a plain format meant to be parsed by other programs:
+///
+/// - Lines starting with `#` are comments. The first records the format version, and those of the
+///   form `# key: value` record the field strength (in MHz), the FWHM (in Hz), and the lineshape.
+/// - Every other line is a line of the multiplet, as its shift from the center (in Hz) and its
+///   integration, separated by a single space. Lines are in order of increasing shift, and the
+///   integrations sum to one.
+///
+/// Numbers are written with as many digits as needed to be read back exactly.
+pub fn canonical_line_list(cascade: &MultipletCascade, field_strength: f64) -> String {
+    let mut text = format!(
+        "# {} line list, version {CANONICAL_LINE_LIST_VERSION}\n\
+            # field_strength_mhz: {field_strength}\n# fwhm_hz: {}\n# lineshape: {}\n\
+            # columns: delta_hz intensity\n",
+        app_name!(),
+        cascade.fwhm(),
+        cascade.lineshape().name().to_lowercase(),
+    );
+    for peaklet in merged_line_list(cascade) {
+        writeln!(text, "{} {}", peaklet.δ, peaklet.integration).unwrap();
+    }
+    text
+}
+
+#[must_use]
+/// A spectrum sampled at `points`, given as `[δ (ppm), intensity]` in order of increasing
+/// shift, as CSV. The header records `label`, the field strength, and the FWHM (Hz) of the lines,
+/// which are needed to reproduce the spectrum, and the intensities are scaled according to
+/// `normalization`.
+pub fn spectrum_csv(
+    points: &[[f64; 2]],
+    label: &str,
+    field_strength: f64,
+    fwhm: f64,
+    normalization: Normalization,
+) -> String {
+    let mut points = points.to_vec();
+    normalization.normalize_spectrum(&mut points);
+    let mut csv = format!(
+        "# Peak: {label}\n# Field strength: {field_strength} MHz\n# FWHM: {fwhm} Hz\n\
+            # Normalization: {}\nδ (ppm),Intensity\n",
+        normalization.name(),
+    );
+    for [δ, intensity] in points {
+        // Shifts are written in full, since neighboring points are too close to round at high zoom.
+        writeln!(csv, "{δ},{intensity:.6e}").unwrap();
+    }
+    csv
+}
+
+/// Ordinates per line of the `XYDATA` table of [`jcamp_dx`], which keeps lines within the 80
+/// characters the format recommends.
+const JCAMP_VALUES_PER_LINE: usize = 8;
+/// The largest ordinate written by [`jcamp_dx`], which are integers scaled by `YFACTOR`.
+const JCAMP_MAX_ORDINATE: f64 = 1e6;
+
+#[must_use]
+#[allow(clippy::doc_markdown)]
+/// A spectrum sampled at `point_count` evenly spaced shifts across `range` (ppm), in the JCAMP-DX
+/// 4.24 format read by most NMR software. As is conventional, the shifts are written in
+/// decreasing order.
+///
+/// The ordinates are written in the `(X++(Y..Y))` form: each line gives the shift of its first
+/// point, followed by the intensities of successive points, as integers to be multiplied by
+/// `YFACTOR`. Shifts are written in full, since readers check them against `FIRSTX` and `DELTAX`,
+/// which are too fine to round at high zoom. Line breaks in `label` are replaced by spaces.
+pub fn jcamp_dx<D: RenormalizedDistribution>(
+    waveform: &DistributionSum<D>,
+    label: &str,
+    field_strength: f64,
+    range: RangeInclusive<f64>,
+    point_count: usize,
+) -> String {
+    let point_count = point_count.max(2);
+    let (first_x, last_x) = (
+        range.start().max(*range.end()),
+        range.start().min(*range.end()),
+    );
+    #[allow(clippy::cast_precision_loss)]
+    let delta_x = (last_x - first_x) / (point_count - 1) as f64;
+    #[allow(clippy::cast_precision_loss)]
+    let xs = (0..point_count)
+        .map(|i| first_x + i as f64 * delta_x)
+        .collect_vec();
+    let ys = xs.iter().map(|&x| waveform.evaluate(x)).collect_vec();
+    let max_y = ys.iter().copied().fold(0., f64::max);
+    let y_factor = if max_y > 0. {
+        max_y / JCAMP_MAX_ORDINATE
+    } else {
+        1.
+    };
+    #[allow(clippy::cast_possible_truncation)]
+    let ordinates = ys
+        .iter()
+        .map(|y| (y / y_factor).round() as i64)
+        .collect_vec();
+    #[allow(clippy::cast_precision_loss)]
+    let to_y = |ordinate: i64| ordinate as f64 * y_factor;
+
+    let mut jdx = String::new();
+    for (key, value) in [
+        ("TITLE", label.replace(['\r', '\n'], " ")),
+        ("JCAMP-DX", "4.24".to_owned()),
+        ("DATA TYPE", "NMR SPECTRUM".to_owned()),
+        ("DATA CLASS", "XYDATA".to_owned()),
+        ("ORIGIN", format!("Simulated by {}", app_name!())),
+        ("OWNER", String::new()),
+        (".OBSERVE FREQUENCY", field_strength.to_string()),
+        (".OBSERVE NUCLEUS", "^1H".to_owned()),
+        ("XUNITS", "PPM".to_owned()),
+        ("YUNITS", "ARBITRARY UNITS".to_owned()),
+        ("XFACTOR", "1".to_owned()),
+        ("YFACTOR", format!("{y_factor:e}")),
+        ("FIRSTX", first_x.to_string()),
+        ("LASTX", xs[point_count - 1].to_string()),
+        ("DELTAX", delta_x.to_string()),
+        // Unlike the table, these are actual values rather than ordinates.
+        (
+            "MAXY",
+            to_y(*ordinates.iter().max().unwrap_or(&0)).to_string(),
+        ),
+        (
+            "MINY",
+            to_y(*ordinates.iter().min().unwrap_or(&0)).to_string(),
+        ),
+        ("NPOINTS", point_count.to_string()),
+        ("FIRSTY", to_y(ordinates[0]).to_string()),
+    ] {
+        writeln!(jdx, "##{key}={value}").unwrap();
+    }
+    writeln!(jdx, "##XYDATA=(X++(Y..Y))").unwrap();
+    for (xs, ordinates) in xs
+        .chunks(JCAMP_VALUES_PER_LINE)
+        .zip(ordinates.chunks(JCAMP_VALUES_PER_LINE))
+    {
+        write!(jdx, "{}", xs[0]).unwrap();
+        for ordinate in ordinates {
+            write!(jdx, " {ordinate}").unwrap();
+        }
+        jdx.push('\n');
+    }
+    writeln!(jdx, "##END=").unwrap();
+    jdx
+}
+
+#[must_use]
+/// The multiplicity (as named by `naming`) and coupling constants of `peak`, _e.g._,
+/// `(dd, J = 7.2, 2.1 Hz, 1H)`.
+pub fn descriptor(peak: &Peak, naming: PatternNaming) -> String {
+    descriptor_with(
+        peak,
+        naming,
+        |multiplicity| multiplicity.to_owned(),
+        "J",
+        " Hz",
+    )
+}
+
+#[must_use]
+/// Like [`descriptor`], but typeset for LaTeX, _e.g._,
+/// `(\textit{dd}, $J$ = 7.2, 2.1~Hz, 1H)`.
+pub fn descriptor_latex(peak: &Peak, naming: PatternNaming) -> String {
+    descriptor_with(
+        peak,
+        naming,
+        |multiplicity| format!(r"\textit{{{multiplicity}}}"),
+        "$J$",
+        "~Hz",
+    )
+}
+
+#[must_use]
+/// A description of the coupling environment of `peak` in terms of its coupling partners, _e.g._,
+/// `CH₂ coupled to 2H (J = 7.0 Hz) + 3H (J = 7.0 Hz)`.
+pub fn coupling_description(peak: &Peak) -> String {
+    let observed = match peak.proton_count {
+        1 => "CH".to_owned(),
+        2 => "CH₂".to_owned(),
+        3 => "CH₃".to_owned(),
+        n => format!("{n}H"),
+    };
+    if peak.active_splitters().next().is_none() {
+        return format!("{observed}, uncoupled");
+    }
+    let partners = peak
+        .active_splitters()
+        .map(|s| format!("{}H (J = {:.1} Hz)", s.n, s.j))
+        .join(" + ");
+    format!("{observed} coupled to {partners}")
+}
+
+fn descriptor_with(
+    peak: &Peak,
+    naming: PatternNaming,
+    format_multiplicity: impl Fn(&str) -> String,
+    j_symbol: &str,
+    hz_suffix: &str,
+) -> String {
+    let mut fields = vec![];
+    // Coupling constants are only meaningful if the pattern can be named.
+    if let Some(name) = peak.name(naming) {
+        fields.push(format_multiplicity(&name));
+        if peak.active_splitters().next().is_some() {
+            let js = peak
+                .active_splitters()
+                .map(|s| format!("{:.1}", s.j))
+                .join(", ");
+            fields.push(format!("{j_symbol} = {js}{hz_suffix}"));
+        }
+    } else {
+        fields.push(format_multiplicity("m"));
+    }
+    fields.push(format!("{}H", peak.proton_count));
+    format!("({})", fields.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::peak::{PatternNaming, Peak, Peaklet, Splitter};
+
+    #[test]
+    fn normalizations() {
+        use super::Normalization;
+
+        let mut lines = [0.25, 0.5, 0.25];
+        Normalization::Raw.normalize_lines(&mut lines);
+        approx::assert_abs_diff_eq!(&lines[..], &[0.25, 0.5, 0.25][..]);
+        Normalization::UnitMaximum.normalize_lines(&mut lines);
+        approx::assert_abs_diff_eq!(&lines[..], &[0.5, 1., 0.5][..]);
+        Normalization::UnitArea.normalize_lines(&mut lines);
+        approx::assert_abs_diff_eq!(&lines[..], &[0.25, 0.5, 0.25][..]);
+
+        // A triangle of area 2 and height 2.
+        let mut spectrum = [[-1., 0.], [0., 2.], [1., 0.]];
+        Normalization::UnitArea.normalize_spectrum(&mut spectrum);
+        approx::assert_abs_diff_eq!(spectrum[1][1], 1.);
+        Normalization::UnitMaximum.normalize_spectrum(&mut spectrum);
+        approx::assert_abs_diff_eq!(spectrum[1][1], 1.);
+
+        let mut empty: [[f64; 2]; 0] = [];
+        Normalization::UnitArea.normalize_spectrum(&mut empty);
+    }
+
+    #[test]
+    fn spectrum_csv() {
+        use super::Normalization;
+
+        let points = [[-0.01, 0.], [0., 4.], [0.01, 0.]];
+        let csv = super::spectrum_csv(&points, "CH₃", 400., 1.5, Normalization::UnitMaximum);
+        let (header, rows): (Vec<_>, Vec<_>) = csv.lines().partition(|line| line.starts_with('#'));
+        assert!(header.contains(&"# Field strength: 400 MHz"), "{header:?}");
+        assert!(header.contains(&"# FWHM: 1.5 Hz"), "{header:?}");
+        assert_eq!(rows[0], "δ (ppm),Intensity");
+        let intensities = rows[1..]
+            .iter()
+            .map(|row| row.split(',').nth(1).unwrap().parse::<f64>().unwrap())
+            .collect::<Vec<_>>();
+        approx::assert_abs_diff_eq!(&intensities[..], &[0., 1., 0.][..]);
+
+        // Closely spaced shifts remain distinct.
+        let points = [[1e-3, 0.], [1e-3 + 2e-7, 1.], [1e-3 + 4e-7, 0.]];
+        let csv = super::spectrum_csv(&points, "CH₃", 400., 1.5, Normalization::Raw);
+        let shifts = csv
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .skip(1)
+            .map(|row| row.split(',').next().unwrap().parse::<f64>().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(shifts.len(), 3);
+        assert!(
+            shifts.windows(2).all(|pair| pair[0] < pair[1]),
+            "{shifts:?}"
+        );
+    }
+
+    #[test]
+    fn jcamp_dx() {
+        let peak = Peak {
+            splitters: vec![Splitter::new(1, 7.)],
+            ..Default::default()
+        };
+        let waveform = peak
+            .build_multiplet_cascade()
+            .final_lineshape_waveform(400.);
+        let jdx = super::jcamp_dx(&waveform, "CH", 400., -0.05..=0.05, 101);
+        let field = |key: &str| {
+            jdx.lines()
+                .find_map(|line| line.strip_prefix(&format!("##{key}=")))
+                .unwrap_or_else(|| panic!("no {key}"))
+        };
+        assert_eq!(field("TITLE"), "CH");
+        assert_eq!(field("DATA TYPE"), "NMR SPECTRUM");
+        assert_eq!(field(".OBSERVE FREQUENCY"), "400");
+        assert_eq!(field("NPOINTS"), "101");
+        let y_factor = field("YFACTOR").parse::<f64>().unwrap();
+        let first_y = field("FIRSTY").parse::<f64>().unwrap();
+        let max_y = field("MAXY").parse::<f64>().unwrap();
+        assert!(jdx.trim_end().ends_with("##END="));
+
+        // Read the table back: the shifts decrease, and every point is present.
+        let rows = jdx
+            .lines()
+            .skip_while(|line| !line.starts_with("##XYDATA"))
+            .skip(1)
+            .take_while(|line| !line.starts_with("##"))
+            .map(|line| {
+                let mut fields = line.split(' ');
+                let x = fields.next().unwrap().parse::<f64>().unwrap();
+                let ys = fields
+                    .map(|y| y.parse::<i64>().unwrap())
+                    .collect::<Vec<_>>();
+                (x, ys)
+            })
+            .collect::<Vec<_>>();
+        approx::assert_abs_diff_eq!(rows[0].0, 0.05);
+        assert!(rows.windows(2).all(|pair| pair[1].0 < pair[0].0));
+        let ys = rows.into_iter().flat_map(|(_, ys)| ys).collect::<Vec<_>>();
+        assert_eq!(ys.len(), 101);
+        // The header gives actual values, which readers check against the table.
+        #[allow(clippy::cast_precision_loss)]
+        {
+            approx::assert_relative_eq!(first_y, ys[0] as f64 * y_factor);
+            approx::assert_relative_eq!(max_y, *ys.iter().max().unwrap() as f64 * y_factor);
+        }
+        // The doublet is symmetric about the center.
+        assert_eq!(ys, ys.iter().copied().rev().collect::<Vec<_>>());
+        #[allow(clippy::cast_precision_loss)]
+        let center = ys[50] as f64 * y_factor;
+        // Up to the rounding of the ordinates.
+        approx::assert_abs_diff_eq!(center, waveform.evaluate(0.), epsilon = y_factor);
+
+        // At high zoom, the shifts still agree with the header, and the title is a single line.
+        let jdx = super::jcamp_dx(&waveform, "CH\n##END=", 400., 1e-3..=1.01e-3, 5000);
+        let field = |key: &str| {
+            jdx.lines()
+                .find_map(|line| line.strip_prefix(&format!("##{key}=")))
+                .unwrap_or_else(|| panic!("no {key}"))
+                .parse::<f64>()
+                .unwrap()
+        };
+        let (first_x, delta_x) = (field("FIRSTX"), field("DELTAX"));
+        approx::assert_relative_eq!(field("LASTX"), 1e-3);
+        let xs = jdx
+            .lines()
+            .skip_while(|line| !line.starts_with("##XYDATA"))
+            .skip(1)
+            .take_while(|line| !line.starts_with("##"))
+            .map(|line| line.split(' ').next().unwrap().parse::<f64>().unwrap());
+        for (i, x) in xs.enumerate() {
+            #[allow(clippy::cast_precision_loss)]
+            let expected = first_x + (i * super::JCAMP_VALUES_PER_LINE) as f64 * delta_x;
+            approx::assert_relative_eq!(x, expected, max_relative = 1e-12);
+        }
+        assert!(jdx.lines().any(|line| line == "##TITLE=CH ##END="));
+        assert_eq!(jdx.matches("\n##END=").count(), 1);
+    }
+
+    #[test]
+    fn merged_line_list() {
+        // A dd with equal coupling constants is a 1:2:1 triplet.
+        let peak = Peak {
+            proton_count: 1,
+            splitters: vec![Splitter::new(1, 7.), Splitter::new(1, 7.)],
+            ..Default::default()
+        };
+        let cascade = peak.build_multiplet_cascade();
+        assert_eq!(super::line_list(&cascade).len(), 4);
+        let integrations = super::merged_line_list(&cascade)
+            .into_iter()
+            .map(|peaklet| peaklet.integration)
+            .collect::<Vec<_>>();
+        approx::assert_abs_diff_eq!(&integrations[..], &[0.25, 0.5, 0.25][..]);
+    }
+
+    #[test]
+    fn canonical_line_list() {
+        let peak = Peak {
+            proton_count: 1,
+            splitters: vec![Splitter::new(2, 6.9)],
+            fwhm: 0.8,
+            ..Default::default()
+        };
+        let text = super::canonical_line_list(&peak.build_multiplet_cascade(), 400.);
+        let (header, lines): (Vec<_>, Vec<_>) =
+            text.lines().partition(|line| line.starts_with('#'));
+        assert!(header.contains(&"# field_strength_mhz: 400"));
+        assert!(header.contains(&"# fwhm_hz: 0.8"));
+        let lines = lines
+            .into_iter()
+            .flat_map(|line| line.split(' '))
+            .map(|field| field.parse::<f64>().unwrap())
+            .collect::<Vec<_>>();
+        approx::assert_abs_diff_eq!(
+            &lines[..],
+            &[-6.9, 0.25, 0., 0.5, 6.9, 0.25][..],
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn integer_ratios() {
+        let ratios = |splitters: Vec<Splitter>, merge: bool| {
+            let cascade = Peak {
+                splitters,
+                ..Default::default()
+            }
+            .build_multiplet_cascade();
+            let lines = if merge {
+                super::merged_line_list(&cascade)
+            } else {
+                super::line_list(&cascade)
+            };
+            super::integer_ratios(&lines).unwrap()
+        };
+        assert_eq!(ratios(vec![], true), [1]);
+        assert_eq!(ratios(vec![Splitter::new(3, 7.)], true), [1, 3, 3, 1]);
+        assert_eq!(
+            ratios(vec![Splitter::new(2, 7.), Splitter::new(1, 2.)], true),
+            [1, 1, 2, 2, 1, 1]
+        );
+        // Equal couplings to two groups coincide into a quintet.
+        let equal = || vec![Splitter::new(2, 7.), Splitter::new(2, 7.)];
+        assert_eq!(ratios(equal(), true), [1, 4, 6, 4, 1]);
+        assert_eq!(ratios(equal(), false).iter().sum::<u64>(), 16);
+
+        let irrational = [
+            Peaklet {
+                δ: 0.,
+                integration: 1.,
+            },
+            Peaklet {
+                δ: 1.,
+                integration: std::f64::consts::SQRT_2,
+            },
+        ];
+        assert_eq!(super::integer_ratios(&irrational), None);
+        assert_eq!(super::integer_ratios(&[]), None);
+    }
+
+    #[test]
+    fn descriptors() {
+        let mut peak = Peak {
+            proton_count: 2,
+            splitters: vec![Splitter::new(1, 7.2), Splitter::new(1, 2.1)],
+            ..Default::default()
+        };
+        let naming = PatternNaming::default();
+        assert_eq!(
+            super::descriptor(&peak, naming),
+            "(dd, J = 7.2, 2.1 Hz, 2H)"
+        );
+        assert_eq!(
+            super::descriptor_latex(&peak, naming),
+            r"(\textit{dd}, $J$ = 7.2, 2.1~Hz, 2H)"
+        );
+
+        peak.splitters.clear();
+        assert_eq!(super::descriptor(&peak, naming), "(s, 2H)");
+
+        peak.splitters = vec![Splitter::new(9, 7.)];
+        assert_eq!(super::descriptor(&peak, naming), "(m, 2H)");
+        assert_eq!(
+            super::descriptor(&peak, PatternNaming::Latin),
+            "(dec, J = 7.0 Hz, 2H)"
+        );
+    }
+
+    #[test]
+    fn coupling_descriptions() {
+        let mut peak = Peak {
+            proton_count: 1,
+            splitters: vec![Splitter::new(2, 7.), Splitter::new(3, 6.95)],
+            ..Default::default()
+        };
+        assert_eq!(
+            super::coupling_description(&peak),
+            "CH coupled to 2H (J = 7.0 Hz) + 3H (J = 7.0 Hz)"
+        );
+
+        peak.proton_count = 6;
+        peak.splitters.clear();
+        assert_eq!(super::coupling_description(&peak), "6H, uncoupled");
+    }
+}
@@ -28,8 +28,10 @@ macro_rules! app_name {
     };
 }
 
+pub mod export;
 pub mod numerics;
 pub mod peak;
+pub mod render;
 pub mod ui;
 pub mod utils;
 
@@ -2,6 +2,7 @@ use std::f64::consts::PI;
 
 pub mod distribution;
 pub mod error_function;
+pub mod fid;
 
 pub fn pascals_triangle(n: u32) -> impl Iterator<Item = u32> {
     let mut prev = 1;
@@ -26,8 +27,89 @@ pub fn ease_transition(factor: f64) -> f64 {
 }
 
 #[must_use]
+/// The inverse of [`ease_transition`]. Out-of-domain values are clamped to `[0, 1]`.
 pub fn ease_transition_inverse(value: f64) -> f64 {
-    (1.0 - 2.0 * value).acos() / PI
+    (1.0 - 2.0 * clamp_unit(value)).acos() / PI
+}
+
+/// The smallest permissible width of a distribution, below which its density would overflow.
+pub const MIN_WIDTH: f64 = 1e-9;
+
+#[must_use]
+/// Replace NaN with `fallback`, and clamp infinities to the largest finite values.
+pub fn finite_or(x: f64, fallback: f64) -> f64 {
+    if x.is_nan() {
+        fallback
+    } else {
+        x.clamp(-f64::MAX, f64::MAX)
+    }
+}
+
+#[must_use]
+/// Coerce `width` to be finite and at least [`MIN_WIDTH`].
+pub fn sanitize_width(width: f64) -> f64 {
+    finite_or(width, MIN_WIDTH).max(MIN_WIDTH)
+}
+
+#[must_use]
+/// Clamp `x` to `[0, 1]`, mapping NaN to zero.
+pub fn clamp_unit(x: f64) -> f64 {
+    finite_or(x, 0.0).clamp(0.0, 1.0)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+/// An easing curve mapping a normalized time in `[0, 1]` to a normalized value in `[0, 1]`.
+pub enum Easing {
+    Linear,
+    #[default]
+    Cosine,
+    /// Cubic ease-in-out, which lingers at the ends more than [`Easing::Cosine`].
+    Cubic,
+}
+
+impl Easing {
+    pub const ALL: [Self; 3] = [Self::Linear, Self::Cosine, Self::Cubic];
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Linear => "Linear",
+            Self::Cosine => "Cosine",
+            Self::Cubic => "Cubic",
+        }
+    }
+
+    #[must_use]
+    pub fn ease(self, factor: f64) -> f64 {
+        match self {
+            Self::Linear => factor,
+            Self::Cosine => ease_transition(factor),
+            Self::Cubic => {
+                if factor < 0.5 {
+                    4.0 * factor.powi(3)
+                } else {
+                    1.0 - 0.5 * (2.0 - 2.0 * factor).powi(3)
+                }
+            }
+        }
+    }
+
+    #[must_use]
+    /// The inverse of [`Easing::ease`], _i.e._, the time at which the given value is reached.
+    pub fn inverse(self, value: f64) -> f64 {
+        let value = clamp_unit(value);
+        match self {
+            Self::Linear => value,
+            Self::Cosine => ease_transition_inverse(value),
+            Self::Cubic => {
+                if value < 0.5 {
+                    (0.25 * value).cbrt()
+                } else {
+                    1.0 - 0.5 * (2.0 - 2.0 * value).cbrt()
+                }
+            }
+        }
+    }
 }
 
 #[must_use]
@@ -69,4 +151,46 @@ mod tests {
             approx::assert_abs_diff_eq!(sum, 1.);
         }
     }
+
+    #[test]
+    fn pathological_inputs_are_finite() {
+        use super::distribution::gaussian::Gaussian;
+        use super::distribution::lorentzian::Lorentzian;
+        use super::distribution::RenormalizedDistribution;
+
+        const PATHOLOGICAL: [f64; 6] =
+            [0., -1., f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1e-300];
+
+        for value in PATHOLOGICAL {
+            assert!(super::ease_transition_inverse(value).is_finite());
+            for easing in super::Easing::ALL {
+                assert!(easing.inverse(value).is_finite());
+            }
+        }
+        for fwhm in PATHOLOGICAL {
+            for normalization in [1., f64::NAN] {
+                let gaussian = Gaussian::with_fwhm_normalized(0., fwhm, normalization);
+                let lorentzian = Lorentzian::with_fwhm_normalized(0., fwhm, normalization);
+                for x in [0., 1e-3, -1., 1e6] {
+                    assert!(gaussian.evaluate(x).is_finite());
+                    assert!(gaussian.evaluate_cdf(x).is_finite());
+                    assert!(lorentzian.evaluate(x).is_finite());
+                    assert!(lorentzian.evaluate_cdf(x).is_finite());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn easing_inverse() {
+        for easing in super::Easing::ALL {
+            approx::assert_abs_diff_eq!(easing.ease(0.), 0.);
+            approx::assert_abs_diff_eq!(easing.ease(1.), 1.);
+            for i in 0..=20 {
+                let factor = f64::from(i) / 20.;
+                let roundtrip = easing.inverse(easing.ease(factor));
+                approx::assert_abs_diff_eq!(roundtrip, factor, epsilon = 1e-9);
+            }
+        }
+    }
 }
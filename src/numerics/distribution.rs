@@ -1,15 +1,45 @@
 pub mod distribution_sum;
+pub mod exponentially_modified_gaussian;
 pub mod gaussian;
+pub mod lineshape;
 pub mod lorentzian;
+pub mod pseudo_voigt;
 
+use std::cmp::Ordering;
 use std::ops::RangeInclusive;
 
+/// Distributions whose means and widths differ by less than this fraction of their width are
+/// considered coincident.
+pub const COINCIDENCE_TOLERANCE: f64 = 1e-6;
+
 /// A probability density function which has been renormalized by some factor.
+///
+/// Besides the built-in lineshapes, this may be implemented to draw peaks of any shape: waveforms
+/// ([`MultipletCascade::nth_waveform`](crate::peak::MultipletCascade::nth_waveform)), their sums
+/// ([`DistributionSum`](distribution_sum::DistributionSum)), and rendering
+/// ([`render_waveform`](crate::render::render_waveform)) are generic over the distribution.
+/// Implementations must uphold the following, on which sampling and integration rely:
+/// - [`Self::evaluate`] is non-negative, and integrates over the real line to
+///   [`Self::normalization`].
+/// - [`Self::evaluate_cdf`] is the integral of [`Self::evaluate`] from negative infinity; thus it
+///   is non-decreasing, and tends to zero and [`Self::normalization`] at either end.
+/// - The density is concentrated around [`Self::μ`], within a few [`Self::fwhm`]s, which is where
+///   the waveform will be sampled densely. (See also [`Self::extent_by_fwhm`].)
+/// - All of the above are finite for any finite input.
 pub trait RenormalizedDistribution: Copy + PartialEq {
+    /// A distribution centered at `μ`, whose full width at half maximum is `fwhm` and whose total
+    /// integral is `normalization`.
     fn with_fwhm_normalized(μ: f64, fwhm: f64, normalization: f64) -> Self;
+    #[must_use]
+    fn with_normalization(self, normalization: f64) -> Self;
     fn μ(&self) -> f64;
     fn fwhm(&self) -> f64;
     fn normalization(&self) -> f64;
+    /// The first moment of the distribution, per unit normalization. By default, that of a
+    /// distribution symmetric about [`Self::μ`].
+    fn mean(&self) -> f64 {
+        self.μ()
+    }
     fn evaluate(&self, x: f64) -> f64;
     fn evaluate_cdf(&self, x: f64) -> f64;
     fn extent_by_fwhm(&self, n: f64) -> RangeInclusive<f64> {
@@ -17,4 +47,99 @@ pub trait RenormalizedDistribution: Copy + PartialEq {
         let fwhm = self.fwhm();
         (μ - fwhm * n)..=(μ + fwhm * n)
     }
+
+    /// Whether `other` has the same mean and width as this distribution, up to
+    /// [`COINCIDENCE_TOLERANCE`], such that the two could be combined into one.
+    fn coincides_with(&self, other: &Self) -> bool {
+        let tolerance = COINCIDENCE_TOLERANCE * self.fwhm().max(other.fwhm());
+        (self.μ() - other.μ()).abs() <= tolerance && (self.fwhm() - other.fwhm()).abs() <= tolerance
+    }
+
+    /// Order by mean, then width, then normalization. This is a total order, such that the order
+    /// never depends on that of the input. Coincident distributions (see [`Self::coincides_with`])
+    /// are ordered by any floating-point jitter in their means, but
+    /// [`DistributionSum::merge_coincident`](distribution_sum::DistributionSum::merge_coincident)
+    /// combines them regardless.
+    fn cmp_position(&self, other: &Self) -> Ordering {
+        self.μ()
+            .total_cmp(&other.μ())
+            .then(self.fwhm().total_cmp(&other.fwhm()))
+            .then(self.normalization().total_cmp(&other.normalization()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::exponentially_modified_gaussian::ExponentiallyModifiedGaussian;
+    use super::gaussian::Gaussian;
+    use super::lineshape::Lineshape;
+    use super::lorentzian::Lorentzian;
+    use super::pseudo_voigt::PseudoVoigt;
+    use super::RenormalizedDistribution;
+
+    /// Check that the CDF of `distribution` is the integral of its PDF, is non-decreasing, and
+    /// tends to its normalization.
+    fn assert_consistent<D: RenormalizedDistribution>(distribution: D) {
+        const STEPS_PER_FWHM: usize = 200;
+        /// In FWHMs, on either side of the mean.
+        const EXTENT: usize = 20;
+
+        let (μ, fwhm) = (distribution.μ(), distribution.fwhm());
+        let normalization = distribution.normalization();
+        let start = μ - EXTENT as f64 * fwhm;
+        let step = fwhm / STEPS_PER_FWHM as f64;
+        let mut integral = 0.;
+        let mut prev_cdf = distribution.evaluate_cdf(start);
+        let steps = 2 * EXTENT * STEPS_PER_FWHM;
+        for i in 1..=steps {
+            let (a, b) = (start + (i - 1) as f64 * step, start + i as f64 * step);
+            // Trapezoidal rule.
+            integral += 0.5 * step * (distribution.evaluate(a) + distribution.evaluate(b));
+            let cdf = distribution.evaluate_cdf(b);
+            assert!(cdf >= prev_cdf, "CDF decreases at {b}");
+            prev_cdf = cdf;
+            approx::assert_abs_diff_eq!(
+                integral,
+                cdf - distribution.evaluate_cdf(start),
+                epsilon = 1e-5 * normalization
+            );
+        }
+        approx::assert_abs_diff_eq!(
+            distribution.evaluate_cdf(μ + 1e9 * fwhm),
+            normalization,
+            epsilon = 1e-6 * normalization
+        );
+        approx::assert_abs_diff_eq!(
+            distribution.evaluate_cdf(μ - 1e9 * fwhm),
+            0.,
+            epsilon = 1e-6 * normalization
+        );
+    }
+
+    #[test]
+    fn pdf_cdf_consistency() {
+        for (μ, fwhm, normalization) in [(0., 1., 1.), (-3.2, 0.01, 2.5), (150., 12., 0.3)] {
+            assert_consistent(Gaussian::with_fwhm_normalized(μ, fwhm, normalization));
+            assert_consistent(Lorentzian::with_fwhm_normalized(μ, fwhm, normalization));
+            for skew in [0., 0.05, 0.5, 3.] {
+                assert_consistent(ExponentiallyModifiedGaussian::with_fwhm_skew_normalized(
+                    μ,
+                    fwhm,
+                    skew,
+                    normalization,
+                ));
+            }
+            for η in [0., 0.3, 1.] {
+                assert_consistent(PseudoVoigt::with_fwhm_mixing_normalized(
+                    μ,
+                    fwhm,
+                    η,
+                    normalization,
+                ));
+            }
+            for lineshape in Lineshape::ALL {
+                assert_consistent(lineshape.with_fwhm_normalized(μ, fwhm, normalization));
+            }
+        }
+    }
 }
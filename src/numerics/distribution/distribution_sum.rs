@@ -2,7 +2,7 @@ use std::ops::RangeInclusive;
 
 use itertools::Itertools;
 
-use crate::numerics::distribution::RenormalizedDistribution;
+use crate::numerics::distribution::{RenormalizedDistribution, COINCIDENCE_TOLERANCE};
 
 #[derive(Clone, PartialEq, Debug)]
 /// A linear combination of individual distributions.
@@ -11,12 +11,17 @@ pub struct DistributionSum<D>(Vec<D>);
 impl<D: RenormalizedDistribution> FromIterator<D> for DistributionSum<D> {
     fn from_iter<T: IntoIterator<Item = D>>(iter: T) -> Self {
         let mut distributions = iter.into_iter().collect_vec();
-        distributions.sort_by(|a, b| a.μ().total_cmp(&b.μ()));
+        distributions.sort_by(D::cmp_position);
         Self(distributions)
     }
 }
 
 impl<D: RenormalizedDistribution> DistributionSum<D> {
+    /// Extent, in FWHMs, around each component that is sampled densely by
+    /// [`DistributionSum::adaptive_sample_grid`].
+    const DENSE_EXTENT: f64 = 4.;
+    const MIN_SAMPLES_PER_COMPONENT: usize = 8;
+
     /// Iterate over the individual distributions of the sum.
     pub fn components(&self) -> impl Iterator<Item = &D> {
         self.0.iter()
@@ -24,12 +29,16 @@ impl<D: RenormalizedDistribution> DistributionSum<D> {
 
     #[must_use]
     pub fn evaluate(&self, x: f64) -> f64 {
-        self.components().map(|g| g.evaluate(x)).sum()
+        let value = self.components().map(|g| g.evaluate(x)).sum();
+        debug_assert!(f64::is_finite(value), "waveform is not finite at {x}");
+        value
     }
 
     #[must_use]
     pub fn evaluate_cdf(&self, x: f64) -> f64 {
-        self.components().map(|g| g.evaluate_cdf(x)).sum()
+        let value = self.components().map(|g| g.evaluate_cdf(x)).sum();
+        debug_assert!(f64::is_finite(value), "integral is not finite at {x}");
+        value
     }
 
     #[must_use]
@@ -42,6 +51,104 @@ impl<D: RenormalizedDistribution> DistributionSum<D> {
             .map_or(0.0..=0.0, |(l, r)| l..=r)
     }
 
+    #[must_use]
+    /// Choose about `budget` points within `range` at which to sample the sum for display. A fifth
+    /// are spread uniformly; the remainder are divided among the components in view and placed
+    /// within a few FWHMs of their means, where the sum varies most rapidly. The points are
+    /// sorted and unique.
+    pub fn adaptive_sample_grid(&self, range: RangeInclusive<f64>, budget: usize) -> Vec<f64> {
+        fn uniform(lo: f64, hi: f64, n: usize) -> impl Iterator<Item = f64> {
+            let n = n.max(2);
+            let step = (hi - lo) / (n - 1) as f64;
+            (0..n).map(move |i| lo + i as f64 * step)
+        }
+
+        let (min, max) = range.into_inner();
+        let in_view = self
+            .components()
+            .map(|g| g.extent_by_fwhm(Self::DENSE_EXTENT).into_inner())
+            .filter(|&(lo, hi)| hi >= min && lo <= max)
+            .collect_vec();
+        let baseline = budget / 5;
+        let per_component = (budget - baseline)
+            .checked_div(in_view.len())
+            .unwrap_or(0)
+            .max(Self::MIN_SAMPLES_PER_COMPONENT);
+
+        let mut grid = uniform(min, max, baseline).collect_vec();
+        for (lo, hi) in in_view {
+            grid.extend(uniform(lo.max(min), hi.min(max), per_component));
+        }
+        grid.sort_by(f64::total_cmp);
+        grid.dedup();
+        grid
+    }
+
+    #[must_use]
+    /// Sample the sum as if digitized with a resolution of `bin_width`: the part of `range`
+    /// covered by whole bins, aligned to multiples of `bin_width`, is divided into bins, each of
+    /// which is represented by the mean of the sum over the bin, at its center. Empty if
+    /// `bin_width` is not positive, or any bound is not finite.
+    pub fn rebin(&self, range: RangeInclusive<f64>, bin_width: f64) -> Vec<[f64; 2]> {
+        let (min, max) = range.into_inner();
+        if !(bin_width > 0. && bin_width.is_finite() && min.is_finite() && max.is_finite()) {
+            return Vec::new();
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        let (first, last) = (
+            (min / bin_width).ceil() as i64,
+            (max / bin_width).floor() as i64,
+        );
+        (first..last)
+            .map(|i| {
+                let (lo, hi) = (i as f64 * bin_width, (i + 1) as f64 * bin_width);
+                let mean = (self.evaluate_cdf(hi) - self.evaluate_cdf(lo)) / bin_width;
+                [0.5 * (lo + hi), mean]
+            })
+            .collect()
+    }
+
+    #[must_use]
+    /// An equivalent sum in which coincident components (see
+    /// [`RenormalizedDistribution::coincides_with`]) are combined: each is merged into the first
+    /// earlier component with which it coincides.
+    pub fn merge_coincident(&self) -> Self {
+        let mut merged: Vec<D> = Vec::with_capacity(self.0.len());
+        for &component in self.components() {
+            // The components are sorted by mean, so only the last few merged could coincide with
+            // this one, however the jitter has ordered them.
+            let reach = 2. * COINCIDENCE_TOLERANCE * component.fwhm();
+            let partner = merged
+                .iter_mut()
+                .rev()
+                .take_while(|earlier| component.μ() - earlier.μ() <= reach)
+                .find(|earlier| earlier.coincides_with(&component));
+            match partner {
+                Some(earlier) => {
+                    *earlier = earlier
+                        .with_normalization(earlier.normalization() + component.normalization());
+                }
+                None => merged.push(component),
+            }
+        }
+        Self(merged)
+    }
+
+    #[must_use]
+    /// The intensity-weighted mean position (first moment) of the sum, or `None` if it has no
+    /// intensity. This is the normalization-weighted mean of the
+    /// [means](RenormalizedDistribution::mean) of the components, even for distributions (_e.g._,
+    /// the Lorentzian) whose first moment is, strictly, undefined.
+    pub fn center_of_mass(&self) -> Option<f64> {
+        let (moment, total) = self.components().fold((0., 0.), |(moment, total), g| {
+            (
+                moment + g.normalization() * g.mean(),
+                total + g.normalization(),
+            )
+        });
+        (total > 0.).then_some(moment / total)
+    }
+
     #[must_use]
     /// Give an _estimate_ of the max value of the sum, by evaluating the sum at the maxima
     /// (i.e., means) of the components.
@@ -52,3 +159,147 @@ impl<D: RenormalizedDistribution> DistributionSum<D> {
             .unwrap_or(0.)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::DistributionSum;
+    use crate::numerics::distribution::lorentzian::Lorentzian;
+    use crate::numerics::distribution::RenormalizedDistribution;
+
+    #[test]
+    fn coincident_components() {
+        let fwhm = 0.01;
+        let components = [(0.1, 1.), (0.3, 1.), (0.1 + 1e-12, 2.), (0.1 - 1e-12, 0.5)]
+            .map(|(μ, normalization)| Lorentzian::with_fwhm_normalized(μ, fwhm, normalization));
+        let sum = components.into_iter().collect::<DistributionSum<_>>();
+        // Coincident components are ordered by their jitter, and merged regardless of it.
+        let normalizations = sum
+            .components()
+            .map(|g| g.normalization)
+            .collect::<Vec<_>>();
+        approx::assert_abs_diff_eq!(&normalizations[..], &[0.5, 1., 2., 1.][..]);
+        let reversed = components.into_iter().rev().collect::<DistributionSum<_>>();
+        assert_eq!(sum, reversed);
+
+        let merged = sum.merge_coincident();
+        let normalizations = merged
+            .components()
+            .map(|g| g.normalization)
+            .collect::<Vec<_>>();
+        approx::assert_abs_diff_eq!(&normalizations[..], &[3.5, 1.][..]);
+        for x in [-1., 0.1, 0.2, 0.3] {
+            approx::assert_relative_eq!(merged.evaluate(x), sum.evaluate(x), max_relative = 1e-9);
+        }
+    }
+
+    #[test]
+    fn chained_coincidence() {
+        // Each component coincides with its neighbors, but the outer two do not coincide.
+        let fwhm = 1.;
+        let components = [(0., 1.), (0.8e-6, 2.), (1.6e-6, 4.), (0.8e-6, 8.)]
+            .map(|(μ, normalization)| Lorentzian::with_fwhm_normalized(μ, fwhm, normalization));
+        let sum = components.into_iter().collect::<DistributionSum<_>>();
+        let merged = sum.merge_coincident();
+        for permutation in components.into_iter().permutations(components.len()) {
+            let permuted = permutation.into_iter().collect::<DistributionSum<_>>();
+            assert_eq!(permuted, sum);
+            assert_eq!(permuted.merge_coincident(), merged);
+        }
+        let normalizations = merged
+            .components()
+            .map(|g| g.normalization)
+            .collect::<Vec<_>>();
+        approx::assert_abs_diff_eq!(&normalizations[..], &[11., 4.][..]);
+    }
+
+    #[test]
+    fn identical_means() {
+        let components =
+            [(0.02, 1.), (0.01, 2.), (0.01, 1.), (0.03, 0.5)].map(|(fwhm, normalization)| {
+                Lorentzian::with_fwhm_normalized(0.2, fwhm, normalization)
+            });
+        let sum = components.into_iter().collect::<DistributionSum<_>>();
+        for permutation in components.into_iter().permutations(components.len()) {
+            assert_eq!(permutation.into_iter().collect::<DistributionSum<_>>(), sum);
+        }
+        let shapes = sum
+            .components()
+            .flat_map(|g| [g.fwhm(), g.normalization])
+            .collect::<Vec<_>>();
+        approx::assert_abs_diff_eq!(
+            &shapes[..],
+            &[0.01, 1., 0.01, 2., 0.02, 1., 0.03, 0.5][..],
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn center_of_mass() {
+        let sum = [(-0.1, 1.), (0.2, 2.), (0.5, 1.)]
+            .into_iter()
+            .map(|(μ, normalization)| Lorentzian::with_fwhm_normalized(μ, 0.05, normalization))
+            .collect::<DistributionSum<_>>();
+        approx::assert_relative_eq!(sum.center_of_mass().unwrap(), 0.2);
+        assert!(DistributionSum::<Lorentzian>::from_iter([])
+            .center_of_mass()
+            .is_none());
+    }
+
+    #[test]
+    fn rebinning_preserves_area() {
+        let sum = [-0.3, 0.2]
+            .into_iter()
+            .map(|μ| Lorentzian::with_fwhm_normalized(μ, 0.05, 1.))
+            .collect::<DistributionSum<_>>();
+        let bin_width = 0.04;
+        let bins = sum.rebin(-1.01..=1.01, bin_width);
+        assert_eq!(bins.len(), 50);
+        approx::assert_abs_diff_eq!(bins[0][0], -0.98, epsilon = 1e-12);
+        let area = bins.iter().map(|[_, y]| y * bin_width).sum::<f64>();
+        approx::assert_relative_eq!(
+            area,
+            sum.evaluate_cdf(1.) - sum.evaluate_cdf(-1.),
+            max_relative = 1e-9
+        );
+        // Coarse bins flatten the peaks.
+        assert!(bins.iter().all(|&[_, y]| y < sum.max()));
+
+        for bin_width in [0., -0.04, f64::NAN, f64::INFINITY] {
+            assert!(sum.rebin(-1.0..=1.0, bin_width).is_empty(), "{bin_width}");
+        }
+        assert!(sum.rebin(f64::NEG_INFINITY..=1.0, 0.04).is_empty());
+        assert!(sum.rebin(-1.0..=f64::NAN, 0.04).is_empty());
+        assert!(sum.rebin(1.0..=-1.0, 0.04).is_empty());
+    }
+
+    #[test]
+    fn adaptive_sample_grid_resolves_narrow_peaks() {
+        let sum = [-0.6, 0.05, 0.052, 0.7]
+            .into_iter()
+            .map(|μ| Lorentzian::with_fwhm_normalized(μ, 1e-3, 1.))
+            .collect::<DistributionSum<_>>();
+        let grid = sum.adaptive_sample_grid(-1.0..=1.0, 2000);
+        assert!(grid.windows(2).all(|w| w[0] < w[1]));
+        let samples = grid
+            .iter()
+            .map(|&x| (x, sum.evaluate(x)))
+            .collect::<Vec<_>>();
+
+        // Compare the linear interpolant, as drawn, against dense uniform sampling.
+        let max = sum.max();
+        for i in 0..=200_000 {
+            let x = -1. + f64::from(i) * 1e-5;
+            let right = samples
+                .partition_point(|&(sx, _)| sx < x)
+                .clamp(1, samples.len() - 1);
+            let ((x0, y0), (x1, y1)) = (samples[right - 1], samples[right]);
+            let interpolated = y0 + (y1 - y0) * (x - x0) / (x1 - x0);
+            assert!(
+                (interpolated - sum.evaluate(x)).abs() < 0.01 * max,
+                "poorly resolved at {x}"
+            );
+        }
+    }
+}
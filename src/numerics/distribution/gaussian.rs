@@ -1,6 +1,7 @@
 use std::f64::consts::FRAC_1_SQRT_2;
 
 use super::RenormalizedDistribution;
+use crate::numerics;
 use crate::numerics::error_function::erfc;
 
 pub const FRAC_1_SQRT_2PI: f64 = 0.398_942_280_4;
@@ -21,9 +22,16 @@ impl Gaussian {
 impl RenormalizedDistribution for Gaussian {
     fn with_fwhm_normalized(μ: f64, fwhm: f64, normalization: f64) -> Self {
         Self {
-            μ,
-            σ: fwhm / Self::FWHM_FOR_σ,
-            normalization,
+            μ: numerics::finite_or(μ, 0.),
+            σ: numerics::sanitize_width(fwhm) / Self::FWHM_FOR_σ,
+            normalization: numerics::finite_or(normalization, 0.),
+        }
+    }
+
+    fn with_normalization(self, normalization: f64) -> Self {
+        Self {
+            normalization: numerics::finite_or(normalization, 0.),
+            ..self
         }
     }
 
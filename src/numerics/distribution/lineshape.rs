@@ -0,0 +1,255 @@
+use std::cmp::Ordering;
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+
+use super::exponentially_modified_gaussian::ExponentiallyModifiedGaussian;
+use super::gaussian::Gaussian;
+use super::lorentzian::Lorentzian;
+use super::pseudo_voigt::PseudoVoigt;
+use super::RenormalizedDistribution;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default, Serialize, Deserialize)]
+/// The functional form of a peak, selectable at runtime.
+pub enum Lineshape {
+    /// The natural lineshape of a freely-decaying signal.
+    #[default]
+    Lorentzian,
+    Gaussian,
+    /// A blend of a Lorentzian and a Gaussian, as for real lines.
+    PseudoVoigt,
+    /// A Gaussian with an exponential tail to one side: an artifact of poor shimming.
+    ExponentiallyModifiedGaussian,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+/// A distribution of any [`Lineshape`], such that components of different shapes can be summed
+/// into a single [`DistributionSum`](super::distribution_sum::DistributionSum).
+pub enum AnyDistribution {
+    Lorentzian(Lorentzian),
+    Gaussian(Gaussian),
+    PseudoVoigt(PseudoVoigt),
+    ExponentiallyModifiedGaussian(ExponentiallyModifiedGaussian),
+}
+
+impl Lineshape {
+    pub const ALL: [Self; 4] = [
+        Self::Lorentzian,
+        Self::Gaussian,
+        Self::PseudoVoigt,
+        Self::ExponentiallyModifiedGaussian,
+    ];
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Lorentzian => "Lorentzian",
+            Self::Gaussian => "Gaussian",
+            Self::PseudoVoigt => "Pseudo-Voigt",
+            Self::ExponentiallyModifiedGaussian => "Skewed (EMG)",
+        }
+    }
+
+    #[must_use]
+    /// Whether the lineshape is symmetric about its mean, such that a first-order multiplet drawn
+    /// with it is symmetric about its center.
+    pub fn is_symmetric(self) -> bool {
+        self != Self::ExponentiallyModifiedGaussian
+    }
+
+    #[must_use]
+    /// A distribution of this shape. Skewed shapes are of
+    /// [`ExponentiallyModifiedGaussian::DEFAULT_SKEW`], and mixed shapes of
+    /// [`PseudoVoigt::DEFAULT_MIXING`].
+    pub fn with_fwhm_normalized(self, μ: f64, fwhm: f64, normalization: f64) -> AnyDistribution {
+        self.with_fwhm_shape_normalized(
+            μ,
+            fwhm,
+            ExponentiallyModifiedGaussian::DEFAULT_SKEW,
+            PseudoVoigt::DEFAULT_MIXING,
+            normalization,
+        )
+    }
+
+    #[must_use]
+    /// A distribution of this shape whose tail, if it is skewed, decays over `skew` FWHMs, and
+    /// whose Lorentzian fraction, if it is mixed, is `η`. Each parameter is ignored by the other
+    /// shapes.
+    pub fn with_fwhm_shape_normalized(
+        self,
+        μ: f64,
+        fwhm: f64,
+        skew: f64,
+        η: f64,
+        normalization: f64,
+    ) -> AnyDistribution {
+        match self {
+            Self::Lorentzian => AnyDistribution::Lorentzian(Lorentzian::with_fwhm_normalized(
+                μ,
+                fwhm,
+                normalization,
+            )),
+            Self::Gaussian => {
+                AnyDistribution::Gaussian(Gaussian::with_fwhm_normalized(μ, fwhm, normalization))
+            }
+            Self::PseudoVoigt => AnyDistribution::PseudoVoigt(
+                PseudoVoigt::with_fwhm_mixing_normalized(μ, fwhm, η, normalization),
+            ),
+            Self::ExponentiallyModifiedGaussian => AnyDistribution::ExponentiallyModifiedGaussian(
+                ExponentiallyModifiedGaussian::with_fwhm_skew_normalized(
+                    μ,
+                    fwhm,
+                    skew,
+                    normalization,
+                ),
+            ),
+        }
+    }
+}
+
+impl AnyDistribution {
+    #[must_use]
+    pub fn lineshape(&self) -> Lineshape {
+        match self {
+            Self::Lorentzian(_) => Lineshape::Lorentzian,
+            Self::Gaussian(_) => Lineshape::Gaussian,
+            Self::PseudoVoigt(_) => Lineshape::PseudoVoigt,
+            Self::ExponentiallyModifiedGaussian(_) => Lineshape::ExponentiallyModifiedGaussian,
+        }
+    }
+}
+
+macro_rules! dispatch {
+    ($self:ident, $d:ident => $expr:expr) => {
+        match $self {
+            AnyDistribution::Lorentzian($d) => $expr,
+            AnyDistribution::Gaussian($d) => $expr,
+            AnyDistribution::PseudoVoigt($d) => $expr,
+            AnyDistribution::ExponentiallyModifiedGaussian($d) => $expr,
+        }
+    };
+}
+
+impl RenormalizedDistribution for AnyDistribution {
+    /// Construct a distribution of the default [`Lineshape`]. Use
+    /// [`Lineshape::with_fwhm_normalized`] to choose the shape.
+    fn with_fwhm_normalized(μ: f64, fwhm: f64, normalization: f64) -> Self {
+        Lineshape::default().with_fwhm_normalized(μ, fwhm, normalization)
+    }
+
+    fn with_normalization(self, normalization: f64) -> Self {
+        match self {
+            Self::Lorentzian(d) => Self::Lorentzian(d.with_normalization(normalization)),
+            Self::Gaussian(d) => Self::Gaussian(d.with_normalization(normalization)),
+            Self::PseudoVoigt(d) => Self::PseudoVoigt(d.with_normalization(normalization)),
+            Self::ExponentiallyModifiedGaussian(d) => {
+                Self::ExponentiallyModifiedGaussian(d.with_normalization(normalization))
+            }
+        }
+    }
+
+    fn μ(&self) -> f64 {
+        dispatch!(self, d => d.μ())
+    }
+
+    fn fwhm(&self) -> f64 {
+        dispatch!(self, d => d.fwhm())
+    }
+
+    fn normalization(&self) -> f64 {
+        dispatch!(self, d => d.normalization())
+    }
+
+    fn mean(&self) -> f64 {
+        dispatch!(self, d => d.mean())
+    }
+
+    fn evaluate(&self, x: f64) -> f64 {
+        dispatch!(self, d => d.evaluate(x))
+    }
+
+    fn evaluate_cdf(&self, x: f64) -> f64 {
+        dispatch!(self, d => d.evaluate_cdf(x))
+    }
+
+    fn extent_by_fwhm(&self, n: f64) -> RangeInclusive<f64> {
+        dispatch!(self, d => d.extent_by_fwhm(n))
+    }
+
+    /// Distributions of different [`Lineshape`]s never coincide.
+    fn coincides_with(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Lorentzian(a), Self::Lorentzian(b)) => a.coincides_with(b),
+            (Self::Gaussian(a), Self::Gaussian(b)) => a.coincides_with(b),
+            (Self::PseudoVoigt(a), Self::PseudoVoigt(b)) => a.coincides_with(b),
+            (Self::ExponentiallyModifiedGaussian(a), Self::ExponentiallyModifiedGaussian(b)) => {
+                a.coincides_with(b)
+            }
+            _ => false,
+        }
+    }
+
+    /// Distributions of different [`Lineshape`]s are ordered by mean, then by lineshape, such that
+    /// the order remains total.
+    fn cmp_position(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Lorentzian(a), Self::Lorentzian(b)) => a.cmp_position(b),
+            (Self::Gaussian(a), Self::Gaussian(b)) => a.cmp_position(b),
+            (Self::PseudoVoigt(a), Self::PseudoVoigt(b)) => a.cmp_position(b),
+            (Self::ExponentiallyModifiedGaussian(a), Self::ExponentiallyModifiedGaussian(b)) => {
+                a.cmp_position(b)
+            }
+            _ => self
+                .μ()
+                .total_cmp(&other.μ())
+                .then(self.lineshape().cmp(&other.lineshape())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnyDistribution, Lineshape};
+    use crate::numerics::distribution::distribution_sum::DistributionSum;
+
+    #[test]
+    fn heterogeneous_integral() {
+        let sum = [
+            Lineshape::Lorentzian.with_fwhm_normalized(-0.5, 0.01, 2.),
+            Lineshape::Gaussian.with_fwhm_normalized(0.2, 0.1, 3.),
+            Lineshape::Gaussian.with_fwhm_normalized(0.25, 0.01, 1.),
+        ]
+        .into_iter()
+        .collect::<DistributionSum<_>>();
+        approx::assert_abs_diff_eq!(sum.evaluate_cdf(-1e6), 0., epsilon = 1e-6);
+        approx::assert_abs_diff_eq!(sum.evaluate_cdf(1e6), 6., epsilon = 1e-6);
+        // Only the narrow Lorentzian lies left of zero (less its tail).
+        approx::assert_abs_diff_eq!(sum.evaluate_cdf(0.), 2., epsilon = 0.05);
+    }
+
+    #[test]
+    fn heterogeneous_order() {
+        let components = [
+            // Of coincident means, ordered by lineshape alone.
+            Lineshape::Lorentzian.with_fwhm_normalized(0.1, 0.01, 1.),
+            Lineshape::Gaussian.with_fwhm_normalized(0.1, 0.01, 1.),
+            Lineshape::Gaussian.with_fwhm_normalized(-0.1, 0.01, 1.),
+        ];
+        let sum = components.into_iter().collect::<DistributionSum<_>>();
+        let reversed = components.into_iter().rev().collect::<DistributionSum<_>>();
+        assert_eq!(sum, reversed);
+        let lineshapes = sum
+            .components()
+            .map(AnyDistribution::lineshape)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lineshapes,
+            // The Lorentzian is declared first.
+            [
+                Lineshape::Gaussian,
+                Lineshape::Lorentzian,
+                Lineshape::Gaussian
+            ]
+        );
+    }
+}
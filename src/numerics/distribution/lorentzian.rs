@@ -1,6 +1,7 @@
 use std::f64::consts::FRAC_1_PI;
 
 use super::RenormalizedDistribution;
+use crate::numerics;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Lorentzian {
@@ -12,9 +13,16 @@ pub struct Lorentzian {
 impl RenormalizedDistribution for Lorentzian {
     fn with_fwhm_normalized(μ: f64, fwhm: f64, normalization: f64) -> Self {
         Self {
-            x0: μ,
-            γ: fwhm / 2.,
-            normalization,
+            x0: numerics::finite_or(μ, 0.),
+            γ: numerics::sanitize_width(fwhm) / 2.,
+            normalization: numerics::finite_or(normalization, 0.),
+        }
+    }
+
+    fn with_normalization(self, normalization: f64) -> Self {
+        Self {
+            normalization: numerics::finite_or(normalization, 0.),
+            ..self
         }
     }
 
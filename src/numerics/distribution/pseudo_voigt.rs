@@ -0,0 +1,136 @@
+use super::gaussian::Gaussian;
+use super::lorentzian::Lorentzian;
+use super::{RenormalizedDistribution, COINCIDENCE_TOLERANCE};
+use crate::numerics;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+/// A weighted sum of a Lorentzian and a Gaussian of the same FWHM, which approximates their
+/// convolution (the Voigt profile): the shape of real lines, broadened both by relaxation and by
+/// the inhomogeneity of the field.
+///
+/// Since both components are at half their height at the same points, the FWHM of the sum is
+/// exactly that shared FWHM.
+pub struct PseudoVoigt {
+    pub μ: f64,
+    pub fwhm: f64,
+    /// Fraction of the area in the Lorentzian component, in `[0, 1]`: one for a pure Lorentzian,
+    /// zero for a pure Gaussian.
+    pub η: f64,
+    pub normalization: f64,
+}
+
+impl PseudoVoigt {
+    /// Mixing used by [`RenormalizedDistribution::with_fwhm_normalized`].
+    pub const DEFAULT_MIXING: f64 = 0.5;
+
+    #[must_use]
+    /// A distribution centered at `μ` with a FWHM of `fwhm`, of which the fraction `η` is
+    /// Lorentzian. `η` is clamped to `[0, 1]`.
+    pub fn with_fwhm_mixing_normalized(μ: f64, fwhm: f64, η: f64, normalization: f64) -> Self {
+        Self {
+            μ: numerics::finite_or(μ, 0.),
+            fwhm: numerics::sanitize_width(fwhm),
+            η: numerics::finite_or(η, Self::DEFAULT_MIXING).clamp(0., 1.),
+            normalization: numerics::finite_or(normalization, 0.),
+        }
+    }
+
+    fn lorentzian(&self) -> Lorentzian {
+        Lorentzian::with_fwhm_normalized(self.μ, self.fwhm, self.η * self.normalization)
+    }
+
+    fn gaussian(&self) -> Gaussian {
+        Gaussian::with_fwhm_normalized(self.μ, self.fwhm, (1. - self.η) * self.normalization)
+    }
+}
+
+impl RenormalizedDistribution for PseudoVoigt {
+    /// Construct a distribution of [`Self::DEFAULT_MIXING`].
+    fn with_fwhm_normalized(μ: f64, fwhm: f64, normalization: f64) -> Self {
+        Self::with_fwhm_mixing_normalized(μ, fwhm, Self::DEFAULT_MIXING, normalization)
+    }
+
+    fn with_normalization(self, normalization: f64) -> Self {
+        Self {
+            normalization: numerics::finite_or(normalization, 0.),
+            ..self
+        }
+    }
+
+    fn μ(&self) -> f64 {
+        self.μ
+    }
+
+    fn fwhm(&self) -> f64 {
+        self.fwhm
+    }
+
+    fn normalization(&self) -> f64 {
+        self.normalization
+    }
+
+    fn evaluate(&self, x: f64) -> f64 {
+        self.lorentzian().evaluate(x) + self.gaussian().evaluate(x)
+    }
+
+    fn evaluate_cdf(&self, x: f64) -> f64 {
+        self.lorentzian().evaluate_cdf(x) + self.gaussian().evaluate_cdf(x)
+    }
+
+    /// Distributions of different mixings never coincide.
+    fn coincides_with(&self, other: &Self) -> bool {
+        let tolerance = COINCIDENCE_TOLERANCE * self.fwhm.max(other.fwhm);
+        (self.μ - other.μ).abs() <= tolerance
+            && (self.fwhm - other.fwhm).abs() <= tolerance
+            && (self.η - other.η).abs() <= COINCIDENCE_TOLERANCE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PseudoVoigt;
+    use crate::numerics::distribution::gaussian::Gaussian;
+    use crate::numerics::distribution::lorentzian::Lorentzian;
+    use crate::numerics::distribution::RenormalizedDistribution;
+
+    #[test]
+    fn pseudo_voigt_integral() {
+        const EXTENT: f64 = 2000.;
+        const STEPS_PER_FWHM: f64 = 40.;
+        let (μ, fwhm, normalization) = (1.5, 0.2, 3.);
+        for η in [0., 0.25, 0.5, 0.8, 1.] {
+            let distribution = PseudoVoigt::with_fwhm_mixing_normalized(μ, fwhm, η, normalization);
+            let step = fwhm / STEPS_PER_FWHM;
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let steps = (2. * EXTENT * STEPS_PER_FWHM) as u32;
+            let start = μ - EXTENT * fwhm;
+            let integral = (0..steps)
+                .map(|i| start + f64::from(i) * step)
+                .map(|x| 0.5 * step * (distribution.evaluate(x) + distribution.evaluate(x + step)))
+                .sum::<f64>();
+            // Only the far wings of the Lorentzian, about 1.6e-4 of its area, are left out.
+            approx::assert_abs_diff_eq!(integral, normalization, epsilon = 2e-4 * normalization);
+        }
+    }
+
+    #[test]
+    fn pseudo_voigt_limits() {
+        let (μ, fwhm, normalization) = (-0.4, 0.05, 2.);
+        let lorentzian = Lorentzian::with_fwhm_normalized(μ, fwhm, normalization);
+        let gaussian = Gaussian::with_fwhm_normalized(μ, fwhm, normalization);
+        let pure_lorentzian = PseudoVoigt::with_fwhm_mixing_normalized(μ, fwhm, 1., normalization);
+        // Out-of-range mixings are clamped.
+        let pure_gaussian = PseudoVoigt::with_fwhm_mixing_normalized(μ, fwhm, -2., normalization);
+        for x in [-0.6, -0.42, -0.4, -0.37, 0.] {
+            approx::assert_relative_eq!(pure_lorentzian.evaluate(x), lorentzian.evaluate(x));
+            approx::assert_relative_eq!(pure_gaussian.evaluate(x), gaussian.evaluate(x));
+        }
+        // The mixture is at half its height at the shared half-width.
+        let mixed = PseudoVoigt::with_fwhm_normalized(μ, fwhm, normalization);
+        approx::assert_relative_eq!(
+            mixed.evaluate(μ + 0.5 * fwhm),
+            0.5 * mixed.evaluate(μ),
+            max_relative = 1e-8
+        );
+    }
+}
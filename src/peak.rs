@@ -2,33 +2,78 @@ mod multiplet_cascade;
 
 use std::borrow::Cow;
 use std::collections::VecDeque;
+use std::fmt;
 
-pub use self::multiplet_cascade::{MultipletCascade, SplittingRelationship};
+use serde::{Deserialize, Serialize};
+
+pub use self::multiplet_cascade::{MultipletCascade, PeakletPath, SplittingRelationship};
 use crate::numerics;
+use crate::numerics::distribution::exponentially_modified_gaussian::ExponentiallyModifiedGaussian;
+use crate::numerics::distribution::lineshape::Lineshape;
+use crate::numerics::distribution::pseudo_voigt::PseudoVoigt;
 
 #[must_use]
 #[allow(clippy::doc_markdown)]
 /// Convert an NMR instrument frequency (MHz) to the corresponding magnetic field strength (T).
 pub fn mhz_to_tesla(frequency: f64) -> f64 {
-    #[allow(non_upper_case_globals)]
-    const γ_PROTON: f64 = 42.577_478_518; // MHz/T
     frequency / γ_PROTON
 }
 
+#[allow(non_upper_case_globals)]
+/// The gyromagnetic ratio of the proton divided by 2π, in MHz/T.
+pub const γ_PROTON: f64 = 42.577_478_518;
+
 #[must_use]
 #[allow(clippy::doc_markdown)]
 /// Convert an absolute shift in Hz to ppm at a given field strength in (MHz).
+///
+/// Frequencies below [`MIN_FREQUENCY`] (or not finite) are treated as such, and the result is always
+/// finite. Use [`validate_frequency`] to reject them instead.
 pub fn j_to_ppm(j: f64, frequency: f64) -> f64 {
-    j / frequency
+    let frequency = numerics::finite_or(frequency, MIN_FREQUENCY).max(MIN_FREQUENCY);
+    let ppm = numerics::finite_or(j / frequency, 0.);
+    debug_assert!(ppm.is_finite());
+    ppm
 }
 
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[must_use]
+#[allow(clippy::doc_markdown)]
+/// Convert a shift in ppm at one instrument frequency (MHz) into the ppm corresponding to the same
+/// absolute shift in Hz at another.
+pub fn rescale_ppm(ppm: f64, from_frequency: f64, to_frequency: f64) -> f64 {
+    j_to_ppm(ppm * from_frequency, to_frequency)
+}
+
+#[allow(clippy::doc_markdown)]
+/// The smallest instrument frequency (MHz) accepted by conversions. This is far below that of any
+/// practical spectrometer, but keeps shifts in ppm to a displayable magnitude.
+pub const MIN_FREQUENCY: f64 = 1.;
+
+#[allow(clippy::doc_markdown)]
+/// Check that `frequency` (MHz) is usable as an instrument frequency.
+///
+/// # Errors
+/// If `frequency` is NaN, infinite, or below [`MIN_FREQUENCY`].
+pub fn validate_frequency(frequency: f64) -> Result<f64, InvalidFrequency> {
+    if !frequency.is_finite() {
+        Err(InvalidFrequency::NotFinite(frequency))
+    } else if frequency < MIN_FREQUENCY {
+        Err(InvalidFrequency::TooLow(frequency))
+    } else {
+        Ok(frequency)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
 /// A single type of proton coupled to a [`Peak`].
 pub struct Splitter {
     /// Number of chemically equivalent protons.
     pub n: u32,
     /// Coupling constant in Hz.
     pub j: f64,
+    /// Whether the splitter is excluded from the multiplet, while being kept in its [`Peak`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub muted: bool,
 }
 
 #[derive(Clone, Copy, PartialEq, Debug)]
@@ -43,26 +88,140 @@ pub struct Peaklet {
 #[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
 pub struct FractionalStageIndex(f64);
 
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+/// The reason a value was rejected by [`validate_frequency`].
+pub enum InvalidFrequency {
+    /// The frequency was NaN or infinite.
+    NotFinite(f64),
+    /// The frequency was below [`MIN_FREQUENCY`].
+    TooLow(f64),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+/// The reason a value could not be made into a [`FractionalStageIndex`].
+pub enum InvalidStageIndex {
+    /// The index was NaN or infinite.
+    NotFinite(f64),
+    /// The index was below zero, _i.e._, before the first stage.
+    Negative(f64),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+/// The order in which [`Peak::sort_by_j`] arranges splitters, and hence the stages of the splitting
+/// diagram.
+pub enum SortOrder {
+    Ascending,
+    /// Largest couplings first, _i.e._, outermost in the splitting diagram.
+    #[default]
+    Descending,
+}
+
+impl SortOrder {
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ascending => "ascending",
+            Self::Descending => "descending",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+/// The convention by which simple multiplets are named, as these differ between fields and
+/// journals. Beyond the patterns a convention names, multiplets are given by their line count.
+pub enum PatternNaming {
+    /// Up to the heptet, with single-letter abbreviations up to the hextet.
+    #[default]
+    Concise,
+    /// [`Self::Concise`], continued up to the decet.
+    Extended,
+    /// Latin-derived names (quintet, sextet, septet) up to the decet, as recommended by some
+    /// journals.
+    Latin,
+}
+
+impl PatternNaming {
+    pub const ALL: [Self; 3] = [Self::Concise, Self::Extended, Self::Latin];
+    const CONCISE_ABBREVIATIONS: [&str; 7] = ["s", "d", "t", "q", "p", "h", "hept"];
+    const CONCISE_NAMES: [&str; 7] = [
+        "singlet", "doublet", "triplet", "quartet", "pentet", "hextet", "heptet",
+    ];
+    const EXTENDED_ABBREVIATIONS: [&str; 10] =
+        ["s", "d", "t", "q", "p", "h", "hept", "oct", "non", "dec"];
+    const EXTENDED_NAMES: [&str; 10] = [
+        "singlet", "doublet", "triplet", "quartet", "pentet", "hextet", "heptet", "octet", "nonet",
+        "decet",
+    ];
+    const LATIN_ABBREVIATIONS: [&str; 10] = [
+        "s", "d", "t", "q", "quint", "sext", "sept", "oct", "non", "dec",
+    ];
+    const LATIN_NAMES: [&str; 10] = [
+        "singlet", "doublet", "triplet", "quartet", "quintet", "sextet", "septet", "octet",
+        "nonet", "decet",
+    ];
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Concise => "Concise (to hept)",
+            Self::Extended => "Extended (to dec)",
+            Self::Latin => "Latin (quint, sext, …)",
+        }
+    }
+
+    /// The abbreviations of the patterns of 1, 2, ... lines.
+    fn abbreviations(self) -> &'static [&'static str] {
+        match self {
+            Self::Concise => &Self::CONCISE_ABBREVIATIONS,
+            Self::Extended => &Self::EXTENDED_ABBREVIATIONS,
+            Self::Latin => &Self::LATIN_ABBREVIATIONS,
+        }
+    }
+
+    /// The names of the patterns of 1, 2, ... lines.
+    fn names(self) -> &'static [&'static str] {
+        match self {
+            Self::Concise => &Self::CONCISE_NAMES,
+            Self::Extended => &Self::EXTENDED_NAMES,
+            Self::Latin => &Self::LATIN_NAMES,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
 /// A descriptor of a peak corresponding to a single proton type coupled to arbitrary [`Splitter`]s.
 pub struct Peak {
+    /// User-provided name of the proton environment (_e.g._, "OCH₂"). If empty, the pattern name is
+    /// used instead; see [`Peak::display_label`].
+    pub label: String,
+    /// Number of chemically equivalent protons giving rise to the peak, which determines its
+    /// integration. Contrast with [`Splitter::n`], which counts coupling partners.
+    pub proton_count: u32,
     /// List of coupled proton types.
     pub splitters: Vec<Splitter>,
     /// Full width at half maximum of the peak, in Hz.
     pub fwhm: f64,
+    /// Shape of each line of the peak. Each peak carries its own, such that peaks of different
+    /// shapes may be summed.
+    pub lineshape: Lineshape,
+    /// Decay length of the tail of each line, in FWHMs, if the [`Peak::lineshape`] is skewed.
+    pub skew: f64,
+    /// Fraction of each line which is Lorentzian, if the [`Peak::lineshape`] is pseudo-Voigt.
+    pub mixing: f64,
 }
 
 impl Default for Splitter {
     fn default() -> Self {
-        Self { n: 1, j: 5.0 }
+        Self::new(1, 5.0)
     }
 }
 
 impl Splitter {
-    pub const PATTERN_ABBREVIATIONS: [&str; 7] = ["s", "d", "t", "q", "p", "h", "hept"];
-    pub const PATTERN_NAMES: [&str; 7] = [
-        "singlet", "doublet", "triplet", "quartet", "pentet", "hextet", "heptet",
-    ];
+    #[must_use]
+    pub const fn new(n: u32, j: f64) -> Self {
+        Self { n, j, muted: false }
+    }
 
     #[must_use]
     pub fn resultant_peaklet_count(&self) -> u32 {
@@ -70,9 +229,10 @@ impl Splitter {
     }
 
     #[must_use]
-    pub fn abbreviate_pattern(&self) -> Cow<'static, str> {
+    pub fn abbreviate_pattern(&self, naming: PatternNaming) -> Cow<'static, str> {
         // N.b. indexing: peak count = n + 1, but 0-indexing subtracts 1.
-        Self::PATTERN_ABBREVIATIONS
+        naming
+            .abbreviations()
             .get(self.n as usize)
             .copied()
             .map_or_else(
@@ -82,8 +242,8 @@ impl Splitter {
     }
 
     #[must_use]
-    pub fn name_pattern(&self) -> Option<&'static str> {
-        Self::PATTERN_NAMES.get(self.n as usize).copied()
+    pub fn name_pattern(&self, naming: PatternNaming) -> Option<&'static str> {
+        naming.names().get(self.n as usize).copied()
     }
 
     pub fn peak_ratios(&self) -> impl Iterator<Item = u32> {
@@ -103,11 +263,52 @@ impl Peaklet {
     }
 }
 
+impl fmt::Display for InvalidFrequency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFinite(frequency) => write!(f, "frequency {frequency} MHz is not finite"),
+            Self::TooLow(frequency) => write!(
+                f,
+                "frequency {frequency} MHz is below the minimum of {MIN_FREQUENCY} MHz"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InvalidFrequency {}
+
+impl fmt::Display for InvalidStageIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFinite(index) => write!(f, "stage index {index} is not finite"),
+            Self::Negative(index) => write!(f, "stage index {index} is negative"),
+        }
+    }
+}
+
+impl std::error::Error for InvalidStageIndex {}
+
 impl FractionalStageIndex {
     #[must_use]
+    /// # Panics:
+    /// If `index` is not a finite, non-negative number. See [`Self::try_new`].
     pub fn new(index: f64) -> FractionalStageIndex {
-        assert!(index >= 0.0);
-        Self(index)
+        match Self::try_new(index) {
+            Ok(index) => index,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// # Errors
+    /// If `index` is NaN, infinite, or negative.
+    pub fn try_new(index: f64) -> Result<FractionalStageIndex, InvalidStageIndex> {
+        if !index.is_finite() {
+            Err(InvalidStageIndex::NotFinite(index))
+        } else if index < 0.0 {
+            Err(InvalidStageIndex::Negative(index))
+        } else {
+            Ok(Self(index))
+        }
     }
 
     #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
@@ -139,59 +340,193 @@ impl FractionalStageIndex {
 impl Default for Peak {
     fn default() -> Self {
         Self {
+            label: String::new(),
+            proton_count: 1,
             splitters: vec![],
             fwhm: 0.5,
+            lineshape: Lineshape::default(),
+            skew: ExponentiallyModifiedGaussian::DEFAULT_SKEW,
+            mixing: PseudoVoigt::DEFAULT_MIXING,
         }
     }
 }
 
 impl Peak {
+    /// Coupling constants (Hz) closer than this are considered equal by [`Self::equal_j_groups`].
+    /// This is half the precision to which they are displayed.
+    pub const EQUAL_J_TOLERANCE: f64 = 0.05;
+
+    /// The splitters which are not [muted](Splitter::muted), in order.
+    pub fn active_splitters(&self) -> impl Iterator<Item = &Splitter> {
+        self.splitters.iter().filter(|splitter| !splitter.muted)
+    }
+
+    #[must_use]
+    /// This peak, without its muted splitters.
+    pub fn unmuted(&self) -> Self {
+        Self {
+            splitters: self.active_splitters().copied().collect(),
+            ..self.clone()
+        }
+    }
+
     pub fn total_peaklet_count(&self) -> u32 {
-        self.splitters
-            .iter()
+        self.active_splitters()
             .map(Splitter::resultant_peaklet_count)
             .product()
     }
 
+    #[must_use]
+    /// The number of peaklets at each stage of the splitting, from the base singlet to the
+    /// [`Peak::total_peaklet_count`]: each active splitter multiplies the count of the previous
+    /// stage by its [`Splitter::resultant_peaklet_count`].
+    pub fn stage_peaklet_counts(&self) -> Vec<u32> {
+        std::iter::once(1)
+            .chain(self.active_splitters().scan(1, |count, splitter| {
+                *count *= splitter.resultant_peaklet_count();
+                Some(*count)
+            }))
+            .collect()
+    }
+
     #[must_use]
     #[allow(clippy::cast_possible_truncation)]
+    /// The number of stages of the splitting, including the base singlet. Muted splitters do not
+    /// contribute stages.
     pub fn stage_count(&self) -> u32 {
-        self.splitters.len() as u32 + 1
+        self.active_splitters().count() as u32 + 1
+    }
+
+    #[must_use]
+    /// The user-provided [`Peak::label`], falling back to the pattern name.
+    pub fn display_label(&self, naming: PatternNaming) -> String {
+        if self.label.trim().is_empty() {
+            self.name(naming).unwrap_or("<complex>".to_owned())
+        } else {
+            self.label.clone()
+        }
     }
 
     #[must_use]
-    pub fn name(&self) -> Option<String> {
-        let splitter_count = self.splitters.len();
-        if splitter_count == 0 {
-            return Some(Splitter::PATTERN_ABBREVIATIONS[0].to_owned());
+    /// The abbreviated name of the pattern, _e.g._, `td`, if it has one. Patterns can only be
+    /// named by single letters, except that, beyond [`PatternNaming::Concise`], a single splitting
+    /// is named by any abbreviation of `naming`.
+    pub fn name(&self, naming: PatternNaming) -> Option<String> {
+        let mut splitters = self.active_splitters().peekable();
+        let Some(first) = splitters.next() else {
+            return Some(naming.abbreviations()[0].to_owned());
+        };
+        if naming != PatternNaming::Concise && splitters.peek().is_none() {
+            return match first.abbreviate_pattern(naming) {
+                Cow::Borrowed(name) => Some(name.to_owned()),
+                Cow::Owned(_) => None,
+            };
         }
-        self.splitters
-            .iter()
-            .map(Splitter::abbreviate_pattern)
+        self.active_splitters()
+            .map(|splitter| splitter.abbreviate_pattern(naming))
             .map(|name| (name.len() == 1 && matches!(name, Cow::Borrowed(_))).then_some(name))
             .collect()
     }
 
-    pub fn sort_by_j(&mut self) {
-        self.splitters.sort_by(|a, b| b.j.total_cmp(&a.j));
+    /// Sort splitters by their coupling constants. Ties are broken by the number of protons, in
+    /// the same order, such that the result is deterministic.
+    pub fn sort_by_j(&mut self, order: SortOrder) {
+        self.splitters.sort_by(|a, b| {
+            let ordering = a.j.total_cmp(&b.j).then(a.n.cmp(&b.n));
+            match order {
+                SortOrder::Ascending => ordering,
+                SortOrder::Descending => ordering.reverse(),
+            }
+        });
+    }
+
+    #[must_use]
+    /// The smallest ratio Δν/J among the splitters, where Δν (Hz) is the separation in shift
+    /// between the observed protons and their coupling partners. The first-order treatment used
+    /// here is only accurate when this is large (_i.e._, for weak coupling). `None` if there are
+    /// no splitters.
+    pub fn weak_coupling_ratio(&self, partner_separation: f64) -> Option<f64> {
+        self.active_splitters()
+            .map(|splitter| partner_separation.abs() / splitter.j.abs().max(f64::MIN_POSITIVE))
+            .reduce(f64::min)
     }
 
     #[must_use]
+    /// For each splitter, the index of the group of active splitters sharing its coupling constant
+    /// (to within [`Self::EQUAL_J_TOLERANCE`]), in order of first appearance. Together, such
+    /// splitters produce an apparent higher multiplet. Splitters whose J is unique, and muted
+    /// splitters, are in no group.
+    pub fn equal_j_groups(&self) -> Vec<Option<usize>> {
+        let mut group_count = 0;
+        let mut groups = vec![None; self.splitters.len()];
+        for (i, splitter) in self.splitters.iter().enumerate() {
+            if splitter.muted || groups[i].is_some() {
+                continue;
+            }
+            let partners = (i + 1..self.splitters.len())
+                .filter(|&k| {
+                    let other = &self.splitters[k];
+                    !other.muted && (other.j - splitter.j).abs() <= Self::EQUAL_J_TOLERANCE
+                })
+                .collect::<Vec<_>>();
+            if partners.is_empty() {
+                continue;
+            }
+            for k in std::iter::once(i).chain(partners) {
+                groups[k] = Some(group_count);
+            }
+            group_count += 1;
+        }
+        groups
+    }
+
+    /// Set the coupling constant of every splitter to `j`.
+    pub fn set_all_j(&mut self, j: f64) {
+        for splitter in &mut self.splitters {
+            splitter.j = j;
+        }
+    }
+
+    #[must_use]
+    /// The peak as it appears `n` stages into the splitting. Muted splitters are dropped, and
+    /// stages beyond the last give the full peak (a singlet, if there are no splitters).
     pub fn nth_partial_peak(&self, n: FractionalStageIndex) -> Self {
-        let mut clone = self.clone();
+        let mut clone = self.unmuted();
         clone.splitters.truncate(n.total_stage_count());
         if let Some((idx, part)) = n.partial_and_index() {
             // Note that the splitters do not contain the base stage.
-            clone.splitters[idx - 1].j *= part;
+            if let Some(splitter) = clone.splitters.get_mut(idx - 1) {
+                splitter.j *= part;
+            }
         }
         clone
     }
 
     #[must_use]
+    /// The [`Peak::name`] of the pattern observed at each whole stage, from the base singlet to
+    /// the full multiplet.
+    pub fn stage_names(&self, naming: PatternNaming) -> Vec<String> {
+        (0..self.stage_count())
+            .map(|n| {
+                self.nth_partial_peak(FractionalStageIndex::new(f64::from(n)))
+                    .name(naming)
+                    .unwrap_or("<complex>".to_owned())
+            })
+            .collect()
+    }
+
+    #[must_use]
+    /// The splitting of this peak by each of its active splitters in turn.
     pub fn build_multiplet_cascade(&self) -> MultipletCascade {
+        if self.splitters.iter().any(|splitter| splitter.muted) {
+            return self.unmuted().build_multiplet_cascade();
+        }
         let mut cascade = MultipletCascade {
             stages: itertools::repeat_n(vec![], self.splitters.len() + 1).collect(),
             fwhm: self.fwhm,
+            lineshape: self.lineshape,
+            skew: self.skew,
+            mixing: self.mixing,
         };
 
         let mut queue: VecDeque<(Peaklet, &[Splitter])> = VecDeque::new();
@@ -217,6 +552,413 @@ impl Peak {
             }
         }
 
+        debug_assert!(
+            cascade.stages.iter().map(Vec::len).eq(self
+                .stage_peaklet_counts()
+                .into_iter()
+                .map(|count| count as usize)),
+            "stages of the cascade do not match the splitters",
+        );
         cascade
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        FractionalStageIndex, InvalidFrequency, InvalidStageIndex, PatternNaming, Peak, SortOrder,
+        Splitter, MIN_FREQUENCY,
+    };
+
+    #[test]
+    fn window_follows_field_strength() {
+        let peak = Peak {
+            proton_count: 2,
+            splitters: vec![Splitter::new(3, 7.)],
+            ..Default::default()
+        };
+        let (min, max) = peak.build_multiplet_cascade().final_span().into_inner();
+        // A window framing the quartet at 600 MHz, rescaled to each end of the instrument range.
+        let window = (
+            super::j_to_ppm(1.5 * min, 600.),
+            super::j_to_ppm(1.5 * max, 600.),
+        );
+        for field_strength in [40., 1200.] {
+            let (left, right) = (
+                super::rescale_ppm(window.0, 600., field_strength),
+                super::rescale_ppm(window.1, 600., field_strength),
+            );
+            let (min, max) = (
+                super::j_to_ppm(min, field_strength),
+                super::j_to_ppm(max, field_strength),
+            );
+            assert!(left < min && max < right);
+            // The multiplet occupies the same fraction of the window.
+            approx::assert_relative_eq!((max - min) / (right - left), 1. / 1.5);
+        }
+    }
+
+    #[test]
+    fn broad_singlet() {
+        let peak = Peak {
+            proton_count: 2,
+            fwhm: 100.,
+            ..Default::default()
+        };
+        let field_strength = 60.;
+        let cascade = peak.build_multiplet_cascade();
+        let waveform = cascade.final_lineshape_waveform(field_strength);
+        // The width is over a ppm at this field.
+        let extent = waveform.extent_by_fwhm(1.);
+        approx::assert_relative_eq!(extent.end() - extent.start(), 2. * 100. / 60.);
+        approx::assert_abs_diff_eq!(waveform.max(), waveform.evaluate(0.));
+        assert!(waveform.evaluate(1.) < waveform.max());
+        approx::assert_abs_diff_eq!(
+            waveform.evaluate_cdf(1e9) - waveform.evaluate_cdf(-1e9),
+            1.,
+            epsilon = 1e-6
+        );
+        let grid = waveform.adaptive_sample_grid(-5.0..=5.0, 1000);
+        assert!(grid.iter().any(|x| x.abs() < 0.1));
+    }
+
+    #[test]
+    fn sort_by_j() {
+        let mut peak = Peak {
+            splitters: vec![
+                Splitter::new(1, 2.5),
+                Splitter::new(2, 7.),
+                Splitter::new(1, 10.2),
+                Splitter::new(1, 7.),
+                Splitter::new(3, 0.8),
+            ],
+            ..Default::default()
+        };
+        let as_pairs = |peak: &Peak| {
+            peak.splitters
+                .iter()
+                .map(|s| (s.n, s.j))
+                .collect::<Vec<_>>()
+        };
+
+        peak.sort_by_j(SortOrder::Ascending);
+        assert_eq!(
+            as_pairs(&peak),
+            [(3, 0.8), (1, 2.5), (1, 7.), (2, 7.), (1, 10.2)]
+        );
+        peak.sort_by_j(SortOrder::Descending);
+        assert_eq!(
+            as_pairs(&peak),
+            [(1, 10.2), (2, 7.), (1, 7.), (1, 2.5), (3, 0.8)]
+        );
+    }
+
+    #[test]
+    fn sort_by_j_default_order() {
+        // The default places the largest coupling outermost in the splitting diagram.
+        assert_eq!(SortOrder::default(), SortOrder::Descending);
+        assert_eq!(SortOrder::default().name(), "descending");
+
+        let mut peak = Peak {
+            splitters: vec![Splitter::new(1, 2.), Splitter::new(1, 9.)],
+            ..Default::default()
+        };
+        peak.sort_by_j(SortOrder::default());
+        assert_eq!(peak.splitters[0], Splitter::new(1, 9.));
+    }
+
+    #[test]
+    fn equal_j_groups() {
+        let mut peak = Peak {
+            splitters: vec![
+                Splitter::new(2, 7.),
+                Splitter::new(1, 2.5),
+                Splitter::new(3, 7.02),
+                Splitter::new(1, 2.5),
+                Splitter::new(1, 11.),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            peak.equal_j_groups(),
+            [Some(0), Some(1), Some(0), Some(1), None]
+        );
+
+        // Muted splitters neither join nor form groups.
+        peak.splitters[3].muted = true;
+        assert_eq!(peak.equal_j_groups(), [Some(0), None, Some(0), None, None]);
+        peak.splitters[3].j = 13.;
+        assert_eq!(peak.equal_j_groups()[3], None);
+
+        let distinct = Peak {
+            splitters: vec![Splitter::new(1, 7.), Splitter::new(1, 7.1)],
+            ..Default::default()
+        };
+        assert_eq!(distinct.equal_j_groups(), [None, None]);
+    }
+
+    #[test]
+    fn muted_splitters() {
+        let mut peak = Peak {
+            splitters: vec![
+                Splitter::new(2, 7.),
+                Splitter::new(1, 3.),
+                Splitter::new(1, 11.),
+            ],
+            ..Default::default()
+        };
+        peak.splitters[1].muted = true;
+        let without = Peak {
+            splitters: vec![Splitter::new(2, 7.), Splitter::new(1, 11.)],
+            ..Default::default()
+        };
+        assert_eq!(peak.unmuted(), without);
+        assert_eq!(peak.stage_count(), 3);
+        assert_eq!(peak.total_peaklet_count(), 6);
+        assert_eq!(peak.name(PatternNaming::default()).as_deref(), Some("td"));
+        assert_eq!(peak.stage_names(PatternNaming::default()), ["s", "t", "td"]);
+        assert_eq!(
+            peak.build_multiplet_cascade().final_peaklets(),
+            without.build_multiplet_cascade().final_peaklets()
+        );
+        let partial = FractionalStageIndex::new(1.5);
+        assert_eq!(
+            peak.nth_partial_peak(partial),
+            without.nth_partial_peak(partial)
+        );
+
+        // Every splitter muted leaves a singlet.
+        for splitter in &mut peak.splitters {
+            splitter.muted = true;
+        }
+        assert_eq!(peak.build_multiplet_cascade().final_peaklets().len(), 1);
+        assert_eq!(peak.name(PatternNaming::default()).as_deref(), Some("s"));
+    }
+
+    #[test]
+    fn stage_peaklet_counts() {
+        let configurations = [
+            vec![],
+            vec![Splitter::new(1, 7.)],
+            vec![Splitter::new(3, 7.), Splitter::new(2, 2.)],
+            // Coincident lines are kept apart.
+            vec![Splitter::new(2, 7.), Splitter::new(2, 7.)],
+            vec![
+                Splitter::new(1, 10.),
+                Splitter::new(2, 0.),
+                Splitter::new(1, 4.),
+                Splitter::new(4, 1.5),
+            ],
+        ];
+        for splitters in configurations {
+            let mut peak = Peak {
+                splitters,
+                ..Default::default()
+            };
+            let counts = peak.stage_peaklet_counts();
+            let sizes = |peak: &Peak| {
+                peak.build_multiplet_cascade()
+                    .stages
+                    .iter()
+                    .map(|stage| u32::try_from(stage.len()).unwrap())
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(sizes(&peak), counts, "{:?}", peak.splitters);
+            assert_eq!(counts.last(), Some(&peak.total_peaklet_count()));
+
+            // Muting drops the stage of the splitter.
+            if let Some(splitter) = peak.splitters.first_mut() {
+                splitter.muted = true;
+                let counts = peak.stage_peaklet_counts();
+                assert_eq!(counts.len(), peak.splitters.len());
+                assert_eq!(sizes(&peak), counts);
+            }
+        }
+    }
+
+    #[test]
+    fn no_splitters() {
+        let peak = Peak {
+            proton_count: 2,
+            ..Default::default()
+        };
+        assert_eq!(peak.stage_count(), 1);
+        assert_eq!(peak.total_peaklet_count(), 1);
+        assert_eq!(peak.name(PatternNaming::default()).as_deref(), Some("s"));
+        assert_eq!(peak.stage_names(PatternNaming::default()), ["s"]);
+        // Every stage, even those past the end, is the singlet.
+        for n in [0., 0.5, 1., 2.75] {
+            let partial = peak.nth_partial_peak(FractionalStageIndex::new(n));
+            assert_eq!(partial, peak, "stage {n}");
+            assert_eq!(partial.build_multiplet_cascade().final_peaklets().len(), 1);
+        }
+
+        // Stages past the end of a nonempty peak give the full peak.
+        let doublet = Peak {
+            splitters: vec![Splitter::new(1, 7.)],
+            ..Default::default()
+        };
+        assert_eq!(
+            doublet.nth_partial_peak(FractionalStageIndex::new(2.5)),
+            doublet
+        );
+    }
+
+    #[test]
+    fn symmetric_center_of_mass() {
+        let peak = Peak {
+            splitters: vec![
+                Splitter::new(2, 7.),
+                Splitter::new(1, 3.),
+                Splitter::new(3, 1.2),
+            ],
+            ..Default::default()
+        };
+        // Every stage, whole or partial, is symmetric about the root.
+        for stage in [0., 0.3, 1., 1.5, 2., 2.9, 3.] {
+            let center = peak
+                .nth_partial_peak(FractionalStageIndex::new(stage))
+                .build_multiplet_cascade()
+                .final_lineshape_waveform(400.)
+                .center_of_mass()
+                .unwrap();
+            approx::assert_abs_diff_eq!(center, 0., epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn j_to_ppm_pathological_frequency() {
+        for frequency in [0., -400., f64::NAN, 1e-300] {
+            assert!(super::j_to_ppm(7., frequency).is_finite());
+        }
+        assert!(super::j_to_ppm(f64::NAN, 400.).is_finite());
+        assert!(super::j_to_ppm(f64::MAX, 1e-300).is_finite());
+    }
+
+    #[test]
+    fn stage_names() {
+        let peak = Peak {
+            splitters: vec![
+                Splitter::new(2, 7.),
+                Splitter::new(1, 3.),
+                Splitter::new(9, 1.),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            peak.stage_names(PatternNaming::default()),
+            ["s", "t", "td", "<complex>"]
+        );
+        assert_eq!(
+            Peak::default().stage_names(PatternNaming::default()).len(),
+            Peak::default().splitters.len() + 1
+        );
+    }
+
+    #[test]
+    fn pattern_naming() {
+        let splitter = |n| Splitter::new(n, 7.);
+        let single = |n, naming| {
+            Peak {
+                splitters: vec![splitter(n)],
+                ..Default::default()
+            }
+            .name(naming)
+        };
+        // The default is unchanged.
+        assert_eq!(single(4, PatternNaming::default()).as_deref(), Some("p"));
+        assert_eq!(
+            splitter(5).name_pattern(PatternNaming::default()),
+            Some("hextet")
+        );
+        assert_eq!(single(6, PatternNaming::Concise), None);
+        assert_eq!(
+            splitter(6).abbreviate_pattern(PatternNaming::Concise),
+            "hept"
+        );
+        assert_eq!(single(6, PatternNaming::Extended).as_deref(), Some("hept"));
+        assert_eq!(single(7, PatternNaming::Concise), None);
+        assert_eq!(splitter(7).abbreviate_pattern(PatternNaming::Concise), "8");
+
+        for (n, abbreviation, name) in [
+            (7, "oct", "octet"),
+            (8, "non", "nonet"),
+            (9, "dec", "decet"),
+        ] {
+            for naming in [PatternNaming::Extended, PatternNaming::Latin] {
+                assert_eq!(single(n, naming).as_deref(), Some(abbreviation));
+                assert_eq!(splitter(n).name_pattern(naming), Some(name));
+            }
+        }
+        assert_eq!(single(10, PatternNaming::Latin), None);
+        for (n, abbreviation, name) in [
+            (4, "quint", "quintet"),
+            (5, "sext", "sextet"),
+            (6, "sept", "septet"),
+        ] {
+            assert_eq!(
+                single(n, PatternNaming::Latin).as_deref(),
+                Some(abbreviation)
+            );
+            assert_eq!(splitter(n).name_pattern(PatternNaming::Latin), Some(name));
+        }
+        assert_eq!(
+            splitter(5).name_pattern(PatternNaming::Extended),
+            Some("hextet")
+        );
+
+        // Only single letters combine.
+        let combined = |n| Peak {
+            splitters: vec![splitter(n), Splitter::new(1, 2.)],
+            ..Default::default()
+        };
+        assert_eq!(
+            combined(3).name(PatternNaming::Latin).as_deref(),
+            Some("qd")
+        );
+        assert_eq!(
+            combined(4).name(PatternNaming::Concise).as_deref(),
+            Some("pd")
+        );
+        assert_eq!(combined(4).name(PatternNaming::Latin), None);
+        assert_eq!(
+            combined(4).stage_names(PatternNaming::Latin),
+            ["s", "quint", "<complex>"]
+        );
+    }
+
+    #[test]
+    fn frequency_near_zero() {
+        let at_minimum = super::j_to_ppm(7., MIN_FREQUENCY);
+        for frequency in [0., 1e-300, 1e-9, 0.5 * MIN_FREQUENCY] {
+            approx::assert_relative_eq!(super::j_to_ppm(7., frequency), at_minimum);
+            assert_eq!(
+                super::validate_frequency(frequency),
+                Err(InvalidFrequency::TooLow(frequency))
+            );
+        }
+        assert!(matches!(
+            super::validate_frequency(f64::NAN),
+            Err(InvalidFrequency::NotFinite(_))
+        ));
+        assert_eq!(super::validate_frequency(40.), Ok(40.));
+    }
+
+    #[test]
+    fn fractional_stage_index_try_new() {
+        assert_eq!(FractionalStageIndex::try_new(1.5).unwrap().full(), 1);
+        assert_eq!(FractionalStageIndex::try_new(0.0).unwrap().full(), 0);
+        assert!(matches!(
+            FractionalStageIndex::try_new(f64::NAN),
+            Err(InvalidStageIndex::NotFinite(_))
+        ));
+        assert_eq!(
+            FractionalStageIndex::try_new(f64::INFINITY),
+            Err(InvalidStageIndex::NotFinite(f64::INFINITY))
+        );
+        assert_eq!(
+            FractionalStageIndex::try_new(-1.0),
+            Err(InvalidStageIndex::Negative(-1.0))
+        );
+    }
+}
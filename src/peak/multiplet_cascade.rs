@@ -1,6 +1,11 @@
+use std::ops::RangeInclusive;
+
+use itertools::Itertools;
+
 use super::Peaklet;
 use crate::numerics::distribution::distribution_sum::DistributionSum;
-use crate::numerics::distribution::RenormalizedDistribution;
+use crate::numerics::distribution::lineshape::{AnyDistribution, Lineshape};
+use crate::numerics::distribution::{RenormalizedDistribution, COINCIDENCE_TOLERANCE};
 
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub struct SplittingRelationship<'a> {
@@ -8,6 +13,16 @@ pub struct SplittingRelationship<'a> {
     pub children: &'a [Peaklet],
 }
 
+#[derive(Clone, PartialEq, Debug)]
+/// The lineage of a single final-stage peaklet; see [`MultipletCascade::peaklet_path`].
+pub struct PeakletPath {
+    /// The peaklet's ancestors, one per stage, from the base singlet to (and including) the
+    /// peaklet itself.
+    pub peaklets: Vec<Peaklet>,
+    /// The coupling constant (Hz) applied between each pair of consecutive `peaklets`.
+    pub js: Vec<f64>,
+}
+
 #[derive(Clone, PartialEq, Debug)]
 /// Splitting patterns resulting from the cumulative contributions of all preceding splitters,
 /// starting from the parent singlet.
@@ -21,6 +36,12 @@ pub struct MultipletCascade {
     pub(super) stages: Vec<Vec<Peaklet>>,
     /// Full width at half maximum of a single peaklet, in Hz.
     pub(super) fwhm: f64,
+    /// Shape of each peaklet when drawn via [`Self::final_lineshape_waveform`] and friends.
+    pub(super) lineshape: Lineshape,
+    /// Skew of each peaklet, if the lineshape is skewed; see [`super::Peak::skew`].
+    pub(super) skew: f64,
+    /// Lorentzian fraction of each peaklet, if the lineshape is mixed; see [`super::Peak::mixing`].
+    pub(super) mixing: f64,
 }
 
 impl<'a> SplittingRelationship<'a> {
@@ -44,20 +65,193 @@ impl MultipletCascade {
     }
 
     #[must_use]
+    /// # Panics:
+    /// If `n` is not a valid stage index. See [`Self::try_nth_waveform`] for a checked variant.
     pub fn nth_waveform<D: RenormalizedDistribution>(
         &self,
         n: usize,
         field_strength: f64,
     ) -> DistributionSum<D> {
-        self.stages[n]
+        self.try_nth_waveform(n, field_strength)
+            .expect("stage index out of range")
+    }
+
+    #[must_use]
+    /// The waveform of the `n`-th stage, or `None` if there is no such stage.
+    pub fn try_nth_waveform<D: RenormalizedDistribution>(
+        &self,
+        n: usize,
+        field_strength: f64,
+    ) -> Option<DistributionSum<D>> {
+        self.try_nth_waveform_with(n, field_strength, D::with_fwhm_normalized)
+    }
+
+    #[must_use]
+    /// The waveform of the `n`-th stage drawn with this cascade's [`Lineshape`], or `None` if
+    /// there is no such stage.
+    pub fn try_nth_lineshape_waveform(
+        &self,
+        n: usize,
+        field_strength: f64,
+    ) -> Option<DistributionSum<AnyDistribution>> {
+        self.try_nth_waveform_with(n, field_strength, |μ, fwhm, normalization| {
+            self.lineshape.with_fwhm_shape_normalized(
+                μ,
+                fwhm,
+                self.skew,
+                self.mixing,
+                normalization,
+            )
+        })
+    }
+
+    fn try_nth_waveform_with<D: RenormalizedDistribution>(
+        &self,
+        n: usize,
+        field_strength: f64,
+        make_distribution: impl Fn(f64, f64, f64) -> D,
+    ) -> Option<DistributionSum<D>> {
+        let waveform = self
+            .stages
+            .get(n)?
             .iter()
             .map(|peaklet| {
-                D::with_fwhm_normalized(
+                make_distribution(
                     super::j_to_ppm(peaklet.δ, field_strength),
                     super::j_to_ppm(self.fwhm, field_strength),
                     peaklet.integration,
                 )
             })
+            .collect();
+        Some(waveform)
+    }
+
+    #[must_use]
+    /// Whether there is a peaklet at `index` within stage `stage`.
+    pub fn contains_peaklet(&self, (stage, index): (usize, usize)) -> bool {
+        self.stages
+            .get(stage)
+            .is_some_and(|peaklets| index < peaklets.len())
+    }
+
+    /// The index, within `ancestor_stage`, of the ancestor of the `index`-th peaklet of `stage`,
+    /// which must not precede `ancestor_stage`.
+    fn ancestor_index(&self, stage: usize, index: usize, ancestor_stage: usize) -> usize {
+        // Every peaklet of a stage has the same number of children.
+        (ancestor_stage + 1..=stage)
+            .rev()
+            .fold(index, |index, stage| {
+                index / (self.stages[stage].len() / self.stages[stage - 1].len())
+            })
+    }
+
+    #[must_use]
+    /// The lineage of the `index`-th peaklet of the final stage, or `None` if there is no such
+    /// peaklet.
+    pub fn peaklet_path(&self, index: usize) -> Option<PeakletPath> {
+        let final_stage = self.stages.len() - 1;
+        if !self.contains_peaklet((final_stage, index)) {
+            return None;
+        }
+        let peaklets = (0..=final_stage)
+            .map(|stage| self.stages[stage][self.ancestor_index(final_stage, index, stage)])
+            .collect();
+        let js = (1..=final_stage)
+            .map(|stage| {
+                // Siblings are spaced by the coupling constant.
+                let children_count = self.stages[stage].len() / self.stages[stage - 1].len();
+                let first_sibling = self.ancestor_index(final_stage, index, stage) / children_count
+                    * children_count;
+                let siblings = &self.stages[stage][first_sibling..];
+                siblings[1].δ - siblings[0].δ
+            })
+            .collect();
+        Some(PeakletPath { peaklets, js })
+    }
+
+    #[must_use]
+    /// Whether `peaklet` is an ancestor or descendant of (or is) `pinned`, where both are given as
+    /// `(stage, index within stage)`. See [`Self::contains_peaklet`].
+    pub fn is_in_lineage(&self, pinned: (usize, usize), peaklet: (usize, usize)) -> bool {
+        let (ancestor, descendant) = if peaklet.0 <= pinned.0 {
+            (peaklet, pinned)
+        } else {
+            (pinned, peaklet)
+        };
+        self.ancestor_index(descendant.0, descendant.1, ancestor.0) == ancestor.1
+    }
+
+    #[must_use]
+    pub fn lineshape(&self) -> Lineshape {
+        self.lineshape
+    }
+
+    #[must_use]
+    pub fn skew(&self) -> f64 {
+        self.skew
+    }
+
+    #[must_use]
+    pub fn mixing(&self) -> f64 {
+        self.mixing
+    }
+
+    #[must_use]
+    /// Full width at half maximum of a single peaklet, in Hz.
+    pub fn fwhm(&self) -> f64 {
+        self.fwhm
+    }
+
+    #[must_use]
+    /// A copy of this cascade whose peaklets are drawn with a different width.
+    pub fn with_fwhm(&self, fwhm: f64) -> Self {
+        Self {
+            fwhm,
+            ..self.clone()
+        }
+    }
+
+    #[must_use]
+    /// The peaklets of the final (i.e., fully split) stage.
+    pub fn final_peaklets(&self) -> &[Peaklet] {
+        self.stages.last().unwrap()
+    }
+
+    #[must_use]
+    /// The range of shifts (Hz) spanned by the lines of the final stage.
+    pub fn final_span(&self) -> RangeInclusive<f64> {
+        let (min, max) = self
+            .final_peaklets()
+            .iter()
+            .map(|peaklet| peaklet.δ)
+            .minmax()
+            .into_option()
+            .unwrap_or_default();
+        min..=max
+    }
+
+    #[must_use]
+    /// The peaklets of the final stage in order of increasing shift, each paired with whether it
+    /// overlaps with a neighboring peaklet (i.e., is not individually resolved).
+    pub fn final_peaklets_with_overlap(&self) -> Vec<(Peaklet, bool)> {
+        let sorted = self
+            .final_peaklets()
+            .iter()
+            .copied()
+            .sorted_by(|a, b| a.δ.total_cmp(&b.δ))
+            .collect_vec();
+        let overlaps_next = sorted
+            .array_windows()
+            .map(|[a, b]| a.overlaps_with(*b, self.fwhm))
+            .collect_vec();
+        sorted
+            .iter()
+            .enumerate()
+            .map(|(i, &peaklet)| {
+                let overlaps_prev = i > 0 && overlaps_next[i - 1];
+                let overlaps_next = overlaps_next.get(i).copied().unwrap_or(false);
+                (peaklet, overlaps_prev || overlaps_next)
+            })
             .collect()
     }
 
@@ -69,27 +263,49 @@ impl MultipletCascade {
         self.nth_waveform(self.stages.len() - 1, field_strength)
     }
 
+    #[must_use]
+    /// The waveform of the final stage, drawn with this cascade's [`Lineshape`].
+    pub fn final_lineshape_waveform(
+        &self,
+        field_strength: f64,
+    ) -> DistributionSum<AnyDistribution> {
+        self.try_nth_lineshape_waveform(self.stages.len() - 1, field_strength)
+            .unwrap()
+    }
+
     /// # Panics:
-    /// This iterator can only be called on child stages (that is, not the base peaklet).
+    /// This iterator can only be called on child stages (that is, not the base peaklet), and
+    /// `n` must be a valid stage index. See [`Self::try_iter_nth_stage`] for a checked variant.
     pub fn iter_nth_stage(&self, n: usize) -> impl Iterator<Item = SplittingRelationship<'_>> {
-        let parent_count = self.stages[n
-            .checked_sub(1)
-            .expect("should not be called on base stage")]
-        .len();
-        let children_count = self.stages[n].len();
+        assert_ne!(n, 0, "should not be called on base stage");
+        self.try_iter_nth_stage(n)
+            .expect("stage index out of range")
+    }
+
+    #[must_use]
+    /// Iterate over the splitting relationships of the `n`-th stage, or `None` if `n` does not
+    /// index a child stage.
+    pub fn try_iter_nth_stage(
+        &self,
+        n: usize,
+    ) -> Option<impl Iterator<Item = SplittingRelationship<'_>>> {
+        let parents = self.stages.get(n.checked_sub(1)?)?;
+        let children = self.stages.get(n)?;
         assert_eq!(
-            children_count % parent_count,
+            children.len() % parents.len(),
             0,
             "the number of child peaklets should be an integer multiple of the number of parents"
         );
-        let group_size = children_count / parent_count;
-        self.stages[n]
-            .chunks_exact(group_size)
-            .enumerate()
-            .map(move |(i, group)| SplittingRelationship {
-                parent: &self.stages[n - 1][i],
-                children: group,
-            })
+        let group_size = children.len() / parents.len();
+        let relationships =
+            children
+                .chunks_exact(group_size)
+                .zip(parents)
+                .map(|(group, parent)| SplittingRelationship {
+                    parent,
+                    children: group,
+                });
+        Some(relationships)
     }
 
     pub fn max_integration_of_stage(&self, n: usize) -> f64 {
@@ -117,4 +333,150 @@ impl MultipletCascade {
                 .all(|[a, b]| !a.overlaps_with(*b, self.fwhm))
         }
     }
+
+    #[must_use]
+    /// Like [`Self::is_stage_resolved`], but neighboring lines of each group must additionally be
+    /// at least `min_separation` (Hz) apart, _e.g._, to be told apart at a given display scale.
+    pub fn is_stage_resolved_at(&self, n: usize, min_separation: f64) -> bool {
+        n == 0
+            || self.is_stage_resolved(n)
+                && self
+                    .iter_nth_stage(n)
+                    .next()
+                    .unwrap()
+                    .children
+                    .array_windows()
+                    .all(|[a, b]| (b.δ - a.δ).abs() >= min_separation)
+    }
+
+    #[must_use]
+    /// A stricter estimate than [`Self::is_stage_resolved`]: whether every line of the stage is
+    /// resolved from its neighbors, including those split from _other_ parents. Coincident lines
+    /// (_e.g._, from equal coupling constants) are counted as one.
+    pub fn is_stage_fully_resolved(&self, n: usize) -> bool {
+        let tolerance = COINCIDENCE_TOLERANCE * self.fwhm;
+        self.stages[n]
+            .iter()
+            .sorted_by(|a, b| a.δ.total_cmp(&b.δ))
+            .dedup_by(|a, b| (a.δ - b.δ).abs() <= tolerance)
+            .tuple_windows()
+            .all(|(a, b)| !a.overlaps_with(*b, self.fwhm))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::numerics::distribution::lorentzian::Lorentzian;
+    use crate::numerics::distribution::RenormalizedDistribution;
+    use crate::peak::{PatternNaming, Peak, Splitter};
+
+    fn doublet_of_triplets() -> Peak {
+        Peak {
+            splitters: vec![Splitter::new(1, 10.), Splitter::new(2, 3.)],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn stage_resolution() {
+        let cascade = |splitters| {
+            Peak {
+                splitters,
+                fwhm: 1.,
+                ..Default::default()
+            }
+            .build_multiplet_cascade()
+        };
+        // -8, -5, -2, 2, 5, 8.
+        let dt = doublet_of_triplets().build_multiplet_cascade();
+        assert!(dt.is_stage_resolved(2) && dt.is_stage_fully_resolved(2));
+
+        // The triplets about ±5 Hz meet at ±0.4 Hz.
+        let overlapping = cascade(vec![Splitter::new(1, 10.), Splitter::new(2, 4.6)]);
+        assert!(overlapping.is_stage_resolved(2));
+        assert!(!overlapping.is_stage_fully_resolved(2));
+
+        // Resolved lines 3 Hz apart are indistinguishable if they must be 4 Hz apart.
+        assert!(dt.is_stage_resolved_at(2, 3.));
+        assert!(!dt.is_stage_resolved_at(2, 4.));
+        assert!(dt.is_stage_resolved_at(0, f64::INFINITY));
+
+        // An apparent triplet: the inner lines coincide exactly.
+        let coincident = cascade(vec![Splitter::new(1, 10.), Splitter::new(1, 10.)]);
+        assert!(coincident.is_stage_fully_resolved(2));
+    }
+
+    #[test]
+    fn singlet() {
+        let peak = Peak {
+            proton_count: 3,
+            fwhm: 2.,
+            ..Default::default()
+        };
+        let cascade = peak.build_multiplet_cascade();
+        assert_eq!(cascade.child_stages_count(), 0);
+        assert_eq!(cascade.final_peaklets(), &[cascade.base_peaklet()]);
+        approx::assert_abs_diff_eq!(cascade.base_peaklet().δ, 0.);
+        approx::assert_abs_diff_eq!(cascade.base_peaklet().integration, 1.);
+        assert!(cascade.is_stage_resolved(0) && cascade.is_stage_fully_resolved(0));
+        assert!(cascade.try_iter_nth_stage(1).is_none());
+        assert_eq!(peak.name(PatternNaming::default()).as_deref(), Some("s"));
+
+        // A single line of the peak's width, centered on the peak.
+        let field_strength = 400.;
+        let line = Lorentzian::with_fwhm_normalized(0., 2. / field_strength, 1.);
+        let waveform = cascade.final_lineshape_waveform(field_strength);
+        assert_eq!(waveform.components().count(), 1);
+        for x in [-0.01, -0.002, 0., 0.001, 0.05] {
+            approx::assert_relative_eq!(waveform.evaluate(x), line.evaluate(x));
+        }
+        approx::assert_relative_eq!(waveform.max(), line.evaluate(0.));
+    }
+
+    #[test]
+    fn peaklet_path_of_dd() {
+        let cascade = Peak {
+            splitters: vec![Splitter::new(1, 10.), Splitter::new(1, 4.)],
+            ..Default::default()
+        }
+        .build_multiplet_cascade();
+        // Final stage, in order: -7, -3 (from -5), 3, 7 (from 5).
+        let path = cascade.peaklet_path(2).unwrap();
+        assert_eq!(path.peaklets.len(), 3);
+        for (peaklet, δ) in path.peaklets.iter().zip([0., 5., 3.]) {
+            approx::assert_abs_diff_eq!(peaklet.δ, δ);
+        }
+        assert_eq!(path.js.len(), 2);
+        for (j, expected) in path.js.iter().zip([10., 4.]) {
+            approx::assert_abs_diff_eq!(*j, expected);
+        }
+        approx::assert_abs_diff_eq!(path.peaklets[2].integration, 0.25);
+        assert!(cascade.peaklet_path(4).is_none());
+    }
+
+    #[test]
+    fn try_nth_waveform_bounds() {
+        let cascade = doublet_of_triplets().build_multiplet_cascade();
+        for (n, count) in [(0, 1), (1, 2), (2, 6)] {
+            let waveform = cascade.try_nth_waveform::<Lorentzian>(n, 400.).unwrap();
+            assert_eq!(waveform.components().count(), count);
+        }
+        assert!(cascade.try_nth_waveform::<Lorentzian>(3, 400.).is_none());
+    }
+
+    #[test]
+    fn try_iter_nth_stage_bounds() {
+        let cascade = doublet_of_triplets().build_multiplet_cascade();
+        assert!(cascade.try_iter_nth_stage(0).is_none());
+        assert_eq!(cascade.try_iter_nth_stage(1).unwrap().count(), 1);
+        assert_eq!(cascade.try_iter_nth_stage(2).unwrap().count(), 2);
+        assert!(cascade.try_iter_nth_stage(3).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "base stage")]
+    fn iter_nth_stage_base_panics() {
+        let cascade = doublet_of_triplets().build_multiplet_cascade();
+        let _ = cascade.iter_nth_stage(0);
+    }
 }
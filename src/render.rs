@@ -0,0 +1,830 @@
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::sync::LazyLock;
+
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont};
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::codecs::png::PngEncoder;
+use image::error::{LimitError, LimitErrorKind};
+use image::{ColorType, Delay, Frame, ImageEncoder, ImageError, ImageResult, Rgba, RgbaImage};
+
+use crate::numerics::distribution::distribution_sum::DistributionSum;
+use crate::numerics::distribution::lineshape::Lineshape;
+use crate::numerics::distribution::RenormalizedDistribution;
+use crate::peak::{self, FractionalStageIndex, Peak};
+
+const BACKGROUND_COLOR: Rgba<u8> = Rgba([255, 255, 255, 255]);
+/// Blue, from the same palette as the GUI. (Its yellow is illegible against white.)
+const CURVE_COLOR: Rgba<u8> = Rgba([0, 114, 178, 255]);
+const AXIS_COLOR: Rgba<u8> = Rgba([0, 0, 0, 255]);
+const LABEL_COLOR: Rgba<u8> = Rgba([0, 0, 0, 255]);
+const CELL_BORDER_COLOR: Rgba<u8> = Rgba([200, 200, 200, 255]);
+/// Height of the label atop each cell of a figure sheet, as a fraction of the cell's height.
+const LABEL_HEIGHT: f64 = 0.15;
+/// Length of the tick marks on the x-axis, in pixels.
+const TICK_LENGTH: f64 = 5.;
+/// Approximate number of ticks on the x-axis.
+const TICK_COUNT: f64 = 10.;
+/// Number of waveform samples taken per pixel of width.
+const SAMPLES_PER_PIXEL: usize = 4;
+/// Region on either side of the multiplet included in the frame, in FWHM.
+const FRAME_MARGIN: f64 = 4.;
+/// GIF quantization speed; see [`GifEncoder::new_with_speed`].
+const GIF_SPEED: i32 = 20;
+/// Width of the strokes of [`RenderQuality::Print`], as a fraction of the height of the image,
+/// such that the lines keep their proportions at any resolution.
+const PRINT_LINE_WIDTH: f64 = 1. / 400.;
+/// Width of the strokes of [`RenderQuality::Print`] in small images, in pixels.
+const MIN_PRINT_LINE_WIDTH: f64 = 1.5;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+/// How lines are drawn into an image.
+pub enum RenderQuality {
+    /// Aliased lines, two pixels tall, which are cheap and adequate for the screen.
+    #[default]
+    Draft,
+    /// Anti-aliased strokes whose width grows with the image, for figures at print resolution.
+    Print,
+}
+
+impl RenderQuality {
+    pub const ALL: [Self; 2] = [Self::Draft, Self::Print];
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Draft => "Draft",
+            Self::Print => "Print",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+/// The region of the plane shown by a rendered image, and how it is drawn.
+pub struct Viewport {
+    /// Image dimensions, in pixels.
+    pub width: u32,
+    pub height: u32,
+    /// The plotted shifts (ppm), from left to right.
+    pub x_window: RangeInclusive<f64>,
+    /// The plotted intensities, from bottom to top.
+    pub y_window: RangeInclusive<f64>,
+    /// Whether to draw the baseline and tick marks on it.
+    pub axes: bool,
+    pub quality: RenderQuality,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+/// Parameters of a rendered build-up animation; see [`sweep_frames`].
+pub struct SweepOptions {
+    /// Image dimensions, in pixels.
+    pub width: u32,
+    pub height: u32,
+    pub frame_rate: u32,
+    /// Length of the sweep, in seconds.
+    pub duration: f64,
+    pub quality: RenderQuality,
+}
+
+impl SweepOptions {
+    /// The most pixels, summed over all frames, that [`sweep_gif`] will encode. This is about four
+    /// seconds of 1080p at 30 fps, beyond which encoding would stall the app for minutes.
+    pub const MAX_PIXELS: u64 = 250_000_000;
+
+    #[must_use]
+    /// The number of frames of the sweep, including both the first and the last.
+    pub fn frame_count(&self) -> usize {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let intervals = (self.duration * f64::from(self.frame_rate)).ceil().max(1.) as usize;
+        intervals + 1
+    }
+
+    #[must_use]
+    /// The number of pixels of every frame of the sweep together.
+    pub fn pixel_count(&self) -> u64 {
+        u64::from(self.width) * u64::from(self.height) * self.frame_count() as u64
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+/// Layout of a figure sheet; see [`figure_sheet`].
+pub struct SheetOptions {
+    pub columns: u32,
+    /// Dimensions of each cell, including its label, in pixels.
+    pub cell_width: u32,
+    pub cell_height: u32,
+    pub quality: RenderQuality,
+}
+
+impl Default for SheetOptions {
+    fn default() -> Self {
+        Self {
+            columns: 3,
+            cell_width: 400,
+            cell_height: 260,
+            quality: RenderQuality::Print,
+        }
+    }
+}
+
+/// The typeface of labels, which is that of the app.
+static LABEL_FONT: LazyLock<FontRef<'static>> = LazyLock::new(|| {
+    FontRef::try_from_slice(include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/assets/Inter-Regular.otf"
+    )))
+    .expect("bundled font is valid")
+});
+
+impl Default for SweepOptions {
+    fn default() -> Self {
+        Self {
+            width: 640,
+            height: 360,
+            frame_rate: 25,
+            duration: 4.,
+            quality: RenderQuality::default(),
+        }
+    }
+}
+
+/// Draw the segments connecting consecutive `points`, given in plot coordinates, onto `image`
+/// at the `quality` of `viewport`.
+fn draw_polyline(image: &mut RgbaImage, points: &[[f64; 2]], viewport: &Viewport, color: Rgba<u8>) {
+    let (width, height) = (f64::from(image.width()), f64::from(image.height()));
+    let (x_window, y_window) = (&viewport.x_window, &viewport.y_window);
+    let pixels = points
+        .iter()
+        .map(|[x, y]| {
+            [
+                (x - x_window.start()) / (x_window.end() - x_window.start()) * width,
+                (1. - (y - y_window.start()) / (y_window.end() - y_window.start())) * height,
+            ]
+        })
+        .collect::<Vec<_>>();
+    match viewport.quality {
+        RenderQuality::Draft => draw_aliased_polyline(image, &pixels, color),
+        RenderQuality::Print => {
+            let line_width = (PRINT_LINE_WIDTH * height).max(MIN_PRINT_LINE_WIDTH);
+            draw_antialiased_polyline(image, &pixels, line_width, color);
+        }
+    }
+}
+
+/// Draw the segments connecting consecutive `pixels`, given in image coordinates, one pixel wide.
+fn draw_aliased_polyline(image: &mut RgbaImage, pixels: &[[f64; 2]], color: Rgba<u8>) {
+    let (width, height) = (f64::from(image.width()), f64::from(image.height()));
+    for &[[x0, y0], [x1, y1]] in pixels.array_windows() {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let steps = (x1 - x0).abs().max((y1 - y0).abs()).ceil().max(1.) as usize;
+        for i in 0..=steps {
+            let t = i as f64 / steps as f64;
+            let (x, y) = (x0 + t * (x1 - x0), y0 + t * (y1 - y0));
+            // Draw two pixels tall for legibility.
+            for y in [y, y + 1.] {
+                if (0.0..width).contains(&x) && (0.0..height).contains(&y) {
+                    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                    image.put_pixel(x as u32, y as u32, color);
+                }
+            }
+        }
+    }
+}
+
+/// Draw the segments connecting consecutive `pixels`, given in image coordinates, as a stroke of
+/// `line_width` with round joins. Each pixel is covered in proportion to (approximately) the area
+/// of it within the stroke.
+fn draw_antialiased_polyline(
+    image: &mut RgbaImage,
+    pixels: &[[f64; 2]],
+    line_width: f64,
+    color: Rgba<u8>,
+) {
+    let (width, height) = (image.width(), image.height());
+    let half_width = 0.5 * line_width;
+    // The coverage of each pixel by the whole stroke, such that pixels near joins, which lie
+    // within several segments, are not blended repeatedly. Only pixels near the stroke are kept,
+    // which, for a large image, are but a small fraction of it.
+    let mut coverage = HashMap::<(u32, u32), f32>::new();
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let clamp_to_image = |value: f64, size: u32| value.max(0.).min(f64::from(size)) as u32;
+    for &[a, b] in pixels.array_windows() {
+        let [dx, dy] = [b[0] - a[0], b[1] - a[1]];
+        let length_squared = dx * dx + dy * dy;
+        let reach = half_width + 1.;
+        let columns = clamp_to_image(a[0].min(b[0]) - reach, width)
+            ..clamp_to_image(a[0].max(b[0]) + reach + 1., width);
+        let rows = clamp_to_image(a[1].min(b[1]) - reach, height)
+            ..clamp_to_image(a[1].max(b[1]) + reach + 1., height);
+        for y in rows {
+            for x in columns.clone() {
+                // The distance from the center of the pixel to the segment.
+                let [px, py] = [f64::from(x) + 0.5 - a[0], f64::from(y) + 0.5 - a[1]];
+                let t = if length_squared > 0. {
+                    ((px * dx + py * dy) / length_squared).clamp(0., 1.)
+                } else {
+                    0.
+                };
+                let distance = (px - t * dx).hypot(py - t * dy);
+                #[allow(clippy::cast_possible_truncation)]
+                let covered = (half_width + 0.5 - distance).clamp(0., 1.) as f32;
+                if covered > 0. {
+                    let cell = coverage.entry((x, y)).or_default();
+                    *cell = cell.max(covered);
+                }
+            }
+        }
+    }
+    for ((x, y), covered) in coverage {
+        blend(image.get_pixel_mut(x, y), color, covered);
+    }
+}
+
+/// Paint `color` over `pixel` with the opacity `coverage`, in `[0, 1]`.
+fn blend(pixel: &mut Rgba<u8>, color: Rgba<u8>, coverage: f32) {
+    if coverage <= 0. {
+        return;
+    }
+    for (channel, &target) in pixel.0.iter_mut().zip(&color.0) {
+        let blended = f32::from(*channel) + coverage * (f32::from(target) - f32::from(*channel));
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        {
+            *channel = blended.round() as u8;
+        }
+    }
+}
+
+/// Draw a single line of `text`, `size` pixels tall, whose top left corner is at `origin` in
+/// image coordinates. Text beyond the image is clipped.
+fn draw_text(image: &mut RgbaImage, text: &str, origin: [f32; 2], size: f32, color: Rgba<u8>) {
+    let font = &*LABEL_FONT;
+    let scaled = font.as_scaled(PxScale::from(size));
+    let baseline = origin[1] + scaled.ascent();
+    let mut caret = origin[0];
+    let mut previous = None;
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        if let Some(previous) = previous {
+            caret += scaled.kern(previous, id);
+        }
+        previous = Some(id);
+        let glyph = id.with_scale_and_position(scaled.scale(), ab_glyph::point(caret, baseline));
+        caret += scaled.h_advance(id);
+        let Some(outline) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outline.px_bounds();
+        outline.draw(|x, y, coverage| {
+            #[allow(clippy::cast_possible_truncation)]
+            let (x, y) = (
+                i64::from(x) + bounds.min.x as i64,
+                i64::from(y) + bounds.min.y as i64,
+            );
+            if let (Ok(x), Ok(y)) = (u32::try_from(x), u32::try_from(y)) {
+                if x < image.width() && y < image.height() {
+                    blend(image.get_pixel_mut(x, y), color, coverage);
+                }
+            }
+        });
+    }
+}
+
+/// Draw the baseline, with tick marks at round shifts.
+fn draw_axes(image: &mut RgbaImage, viewport: &Viewport) {
+    let (x_min, x_max) = viewport.x_window.clone().into_inner();
+    let span = x_max - x_min;
+    let (x_start, x_end) = (x_min - span, x_max + span);
+    draw_polyline(image, &[[x_start, 0.], [x_end, 0.]], viewport, AXIS_COLOR);
+
+    // Round the tick spacing to 1, 2, or 5 times a power of ten.
+    let rough_step = span / TICK_COUNT;
+    let magnitude = 10_f64.powf(rough_step.log10().floor());
+    let step = [1., 2., 5., 10.]
+        .into_iter()
+        .map(|factor| factor * magnitude)
+        .find(|&step| step >= rough_step)
+        .unwrap_or(rough_step);
+    if !step.is_normal() {
+        return;
+    }
+    let tick_height = TICK_LENGTH / f64::from(viewport.height)
+        * (viewport.y_window.end() - viewport.y_window.start());
+    let mut x = (x_min / step).ceil() * step;
+    while x <= x_max {
+        draw_polyline(image, &[[x, 0.], [x, -tick_height]], viewport, AXIS_COLOR);
+        x += step;
+    }
+}
+
+#[must_use]
+/// Render `waveform`, which may comprise any (including user-defined) distribution, into an
+/// image.
+pub fn render_waveform<D: RenormalizedDistribution>(
+    waveform: &DistributionSum<D>,
+    viewport: &Viewport,
+) -> RgbaImage {
+    let mut image = RgbaImage::from_pixel(viewport.width, viewport.height, BACKGROUND_COLOR);
+    if viewport.axes {
+        draw_axes(&mut image, viewport);
+    }
+    let points = waveform
+        .adaptive_sample_grid(
+            viewport.x_window.clone(),
+            viewport.width as usize * SAMPLES_PER_PIXEL,
+        )
+        .into_iter()
+        .map(|x| [x, waveform.evaluate(x)])
+        .collect::<Vec<_>>();
+    draw_polyline(&mut image, &points, viewport, CURVE_COLOR);
+    image
+}
+
+#[must_use]
+/// Render the spectrum of `peak`, drawn with the given `lineshape`, into an image.
+pub fn render_peak(
+    peak: &Peak,
+    field_strength: f64,
+    lineshape: Lineshape,
+    viewport: &Viewport,
+) -> RgbaImage {
+    let peak = Peak {
+        lineshape,
+        ..peak.clone()
+    };
+    let waveform = peak
+        .build_multiplet_cascade()
+        .final_lineshape_waveform(field_strength)
+        .merge_coincident();
+    render_waveform(&waveform, viewport)
+}
+
+/// Render the build-up of `peak` from a singlet to the full multiplet, applying the splitters at
+/// a constant rate. The window is fixed such that every frame fits. Frames are rendered as they
+/// are taken, such that only one need be held at a time.
+pub fn sweep_frames(
+    peak: &Peak,
+    field_strength: f64,
+    options: SweepOptions,
+) -> impl Iterator<Item = RgbaImage> {
+    let frame_count = options.frame_count();
+    let stage_count = f64::from(peak.stage_count() - 1);
+    let waveforms = (0..frame_count)
+        .map(|i| {
+            let stage = stage_count * i as f64 / (frame_count - 1) as f64;
+            peak.nth_partial_peak(FractionalStageIndex::new(stage))
+                .build_multiplet_cascade()
+                .final_lineshape_waveform(field_strength)
+                .merge_coincident()
+        })
+        .collect::<Vec<_>>();
+
+    // The singlet is the tallest, but be robust to patterns which are not.
+    let y_max = waveforms
+        .iter()
+        .map(DistributionSum::max)
+        .fold(f64::MIN_POSITIVE, f64::max);
+    let viewport = framing_viewport(peak, field_strength, options, y_max);
+
+    waveforms
+        .into_iter()
+        .map(move |waveform| render_waveform(&waveform, &viewport))
+}
+
+/// A viewport of the size and quality of `options`, spanning the complete multiplet of `peak`
+/// horizontally and intensities up to `y_max` vertically.
+fn framing_viewport(
+    peak: &Peak,
+    field_strength: f64,
+    options: SweepOptions,
+    y_max: f64,
+) -> Viewport {
+    let (min, max) = peak.build_multiplet_cascade().final_span().into_inner();
+    let margin = FRAME_MARGIN * peak.fwhm;
+    let x_window =
+        peak::j_to_ppm(min - margin, field_strength)..=peak::j_to_ppm(max + margin, field_strength);
+    Viewport {
+        width: options.width,
+        height: options.height,
+        x_window,
+        y_window: (-0.05 * y_max)..=(1.1 * y_max),
+        axes: false,
+        quality: options.quality,
+    }
+}
+
+/// The most pixels that [`final_frame`] will render, about 8K UHD.
+pub const MAX_STILL_PIXELS: u64 = 40_000_000;
+
+#[must_use]
+/// The number of pixels of the [`final_frame`] of `options` at `scale`.
+pub fn final_frame_pixel_count(options: SweepOptions, scale: u32) -> u64 {
+    let scale = u64::from(scale.max(1));
+    u64::from(options.width) * scale * u64::from(options.height) * scale
+}
+
+/// Render the complete multiplet of `peak`, framed as the last of the [`sweep_frames`], but at
+/// `scale` times the size of `options`.
+///
+/// # Errors
+/// If the image would have more than [`MAX_STILL_PIXELS`].
+pub fn final_frame(
+    peak: &Peak,
+    field_strength: f64,
+    options: SweepOptions,
+    scale: u32,
+) -> ImageResult<RgbaImage> {
+    if final_frame_pixel_count(options, scale) > MAX_STILL_PIXELS {
+        return Err(ImageError::Limits(LimitError::from_kind(
+            LimitErrorKind::InsufficientMemory,
+        )));
+    }
+    Ok(render_final_frame(peak, field_strength, options, scale))
+}
+
+/// [`final_frame`], of any size.
+fn render_final_frame(
+    peak: &Peak,
+    field_strength: f64,
+    options: SweepOptions,
+    scale: u32,
+) -> RgbaImage {
+    let waveform = peak
+        .build_multiplet_cascade()
+        .final_lineshape_waveform(field_strength)
+        .merge_coincident();
+    let options = SweepOptions {
+        width: options.width * scale.max(1),
+        height: options.height * scale.max(1),
+        ..options
+    };
+    let viewport = framing_viewport(
+        peak,
+        field_strength,
+        options,
+        waveform.max().max(f64::MIN_POSITIVE),
+    );
+    render_waveform(&waveform, &viewport)
+}
+
+/// [`final_frame`], encoded as a PNG.
+///
+/// # Errors
+/// If the image is too large, or encoding fails.
+pub fn final_frame_png(
+    peak: &Peak,
+    field_strength: f64,
+    options: SweepOptions,
+    scale: u32,
+) -> ImageResult<Vec<u8>> {
+    encode_png(&final_frame(peak, field_strength, options, scale)?)
+}
+
+fn encode_png(image: &RgbaImage) -> ImageResult<Vec<u8>> {
+    let mut png = Vec::new();
+    PngEncoder::new(&mut png).write_image(
+        image.as_raw(),
+        image.width(),
+        image.height(),
+        ColorType::Rgba8,
+    )?;
+    Ok(png)
+}
+
+#[must_use]
+/// A grid of the complete multiplets of `entries`, each labeled with its name, filled row by row
+/// into `options.columns` columns. Each multiplet is framed individually, as by [`final_frame`].
+pub fn figure_sheet(
+    entries: &[(String, Peak)],
+    field_strength: f64,
+    options: SheetOptions,
+) -> RgbaImage {
+    let columns = options.columns.max(1);
+    #[allow(clippy::cast_possible_truncation)]
+    let rows = ((entries.len() as u32 + columns - 1) / columns).max(1);
+    let mut sheet = RgbaImage::from_pixel(
+        columns * options.cell_width,
+        rows * options.cell_height,
+        BACKGROUND_COLOR,
+    );
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let label_height = (LABEL_HEIGHT * f64::from(options.cell_height)).round() as u32;
+    let spectrum_options = SweepOptions {
+        width: options.cell_width,
+        height: options.cell_height.saturating_sub(label_height).max(1),
+        quality: options.quality,
+        ..Default::default()
+    };
+    for (i, (label, peak)) in entries.iter().enumerate() {
+        #[allow(clippy::cast_possible_truncation)]
+        let (column, row) = (i as u32 % columns, i as u32 / columns);
+        let (x, y) = (column * options.cell_width, row * options.cell_height);
+        let spectrum = render_final_frame(peak, field_strength, spectrum_options, 1);
+        image::imageops::replace(
+            &mut sheet,
+            &spectrum,
+            i64::from(x),
+            i64::from(y + label_height),
+        );
+        #[allow(clippy::cast_precision_loss)]
+        let margin = 0.2 * label_height as f32;
+        #[allow(clippy::cast_precision_loss)]
+        draw_text(
+            &mut sheet,
+            label,
+            [x as f32 + margin, y as f32 + margin],
+            label_height as f32 - 2. * margin,
+            LABEL_COLOR,
+        );
+    }
+    // Separate the cells.
+    for x in (1..columns).map(|column| column * options.cell_width) {
+        for y in 0..sheet.height() {
+            sheet.put_pixel(x, y, CELL_BORDER_COLOR);
+        }
+    }
+    for y in (1..rows).map(|row| row * options.cell_height) {
+        for x in 0..sheet.width() {
+            sheet.put_pixel(x, y, CELL_BORDER_COLOR);
+        }
+    }
+    sheet
+}
+
+/// [`figure_sheet`], encoded as a PNG.
+///
+/// # Errors
+/// If encoding fails.
+pub fn figure_sheet_png(
+    entries: &[(String, Peak)],
+    field_strength: f64,
+    options: SheetOptions,
+) -> ImageResult<Vec<u8>> {
+    encode_png(&figure_sheet(entries, field_strength, options))
+}
+
+/// [`sweep_frames`], encoded as a looping GIF.
+///
+/// # Errors
+/// If the sweep has more than [`SweepOptions::MAX_PIXELS`], or encoding fails.
+pub fn sweep_gif(peak: &Peak, field_strength: f64, options: SweepOptions) -> ImageResult<Vec<u8>> {
+    if options.pixel_count() > SweepOptions::MAX_PIXELS {
+        return Err(ImageError::Limits(LimitError::from_kind(
+            LimitErrorKind::InsufficientMemory,
+        )));
+    }
+    let mut gif = Vec::new();
+    {
+        let mut encoder = GifEncoder::new_with_speed(&mut gif, GIF_SPEED);
+        encoder.set_repeat(Repeat::Infinite)?;
+        let delay = Delay::from_numer_denom_ms(1000, options.frame_rate);
+        encoder.encode_frames(
+            sweep_frames(peak, field_strength, options)
+                .map(|frame| Frame::from_parts(frame, 0, 0, delay)),
+        )?;
+    }
+    Ok(gif)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RenderQuality, SheetOptions, SweepOptions, Viewport};
+    use crate::numerics::distribution::distribution_sum::DistributionSum;
+    use crate::numerics::distribution::lineshape::Lineshape;
+    use crate::numerics::distribution::RenormalizedDistribution;
+    use crate::peak::{Peak, Splitter};
+
+    /// A user-defined lineshape: an isosceles triangle, whose base is twice the FWHM.
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    struct Triangular {
+        μ: f64,
+        half_base: f64,
+        normalization: f64,
+    }
+
+    impl RenormalizedDistribution for Triangular {
+        fn with_fwhm_normalized(μ: f64, fwhm: f64, normalization: f64) -> Self {
+            Self {
+                μ,
+                half_base: fwhm,
+                normalization,
+            }
+        }
+
+        fn with_normalization(self, normalization: f64) -> Self {
+            Self {
+                normalization,
+                ..self
+            }
+        }
+
+        fn μ(&self) -> f64 {
+            self.μ
+        }
+
+        fn fwhm(&self) -> f64 {
+            self.half_base
+        }
+
+        fn normalization(&self) -> f64 {
+            self.normalization
+        }
+
+        fn evaluate(&self, x: f64) -> f64 {
+            let height = self.normalization / self.half_base;
+            (height * (1. - (x - self.μ).abs() / self.half_base)).max(0.)
+        }
+
+        fn evaluate_cdf(&self, x: f64) -> f64 {
+            let t = ((x - self.μ) / self.half_base).clamp(-1., 1.);
+            let fraction = if t < 0. {
+                0.5 * (1. + t).powi(2)
+            } else {
+                1. - 0.5 * (1. - t).powi(2)
+            };
+            self.normalization * fraction
+        }
+    }
+
+    #[test]
+    fn render_custom_lineshape() {
+        let peak = Peak {
+            proton_count: 1,
+            splitters: vec![Splitter::new(1, 7.), Splitter::new(1, 7.)],
+            ..Default::default()
+        };
+        let waveform: DistributionSum<Triangular> =
+            peak.build_multiplet_cascade().final_waveform(400.);
+        approx::assert_abs_diff_eq!(waveform.evaluate_cdf(1.), 1., epsilon = 1e-12);
+        // The two coincident central lines merge, leaving a 1:2:1 triplet.
+        let merged = waveform.merge_coincident();
+        assert_eq!(merged.components().count(), 3);
+        approx::assert_relative_eq!(merged.evaluate(0.), 2. * merged.evaluate(7. / 400.));
+
+        let viewport = Viewport {
+            width: 200,
+            height: 100,
+            x_window: -0.05..=0.05,
+            y_window: -1.0..=merged.max() * 1.1,
+            axes: false,
+            quality: RenderQuality::Draft,
+        };
+        let image = super::render_waveform(&merged, &viewport);
+        assert!(image.pixels().any(|&pixel| pixel == super::CURVE_COLOR));
+    }
+
+    #[test]
+    fn render_peak() {
+        let peak = Peak {
+            proton_count: 2,
+            splitters: vec![Splitter::new(3, 7.)],
+            ..Default::default()
+        };
+        let viewport = Viewport {
+            width: 320,
+            height: 120,
+            x_window: -0.1..=0.1,
+            y_window: -10.0..=500.0,
+            axes: true,
+            quality: RenderQuality::Draft,
+        };
+        let image = super::render_peak(&peak, 400., Lineshape::Gaussian, &viewport);
+        assert_eq!(image.dimensions(), (320, 120));
+        let curve_pixels = image
+            .pixels()
+            .filter(|&&pixel| pixel == super::CURVE_COLOR)
+            .count();
+        // The curve is continuous across the width, and rises to form peaks (into the upper half).
+        assert!(curve_pixels > 320, "only {curve_pixels} pixels drawn");
+        let drawn_columns = (0..320)
+            .filter(|&x| (0..120).any(|y| *image.get_pixel(x, y) == super::CURVE_COLOR))
+            .count();
+        assert_eq!(drawn_columns, 320);
+        assert!((0..320).any(|x| *image.get_pixel(x, 60) == super::CURVE_COLOR));
+    }
+
+    #[test]
+    fn print_resolution() {
+        let peak = Peak {
+            proton_count: 1,
+            splitters: vec![Splitter::new(2, 7.)],
+            ..Default::default()
+        };
+        let options = SweepOptions {
+            width: 600,
+            height: 400,
+            quality: RenderQuality::Print,
+            ..Default::default()
+        };
+        // 8 by 5⅓ inches at 300 DPI.
+        let image = super::final_frame(&peak, 400., options, 4).unwrap();
+        assert_eq!(image.dimensions(), (2400, 1600));
+        let is_background = |pixel: &image::Rgba<u8>| *pixel == super::BACKGROUND_COLOR;
+        let is_curve = |pixel: &image::Rgba<u8>| *pixel == super::CURVE_COLOR;
+        // The stroke is several pixels thick in every column, and its edges are blended into the
+        // background.
+        for x in [10, 600, 1200, 1800, 2390] {
+            let thickness = (0..1600)
+                .filter(|&y| is_curve(image.get_pixel(x, y)))
+                .count();
+            assert!((3..40).contains(&thickness), "{thickness} pixels at {x}");
+        }
+        assert!(image
+            .pixels()
+            .any(|pixel| !is_background(pixel) && !is_curve(pixel)));
+
+        // Draft rendering has no intermediate shades.
+        let draft = super::final_frame(
+            &peak,
+            400.,
+            SweepOptions {
+                quality: RenderQuality::Draft,
+                ..options
+            },
+            4,
+        )
+        .unwrap();
+        assert_eq!(draft.dimensions(), (2400, 1600));
+        assert!(draft
+            .pixels()
+            .all(|pixel| is_background(pixel) || is_curve(pixel)));
+        assert!(!super::final_frame_png(&peak, 400., options, 1)
+            .unwrap()
+            .is_empty());
+        // Nor is an image of too many pixels rendered.
+        let huge = SweepOptions {
+            width: 1920,
+            height: 1080,
+            ..options
+        };
+        assert!(super::final_frame_pixel_count(huge, 8) > super::MAX_STILL_PIXELS);
+        assert!(super::final_frame(&peak, 400., huge, 8).is_err());
+    }
+
+    #[test]
+    fn figure_sheet() {
+        let entries = [1, 2, 3, 6]
+            .map(|n| {
+                let peak = Peak {
+                    splitters: vec![Splitter::new(n, 7.)],
+                    ..Default::default()
+                };
+                (format!("{n} neighbors"), peak)
+            })
+            .to_vec();
+        let options = SheetOptions {
+            columns: 3,
+            cell_width: 200,
+            cell_height: 120,
+            quality: RenderQuality::Draft,
+        };
+        let sheet = super::figure_sheet(&entries, 400., options);
+        // Four cells fill two rows of three.
+        assert_eq!(sheet.dimensions(), (600, 240));
+        let is_curve = |pixel: &image::Rgba<u8>| *pixel == super::CURVE_COLOR;
+        let cell_has_curve = |column: u32, row: u32| {
+            (0..200).any(|x| {
+                (0..120).any(|y| is_curve(sheet.get_pixel(column * 200 + x, row * 120 + y)))
+            })
+        };
+        assert!(cell_has_curve(0, 0) && cell_has_curve(2, 0) && cell_has_curve(0, 1));
+        assert!(!cell_has_curve(1, 1));
+        // Each label is drawn above its spectrum.
+        let label_rows = 0..18;
+        assert!((0..200).any(|x| label_rows
+            .clone()
+            .any(|y| *sheet.get_pixel(x, y) != super::BACKGROUND_COLOR)));
+        assert!(
+            !super::figure_sheet_png(&entries, 400., SheetOptions::default())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn sweep_limits() {
+        let peak = Peak {
+            splitters: vec![Splitter::new(1, 7.)],
+            ..Default::default()
+        };
+        let options = SweepOptions {
+            width: 64,
+            height: 48,
+            frame_rate: 5,
+            duration: 1.,
+            quality: RenderQuality::Draft,
+        };
+        assert_eq!(options.frame_count(), 6);
+        assert_eq!(super::sweep_frames(&peak, 400., options).count(), 6);
+        assert!(!super::sweep_gif(&peak, 400., options).unwrap().is_empty());
+
+        let oversized = SweepOptions {
+            width: 1920,
+            height: 1080,
+            frame_rate: 60,
+            duration: 30.,
+            ..options
+        };
+        assert!(oversized.pixel_count() > SweepOptions::MAX_PIXELS);
+        assert!(super::sweep_gif(&peak, 400., oversized).is_err());
+    }
+}
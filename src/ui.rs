@@ -1,26 +1,51 @@
 mod animation;
+mod coupling_arcs;
+mod lineshape_gallery;
+mod palette;
+mod session;
+mod shortcuts;
 mod splitting_diagram;
+mod toasts;
 pub mod utils;
+mod view_link;
 
 use std::collections::HashMap;
+use std::ops::RangeInclusive;
 use std::sync::LazyLock;
 
-use eframe::egui::plot::{Line, PlotPoints, PlotUi};
+use eframe::egui::plot::{
+    Bar, BarChart, Corner, Legend, Line, LineStyle, PlotBounds, PlotPoint, PlotPoints, PlotUi,
+    Points, Polygon, Text,
+};
 use eframe::egui::{
-    self, Align, Button, CentralPanel, ComboBox, Context, DragValue, FontData, FontDefinitions,
-    FontTweak, Layout, RichText, ScrollArea, SidePanel, Slider, TextStyle, Ui,
+    self, Align, Align2, Button, CentralPanel, Checkbox, ComboBox, Context, CursorIcon, DragValue,
+    FontData, FontDefinitions, FontTweak, Layout, RichText, ScrollArea, SidePanel, Slider,
+    TextEdit, TextStyle, Ui,
 };
-use eframe::epaint::{Color32, FontFamily, Rect, Vec2};
+use eframe::epaint::{Color32, FontFamily, Rect, Stroke, Vec2};
 use egui_extras::{Column, TableBuilder};
 use itertools::Itertools;
 use maplit::hashmap;
+use serde::{Deserialize, Serialize};
 
-use self::animation::CyclicallyAnimatedF64;
-use crate::numerics;
-use crate::numerics::distribution::lorentzian::Lorentzian;
+use self::animation::{AnimationClock, CyclicallyAnimatedF64};
+use self::shortcuts::Action;
+use self::splitting_diagram::{ConnectorStyle, DiagramOptions, DiagramScale};
+use self::toasts::Toasts;
+use self::utils::InteractionAxes;
+use crate::export::Normalization;
+use crate::numerics::distribution::distribution_sum::DistributionSum;
+use crate::numerics::distribution::lineshape::{AnyDistribution, Lineshape};
+use crate::numerics::distribution::pseudo_voigt::PseudoVoigt;
 use crate::numerics::distribution::RenormalizedDistribution;
-use crate::peak::{self, FractionalStageIndex, MultipletCascade, Peak, Splitter};
+use crate::numerics::fid::{Apodization, Fid};
+use crate::numerics::Easing;
+use crate::peak::{
+    self, FractionalStageIndex, MultipletCascade, PatternNaming, Peak, SortOrder, Splitter,
+};
+use crate::render::{self, RenderQuality, SheetOptions, SweepOptions};
 use crate::utils::StoreOnNthCall;
+use crate::{export, numerics};
 
 macro_rules! load_font {
     ($name:literal) => {
@@ -32,38 +57,466 @@ macro_rules! load_font {
     };
 }
 
-pub type PeakGeometry = Lorentzian;
+pub type PeakGeometry = AnyDistribution;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Preset {
+    pub proton_count: u32,
+    pub splitters: Vec<Splitter>,
+}
 
-pub static PEAK_PRESETS: LazyLock<HashMap<&str, Vec<Splitter>>> = LazyLock::new(|| {
+pub static PEAK_PRESETS: LazyLock<HashMap<&str, Preset>> = LazyLock::new(|| {
     hashmap! {
-        "Et₂O (CH₂)" => vec![Splitter { j: 7., n: 3 }],
-        "Et₂O (CH₃)" => vec![Splitter { j: 7., n: 2 }],
+        "Et₂O (CH₂)" => Preset { proton_count: 2, splitters: vec![Splitter::new(3, 7.)] },
+        "Et₂O (CH₃)" => Preset { proton_count: 3, splitters: vec![Splitter::new(2, 7.)] },
     }
 });
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+/// When to draw the multiplet as a density strip in lieu of its individual contributions.
+enum DensityStrip {
+    /// Only for highly complex patterns.
+    Auto,
+    On,
+    Off,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+/// Where the integral is drawn.
+enum IntegralMode {
+    /// In a separate plot overlaid on top of the peak plot.
+    Overlaid,
+    /// As a line within the peak plot itself, in its upper portion.
+    Combined,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+/// The region of the peak plot occupied by the integral, as fractions of the plot's height.
+struct IntegralPlacement {
+    height: f32,
+    /// Distance from the top of the plot.
+    offset: f32,
+}
+
+impl Default for IntegralPlacement {
+    fn default() -> Self {
+        Self {
+            height: 0.2,
+            offset: 0.025,
+        }
+    }
+}
+
+impl IntegralPlacement {
+    const HEIGHT_RANGE: RangeInclusive<f32> = 0.05..=0.5;
+
+    /// The placement, brought within the ranges of its sliders, such that the integral lies
+    /// within the plot. Values that are not numbers are replaced by the defaults.
+    fn clamped(self) -> Self {
+        let default = Self::default();
+        let or_default = |value: f32, default: f32| if value.is_nan() { default } else { value };
+        let height = or_default(self.height, default.height)
+            .clamp(*Self::HEIGHT_RANGE.start(), *Self::HEIGHT_RANGE.end());
+        let offset = or_default(self.offset, default.offset).clamp(0., 1. - height);
+        Self { height, offset }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+/// A broad signal underlying the multiplet, such as from a polymer or an exchanging proton.
+struct Background {
+    enabled: bool,
+    lineshape: Lineshape,
+    /// Position relative to the center of the multiplet, in Hz.
+    offset: f64,
+    /// In Hz.
+    fwhm: f64,
+    /// Area relative to that of the multiplet.
+    intensity: f64,
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            lineshape: Lineshape::Gaussian,
+            offset: 0.,
+            fwhm: 60.,
+            intensity: 0.5,
+        }
+    }
+}
+
+impl Background {
+    fn distribution(&self, field_strength: f64) -> PeakGeometry {
+        self.lineshape.with_fwhm_normalized(
+            peak::j_to_ppm(self.offset, field_strength),
+            peak::j_to_ppm(self.fwhm, field_strength),
+            self.intensity,
+        )
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+/// How the area beneath a curve is filled.
+struct FillStyle {
+    enabled: bool,
+    /// If absent, that of the curve.
+    color: Option<Color32>,
+    /// From zero to [`Self::MAX_OPACITY`].
+    opacity: f32,
+}
+
+impl Default for FillStyle {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            color: None,
+            opacity: Self::LAYER_OPACITY,
+        }
+    }
+}
+
+impl FillStyle {
+    /// That with which the plot fills beneath a line, which cannot be changed. Denser fills are
+    /// built up of several layers.
+    const LAYER_OPACITY: f32 = 0.05;
+    const MAX_OPACITY: f32 = 0.5;
+
+    /// The default fill, but disabled.
+    fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Default::default()
+        }
+    }
+
+    /// The number of layers whose combined opacity is closest to that requested.
+    fn layer_count(self) -> usize {
+        if !self.enabled {
+            return 0;
+        }
+        let opacity = self.opacity.clamp(0., Self::MAX_OPACITY);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let count = ((1. - opacity).ln() / (1. - Self::LAYER_OPACITY).ln()).round() as usize;
+        count
+    }
+
+    /// Fill the area between `points` and the baseline, beneath a curve of `curve_color`.
+    fn draw(self, plot_ui: &mut PlotUi, points: &[[f64; 2]], curve_color: Color32) {
+        for _ in 0..self.layer_count() {
+            plot_ui.line(
+                Line::new(points.to_vec())
+                    .color(self.color.unwrap_or(curve_color))
+                    .width(0.)
+                    .fill(0.),
+            );
+        }
+    }
+
+    /// Controls for the fill, in a single row.
+    fn controls(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.enabled, "");
+            ui.add_enabled_ui(self.enabled, |ui| {
+                ui.add(
+                    Slider::new(&mut self.opacity, 0.0..=Self::MAX_OPACITY)
+                        .fixed_decimals(2)
+                        .text("opacity"),
+                );
+                let mut custom = self.color.is_some();
+                if ui
+                    .checkbox(&mut custom, "Custom color")
+                    .on_hover_text("Otherwise, that of the curve")
+                    .changed()
+                {
+                    self.color = custom.then_some(Color32::GRAY);
+                }
+                if let Some(color) = &mut self.color {
+                    ui.color_edit_button_srgba(color);
+                }
+            });
+        });
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+/// A configuration whose drawing exceeded the rendering budget. Once it has done so in
+/// [`Protonolysis::OVERRUNS_BEFORE_FALLBACK`] consecutive frames, only its lines' positions are
+/// drawn until the configuration changes.
+struct OverBudget {
+    peak: Peak,
+    field_strength: f64,
+    /// Number of lines in the displayed stage.
+    line_count: usize,
+    /// Time taken by the latest frame.
+    elapsed_ms: f64,
+    /// Number of consecutive frames that exceeded the budget.
+    overruns: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+/// How the view-stage slider displays fractional values.
+struct StageFormat {
+    decimals: usize,
+    /// Values within this distance of a whole number are displayed as that number.
+    snap_epsilon: f64,
+}
+
+impl Default for StageFormat {
+    fn default() -> Self {
+        Self {
+            decimals: 2,
+            snap_epsilon: 8e-3,
+        }
+    }
+}
+
+impl StageFormat {
+    /// Whether `x` is shown as a whole level.
+    fn is_whole(self, x: f64) -> bool {
+        approx::abs_diff_eq!(x, x.round(), epsilon = self.snap_epsilon)
+    }
+
+    fn format(self, x: f64) -> String {
+        if self.is_whole(x) {
+            format!("{x:.0}")
+        } else {
+            format!("{x:.*}", self.decimals)
+        }
+    }
+}
+
+#[allow(clippy::struct_excessive_bools)]
 pub struct Protonolysis {
     field_strength: f64,
+    /// The FWHM (Hz) of the peak in new sessions.
+    default_fwhm: f64,
+    /// Whether the FWHM may exceed [`Self::MAX_FWHM`].
+    broad_lines: bool,
     selected_preset: &'static str,
     peak: Peak,
     view_stage: CyclicallyAnimatedF64,
+    /// Animates the FWHM back and forth across its range, to demonstrate the loss of resolution.
+    fwhm_sweep: CyclicallyAnimatedF64,
+    /// The FWHM (Hz) to restore once the sweep is stopped, if sweeping.
+    fwhm_before_sweep: Option<f64>,
+    /// Whether to mark each line by whether it has merged with a neighbor.
+    show_merges: bool,
+    stage_format: StageFormat,
+    pattern_naming: PatternNaming,
+    snap_view_stage: bool,
+    /// Speed of the play-once sweep relative to the regular animation.
+    play_once_speed: f64,
+    sweep_options: SweepOptions,
+    /// The fills beneath the spectrum, and beneath the curves overlaid on it for comparison.
+    spectrum_fill: FillStyle,
+    overlay_fill: FillStyle,
+    /// Factor by which the still image is larger than the frames of the animation.
+    still_scale: u32,
+    sheet_options: SheetOptions,
+    /// The presets drawn on the figure sheet, besides (optionally) the current configuration.
+    sheet_presets: Vec<&'static str>,
+    sheet_includes_current: bool,
+    export_normalization: Normalization,
+    /// Shift separation (ppm) to coupling partners, for the first-order check.
+    partner_separation: f64,
     show_integral: bool,
+    integral_mode: IntegralMode,
+    integral_placement: IntegralPlacement,
+    /// Whether to mark the extent of the multiplet on the integral, and label the rise across it.
+    annotate_integral: bool,
     show_splitting_diagram: bool,
+    show_resolved_couplings: bool,
+    show_coupling_arcs: bool,
     show_peaklets: bool,
+    show_sticks: bool,
+    show_integration_bars: bool,
+    /// Whether coincident lines are merged in the integration bar chart.
+    merge_coincident_lines: bool,
+    show_sample_overlay: bool,
+    highlight_overlaps: bool,
+    density_strip: DensityStrip,
+    diagram_options: DiagramOptions,
+    interaction_axes: InteractionAxes,
+    /// The visible range of the x-axis (shared between the peak and integral plots), in ppm.
+    linked_x_axis: (f64, f64),
+    /// The visible range of the y-axis of the peak plot.
+    linked_y_axis: (f64, f64),
+    /// The field strength at which the window of the viewer was last set, such that it can be
+    /// rescaled to keep showing the same range in Hz when the field strength changes.
+    windowed_field_strength: f64,
+    /// A range to which the x-axis should be set on the next frame.
+    requested_x_axis: Option<(f64, f64)>,
+    /// A range to which the y-axis of the peak plot should be set on the next frame.
+    requested_y_axis: Option<(f64, f64)>,
+    /// The URL of the page, without its fragment, from which links to the current view are made.
+    #[cfg(target_arch = "wasm32")]
+    link_base: String,
+    show_plot_background: bool,
+    /// Display-only factor applied to the FWHM of the drawn lines; see
+    /// [`Self::is_resolution_enhanced`].
+    resolution_enhancement: f64,
+    /// Whether to draw the spectrum as if digitized at [`Self::points_per_hz`].
+    limit_digital_resolution: bool,
+    points_per_hz: f64,
+    show_processed_spectrum: bool,
+    apodization: Apodization,
+    fixed_x_scale: bool,
+    /// Horizontal scale of the spectrum when it is fixed, in ppm per pixel.
+    x_scale: f64,
+    /// The intensity window of the spectrum when it is pinned, such that splitting visibly lowers
+    /// the lines instead of being rescaled away.
+    pinned_y_window: Option<(f64, f64)>,
+    presentation_mode: bool,
+    presentation_show_diagram: bool,
+    /// Whether the list of keyboard shortcuts is open.
+    show_shortcuts: bool,
+    show_lineshape_gallery: bool,
     side_panel_width: StoreOnNthCall<2, f32>,
+    sort_order: SortOrder,
+    /// Value applied to every splitter by "Set all J".
+    bulk_j: f64,
+    /// Shift (ppm) hovered in any of the linked views, shown as a crosshair in all of them.
+    crosshair: Option<f64>,
+    next_crosshair: Option<f64>,
+    /// Peaklet of the splitting diagram, as `(stage, index within stage)`, whose lineage is
+    /// highlighted.
+    pinned_peaklet: Option<(usize, usize)>,
+    /// The center (ppm) of the line whose half-maximum point is being dragged to set the FWHM.
+    fwhm_grab: Option<f64>,
+    background: Background,
+    /// Whether to overlay the displayed stage at each of [`Self::comparison_fwhms`] (Hz).
+    compare_fwhms: bool,
+    comparison_fwhms: [f64; 2],
+    /// Regions of the spectrum, in ppm, whose integrals are read out.
+    integration_regions: Vec<(f64, f64)>,
+    /// Where the integration region being selected was started.
+    region_anchor: Option<f64>,
     cached_partial_cascade: MultipletCascade,
+    toasts: Toasts,
+    /// Time (ms) after which drawing the spectrum is considered too slow; see [`OverBudget`].
+    render_budget_ms: f64,
+    last_over_budget: Option<OverBudget>,
+    /// Time (ms) taken by the last call to [`Self::update_partial_cascade`], until it is counted
+    /// towards the frame that follows it.
+    cascade_build_ms: f64,
+    /// Distance (in points on screen) below which neighboring lines are considered
+    /// indistinguishable as displayed, whether or not they are resolved.
+    min_display_separation: f32,
+    /// Scale of the viewer when it was last drawn, in ppm per point.
+    displayed_ppm_per_point: Option<f64>,
+}
+
+impl DensityStrip {
+    const ALL: [Self; 3] = [Self::Auto, Self::On, Self::Off];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Auto => "Auto",
+            Self::On => "On",
+            Self::Off => "Off",
+        }
+    }
+}
+
+impl IntegralMode {
+    const ALL: [Self; 2] = [Self::Overlaid, Self::Combined];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Overlaid => "Overlaid",
+            Self::Combined => "Combined",
+        }
+    }
 }
 
 impl Protonolysis {
     const ANIMATION_TIME_PER_STAGE: f64 = 2.0;
+    /// Offset (Hz) of the center of mass from the center of the peak beyond which the multiplet
+    /// is flagged as asymmetric.
+    const CENTER_OF_MASS_TOLERANCE: f64 = 1e-9;
+    const DEFAULT_FWHM_KEY: &str = "default_fwhm";
+    const DEFAULT_MIN_DISPLAY_SEPARATION: f32 = 4.;
     const DEFAULT_PATTERN: &str = "Et₂O (CH₂)";
+    /// That of a frame at 60 fps.
+    const DEFAULT_RENDER_BUDGET_MS: f64 = 16.;
+    /// Half-width of the default window of the viewer, in ppm at [`Self::DEFAULT_X_FIELD`].
     const DEFAULT_X: f64 = 0.15;
+    const DEFAULT_X_FIELD: f64 = 600.;
     const DEFAULT_Y: f64 = 400.;
+    const DENSITY_STRIP_BINS: usize = 400;
+    const DIAGRAM_OPTIONS_KEY: &str = "diagram_options";
+    const FIELD_STRENGTH_KEY: &str = "field_strength";
+    const FIELD_STRENGTH_PRESETS: [f64; 6] = [300., 400., 500., 600., 700., 900.];
+    /// An explanation of first-order multiplets and where they break down.
+    const FIRST_ORDER_DOCS_URL: &str = "https://en.wikipedia.org/wiki/J-coupling";
+    /// Δν/J below which the first-order treatment is flagged as questionable.
+    const FIRST_ORDER_THRESHOLD: f64 = 10.;
+    /// Margin, in FWHMs, left around the multiplet when framing it.
+    const FRAME_PADDING: f64 = 2.;
+    /// Duration (s) of the FWHM sweep from narrowest to broadest.
+    const FWHM_SWEEP_TIME: f64 = 4.;
+    /// Margin, in FWHMs, around the lines of the multiplet within which its integral is annotated.
+    const INTEGRAL_ANNOTATION_WIDTH: f64 = 3.;
+    const INTEGRAL_PLACEMENT_KEY: &str = "integral_placement";
     const INTEGRAL_WIDTH: f64 = 15.;
+    const INTEGRATION_BARS_WIDTH: f32 = 140.;
+    /// Okabe-Ito reddish purple.
+    const INTEGRATION_REGION_COLOR: Color32 = Color32::from_rgb(204, 121, 167);
+    const JCAMP_DX_FILE_NAME: &str = "protonolysis-spectrum.jdx";
+    /// Range of the coupling constants, in Hz, that the controls allow.
+    const J_RANGE: RangeInclusive<f64> = 0.2..=20.0;
+    const MAX_BROAD_FWHM: f64 = 100.;
+    /// Number of lines of a peak beyond which only their positions are drawn, without first
+    /// waiting for the spectrum to exceed the rendering budget.
+    const MAX_DRAWN_PEAKLET_COUNT: u32 = 2_000;
+    /// Upper bound of the FWHM, in Hz, unless broad lines are enabled.
+    const MAX_FWHM: f64 = 5.;
+    /// Upper bound of the number of lines of an imported peak, which is the most the controls
+    /// can produce.
+    const MAX_PEAKLET_COUNT: u32 = 10_000;
     const MAX_PROTON_COUNT: u32 = 9;
+    /// Upper bound of the skew of lines, in FWHMs.
+    const MAX_SKEW: f64 = 3.;
     const MAX_SPLITTERS: usize = 4;
+    const MAX_X_WINDOW: f64 = 50.;
+    const MIN_DISPLAY_SEPARATION_RANGE: RangeInclusive<f32> = 1.0..=20.0;
+    const MIN_FWHM: f64 = 0.1;
+    const MIN_SPECTRAL_WIDTH: f64 = 20.;
+    /// Area of an integration region, as a fraction of that of the peak, below which the region
+    /// is taken to be empty, such that other regions are not compared to it.
+    const NEGLIGIBLE_AREA: f64 = 1e-6;
+    /// Okabe-Ito orange, which is distinguishable from the light blue of resolved peaklets.
+    const OVERLAPPING_PEAKLET_COLOR: Color32 = Color32::from_rgb(230, 159, 0);
+    const OVERLAY_FILL_KEY: &str = "overlay_fill";
+    /// Number of consecutive frames exceeding the rendering budget after which only the positions
+    /// of the lines are drawn, such that a one-off hitch (e.g., resizing the window) does not.
+    const OVERRUNS_BEFORE_FALLBACK: u32 = 3;
+    const PARTNER_SEPARATION_RANGE: RangeInclusive<f64> = 0.0..=10.0;
     const PEAKLET_WIDTH: f64 = 6.;
+    const PEAK_KEY: &str = "peak";
+    const PLAY_ONCE_SPEED_RANGE: RangeInclusive<f64> = 0.25..=4.0;
+    const POINTS_PER_HZ_RANGE: RangeInclusive<f64> = 0.05..=10.0;
+    const PRESENTATION_TEXT_SCALE: f32 = 1.6;
+    const RENDER_BUDGET_RANGE_MS: RangeInclusive<f64> = 4.0..=500.0;
+    const RESOLUTION_ENHANCEMENT_RANGE: RangeInclusive<f64> = 0.1..=1.0;
     const SAMPLES: usize = 5000;
+    const SESSION_FILE_NAME: &str = "protonolysis-session.ron";
+    /// The instrument frequency at which the figure sheet is drawn: a standard, rather than the
+    /// current, field.
+    const SHEET_FIELD_STRENGTH: f64 = 400.;
+    const SHEET_FILE_NAME: &str = "protonolysis-sheet.png";
+    const SPECTRUM_FILE_NAME: &str = "protonolysis-spectrum.csv";
+    const SPECTRUM_FILL_KEY: &str = "spectrum_fill";
+    const STILL_FILE_NAME: &str = "protonolysis-multiplet.png";
+    const SWEEP_FILE_NAME: &str = "protonolysis-sweep.gif";
     const TOO_COMPLEX_THRESHOLD: u32 = 100;
+    /// Range of the fixed horizontal scale, in ppm per point.
+    const X_SCALE_RANGE: RangeInclusive<f64> = 1e-6..=1e-2;
 
     #[must_use]
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
@@ -99,43 +552,236 @@ impl Protonolysis {
         style.spacing.combo_width = 120.;
         cc.egui_ctx.set_style(style);
 
-        let peak = Peak {
-            splitters: PEAK_PRESETS[Self::DEFAULT_PATTERN].clone(),
-            ..Default::default()
-        };
+        let default_fwhm = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, Self::DEFAULT_FWHM_KEY))
+            .unwrap_or(Peak::default().fwhm);
+        let preset = &PEAK_PRESETS[Self::DEFAULT_PATTERN];
+        let peak = cc
+            .storage
+            .and_then(|storage| eframe::get_value::<Peak>(storage, Self::PEAK_KEY))
+            .and_then(|mut peak| Self::check_peak_limits(&mut peak).is_ok().then_some(peak))
+            .unwrap_or_else(|| Peak {
+                proton_count: preset.proton_count,
+                splitters: preset.splitters.clone(),
+                fwhm: default_fwhm,
+                ..Default::default()
+            });
+        let field_strength = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, Self::FIELD_STRENGTH_KEY))
+            .filter(|&frequency| peak::validate_frequency(frequency).is_ok())
+            .unwrap_or(600.);
         let cached_partial_cascade = peak.build_multiplet_cascade();
-        Self {
-            field_strength: 600.,
+        let mut app = Self {
+            field_strength,
+            default_fwhm,
+            broad_lines: peak.fwhm > Self::MAX_FWHM,
             selected_preset: Self::DEFAULT_PATTERN,
             peak,
             view_stage: CyclicallyAnimatedF64::new(1., 0.0..=1.0, Self::ANIMATION_TIME_PER_STAGE),
+            fwhm_sweep: CyclicallyAnimatedF64::new(
+                Self::MIN_FWHM,
+                Self::MIN_FWHM..=Self::MAX_FWHM,
+                Self::FWHM_SWEEP_TIME,
+            ),
+            fwhm_before_sweep: None,
+            show_merges: false,
+            stage_format: StageFormat::default(),
+            pattern_naming: PatternNaming::default(),
+            snap_view_stage: false,
+            play_once_speed: 1.,
+            sweep_options: SweepOptions::default(),
+            spectrum_fill: cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, Self::SPECTRUM_FILL_KEY))
+                .unwrap_or_default(),
+            overlay_fill: cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, Self::OVERLAY_FILL_KEY))
+                .unwrap_or_else(FillStyle::disabled),
+            still_scale: 1,
+            sheet_options: SheetOptions::default(),
+            sheet_presets: PEAK_PRESETS.keys().copied().sorted().collect(),
+            sheet_includes_current: false,
+            export_normalization: Normalization::default(),
+            partner_separation: 1.,
             show_integral: true,
+            integral_mode: IntegralMode::Overlaid,
+            integral_placement: cc
+                .storage
+                .and_then(|storage| {
+                    eframe::get_value::<IntegralPlacement>(storage, Self::INTEGRAL_PLACEMENT_KEY)
+                })
+                .map(IntegralPlacement::clamped)
+                .unwrap_or_default(),
+            annotate_integral: false,
             show_splitting_diagram: true,
+            show_resolved_couplings: false,
+            show_coupling_arcs: false,
             show_peaklets: false,
+            show_sticks: false,
+            show_integration_bars: false,
+            merge_coincident_lines: true,
+            show_sample_overlay: false,
+            highlight_overlaps: true,
+            density_strip: DensityStrip::Auto,
+            diagram_options: cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, Self::DIAGRAM_OPTIONS_KEY))
+                .unwrap_or_default(),
+            interaction_axes: InteractionAxes::default(),
+            linked_x_axis: (-Self::DEFAULT_X, Self::DEFAULT_X),
+            linked_y_axis: (Self::DEFAULT_Y * -0.05, Self::DEFAULT_Y * 1.1),
+            windowed_field_strength: Self::DEFAULT_X_FIELD,
+            requested_x_axis: None,
+            requested_y_axis: None,
+            #[cfg(target_arch = "wasm32")]
+            link_base: cc.integration_info.web_info.location.url.clone(),
+            show_plot_background: true,
+            resolution_enhancement: 1.,
+            limit_digital_resolution: false,
+            points_per_hz: 0.5,
+            show_processed_spectrum: false,
+            apodization: Apodization::default(),
+            fixed_x_scale: false,
+            x_scale: 2e-4,
+            pinned_y_window: None,
+            presentation_mode: false,
+            presentation_show_diagram: false,
+            show_shortcuts: false,
+            show_lineshape_gallery: false,
             side_panel_width: StoreOnNthCall::default(),
+            sort_order: SortOrder::default(),
+            bulk_j: 7.,
+            crosshair: None,
+            next_crosshair: None,
+            pinned_peaklet: None,
+            fwhm_grab: None,
+            background: Background::default(),
+            compare_fwhms: false,
+            comparison_fwhms: [0.5, 2.],
+            integration_regions: Vec::new(),
+            region_anchor: None,
             cached_partial_cascade,
-        }
+            toasts: Toasts::default(),
+            render_budget_ms: Self::DEFAULT_RENDER_BUDGET_MS,
+            last_over_budget: None,
+            cascade_build_ms: 0.,
+            min_display_separation: Self::DEFAULT_MIN_DISPLAY_SEPARATION,
+            displayed_ppm_per_point: None,
+        };
+        app.update_animation_parameters();
+        app.view_stage.set_value_clamping(f64::INFINITY);
+        #[cfg(target_arch = "wasm32")]
+        app.load_view_link(&cc.egui_ctx, &cc.integration_info.web_info.location.hash);
+        app
     }
 }
 
 impl Protonolysis {
+    /// Check that `peak` is within the limits enforced by the controls, such that an imported
+    /// configuration can be edited as though it had been entered by hand. The shape parameters,
+    /// which only affect how lines are drawn, are clamped to the ranges of their sliders.
+    ///
+    /// # Errors
+    /// If `peak` has more than [`Self::MAX_SPLITTERS`] splitters, more protons (observed or
+    /// coupled) than [`Self::MAX_PROTON_COUNT`], more than [`Self::MAX_PEAKLET_COUNT`] lines, a
+    /// width or a coupling constant outside of those allowed.
+    fn check_peak_limits(peak: &mut Peak) -> Result<(), String> {
+        let proton_counts = 1..=Self::MAX_PROTON_COUNT;
+        if peak.splitters.len() > Self::MAX_SPLITTERS {
+            return Err(format!(
+                "{} coupled proton types, but at most {} are supported",
+                peak.splitters.len(),
+                Self::MAX_SPLITTERS,
+            ));
+        }
+        if !proton_counts.contains(&peak.proton_count) {
+            return Err(format!(
+                "{} observed protons, but between {} and {} are supported",
+                peak.proton_count,
+                proton_counts.start(),
+                proton_counts.end(),
+            ));
+        }
+        if let Some(splitter) = peak
+            .splitters
+            .iter()
+            .find(|splitter| !proton_counts.contains(&splitter.n))
+        {
+            return Err(format!(
+                "{} coupled protons of one type, but between {} and {} are supported",
+                splitter.n,
+                proton_counts.start(),
+                proton_counts.end(),
+            ));
+        }
+        if let Some(splitter) = peak
+            .splitters
+            .iter()
+            .find(|splitter| !Self::J_RANGE.contains(&splitter.j))
+        {
+            return Err(format!(
+                "coupling constant of {} Hz, but it must be between {} and {} Hz",
+                splitter.j,
+                Self::J_RANGE.start(),
+                Self::J_RANGE.end(),
+            ));
+        }
+        if !(Self::MIN_FWHM..=Self::MAX_BROAD_FWHM).contains(&peak.fwhm) {
+            return Err(format!(
+                "FWHM of {} Hz, but it must be between {} and {} Hz",
+                peak.fwhm,
+                Self::MIN_FWHM,
+                Self::MAX_BROAD_FWHM,
+            ));
+        }
+        let peaklet_count = peak
+            .splitters
+            .iter()
+            .try_fold(1_u32, |count, splitter| {
+                count.checked_mul(splitter.resultant_peaklet_count())
+            })
+            .filter(|&count| count <= Self::MAX_PEAKLET_COUNT);
+        if peaklet_count.is_none() {
+            return Err(format!(
+                "more than {} lines, which is too many to draw",
+                Self::MAX_PEAKLET_COUNT,
+            ));
+        }
+        peak.skew = numerics::finite_or(peak.skew, 0.).clamp(0., Self::MAX_SKEW);
+        peak.mixing = numerics::finite_or(peak.mixing, PseudoVoigt::DEFAULT_MIXING).clamp(0., 1.);
+        Ok(())
+    }
+
+    /// `stage`, if it can be viewed: a [`FractionalStageIndex`], or positive infinity for the
+    /// complete multiplet, to which [`Self::view_stage`] is clamped.
+    fn valid_view_stage(stage: f64) -> Option<f64> {
+        let is_complete = stage.is_infinite() && stage > 0.;
+        (is_complete || FractionalStageIndex::try_new(stage).is_ok()).then_some(stage)
+    }
+
     fn can_modify_configuration(&self) -> bool {
         !self.view_stage.is_animating()
     }
 
     fn update_animation_parameters(&mut self) {
         self.view_stage
-            .set_range_clamping(0.0..=(self.peak.splitters.len() as f64));
+            .set_range_clamping(0.0..=f64::from(self.peak.stage_count() - 1));
         self.view_stage
             .set_duration(Self::ANIMATION_TIME_PER_STAGE * f64::from(self.peak.stage_count()));
     }
 
     fn is_preset_modified(&self) -> bool {
-        self.peak.splitters != PEAK_PRESETS[self.selected_preset]
+        let preset = &PEAK_PRESETS[self.selected_preset];
+        self.peak.proton_count != preset.proton_count || self.peak.splitters != preset.splitters
     }
 
     fn apply_preset(&mut self) {
-        self.peak.splitters = PEAK_PRESETS[self.selected_preset].clone();
+        let preset = &PEAK_PRESETS[self.selected_preset];
+        self.peak.proton_count = preset.proton_count;
+        self.peak.splitters = preset.splitters.clone();
         self.update_animation_parameters();
         self.view_stage.set_value_clamping(f64::INFINITY);
     }
@@ -149,40 +795,37 @@ impl Protonolysis {
         ui.heading("¹H-NMR Splitting Patterns");
         ui.separator();
 
-        utils::two_column_grid("controls_instrument", ui, |ui| {
-            ui.label("Instrument frequency:");
-            ui.add_enabled(
-                enabled,
-                Slider::new(&mut self.field_strength, 40.0..=1200.0)
-                    .fixed_decimals(0)
-                    .step_by(20.)
-                    .suffix(" MHz"),
-            );
-            ui.end_row();
-
-            ui.label("Field strength:")
-                .on_hover_text("Strength of magnetic field of instrument");
-            ui.add_enabled(
-                false,
-                DragValue::new(&mut peak::mhz_to_tesla(self.field_strength))
-                    .max_decimals(1)
-                    .suffix(" T"),
-            );
-            ui.end_row();
+        // The sweep runs whether or not its controls are shown.
+        self.tick_fwhm_sweep(ui.ctx());
+        self.instrument_bar(ui, enabled);
+        ui.collapsing("Instrument and sample", |ui| {
+            self.instrument_controls(ui, enabled);
         });
 
         ui.separator();
 
         utils::two_column_grid("controls_peak", ui, |ui| {
-            ui.label("Peak FWHM:")
-                .on_hover_text("Full width at half maximum (i.e., broadness) of peaks");
+            ui.label("Label:")
+                .on_hover_text("Name of the proton environment, shown in the viewer and exports");
+            let hint = self
+                .peak
+                .name(self.pattern_naming)
+                .unwrap_or("<complex>".to_owned());
+            ui.add(
+                TextEdit::singleline(&mut self.peak.label)
+                    .hint_text(hint)
+                    .desired_width(ui.spacing().slider_width),
+            );
+            ui.end_row();
+
+            ui.label("Observed protons:").on_hover_text(
+                "Number of equivalent protons giving rise to this peak, which determines its \
+                    integration. Not to be confused with the number of protons they couple to, \
+                    which is configured below.",
+            );
             ui.add_enabled(
                 enabled,
-                Slider::new(&mut self.peak.fwhm, 0.1..=5.0)
-                    .logarithmic(true)
-                    .fixed_decimals(1)
-                    .smart_aim(false)
-                    .suffix(" Hz"),
+                Slider::new(&mut self.peak.proton_count, 1..=Self::MAX_PROTON_COUNT).suffix("H"),
             );
             ui.end_row();
 
@@ -228,38 +871,108 @@ impl Protonolysis {
                 }
                 if ui
                     .add_enabled(enabled, Button::new("Sort by J"))
-                    .on_hover_text("Sort by splitting constant in descending order")
+                    .on_hover_text(format!(
+                        "Sort by splitting constant in {} order",
+                        self.sort_order.name(),
+                    ))
+                    .clicked()
+                {
+                    self.peak.sort_by_j(self.sort_order);
+                }
+                let (order_symbol, next_order) = match self.sort_order {
+                    SortOrder::Ascending => ("↑", SortOrder::Descending),
+                    SortOrder::Descending => ("↓", SortOrder::Ascending),
+                };
+                if ui
+                    .add_enabled(enabled, Button::new(order_symbol))
+                    .on_hover_text("Toggle the sort order")
+                    .clicked()
+                {
+                    self.sort_order = next_order;
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Set all J to:");
+                ui.add_enabled(
+                    enabled,
+                    DragValue::new(&mut self.bulk_j)
+                        .clamp_range(Self::J_RANGE)
+                        .speed(0.1)
+                        .fixed_decimals(1)
+                        .suffix(" Hz"),
+                );
+                if ui
+                    .add_enabled(
+                        enabled && !self.peak.splitters.is_empty(),
+                        Button::new("Apply"),
+                    )
+                    .on_hover_text(
+                        "Give every coupled proton type the same splitting constant, producing an \
+                            apparent higher multiplet",
+                    )
                     .clicked()
                 {
-                    self.peak.sort_by_j();
+                    self.peak.set_all_j(self.bulk_j);
                 }
             });
 
+            let full_cascade = self.peak.build_multiplet_cascade();
+            // The separation (Hz) which lines need in order to be told apart on screen.
+            let min_displayed_separation = self
+                .displayed_ppm_per_point
+                .map(|scale| f64::from(self.min_display_separation) * scale * self.field_strength);
             let row_height = ui.text_style_height(&TextStyle::Body) + ui.spacing().item_spacing.y;
             let table = TableBuilder::new(ui)
                 .striped(true)
                 .cell_layout(Layout::left_to_right(Align::Center))
-                .columns(Column::auto_with_initial_suggestion(20.), 5)
+                .columns(Column::auto_with_initial_suggestion(20.), 6)
                 .header(row_height, |mut header| {
                     let mut col = |text: &str| {
                         header.col(|ui| {
                             ui.label(RichText::new(text).underline());
-                        });
+                        })
                     };
                     col("");
-                    col("Count");
+                    col("Count").1.on_hover_text(
+                        "Number of equivalent protons of this type coupled to the observed protons",
+                    );
                     col("J (Hz)");
                     col("Pattern");
+                    col("Resolved");
                     col("Actions");
                 });
+            let equal_j_groups = self.peak.equal_j_groups();
+            let mut mute_toggled = false;
             table.body(|mut body| {
                 let mut i = 0;
                 while i < self.peak.splitters.len() {
+                    // The stage introduced by this splitter, unless it is muted. Note that the
+                    // stages include the base peaklet.
+                    let stage = (!self.peak.splitters[i].muted).then(|| {
+                        self.peak.splitters[..=i]
+                            .iter()
+                            .filter(|splitter| !splitter.muted)
+                            .count()
+                    });
                     let row = |mut row: egui_extras::TableRow| {
+                        let splitter = &mut self.peak.splitters[i];
                         row.col(|ui| {
-                            ui.label((i + 1).to_string());
+                            let mut active = !splitter.muted;
+                            let response = ui
+                                .add_enabled(
+                                    enabled,
+                                    Checkbox::new(&mut active, (i + 1).to_string()),
+                                )
+                                .on_hover_text(
+                                    "Whether this splitter contributes to the pattern. Unchecked, \
+                                        it is kept in the list, but has no effect.",
+                                );
+                            if response.changed() {
+                                splitter.muted = !active;
+                                mute_toggled = true;
+                            }
                         });
-                        let splitter = &mut self.peak.splitters[i];
                         row.col(|ui| {
                             ui.style_mut().spacing.slider_width = 80.;
                             ui.style_mut().spacing.interact_size.x = 25.;
@@ -269,9 +982,22 @@ impl Protonolysis {
                             );
                         });
                         row.col(|ui| {
+                            // Rows in no group keep the space of the dot, to align the sliders.
+                            let (color, hover) = match equal_j_groups.get(i).copied().flatten() {
+                                Some(group) => (
+                                    palette::equal_j_color(group),
+                                    "Splitters marked with the same color share this J, so their \
+                                        lines coincide as an apparent higher multiplet",
+                                ),
+                                None => (Color32::TRANSPARENT, ""),
+                            };
+                            let dot = ui.label(RichText::new("●").color(color));
+                            if !hover.is_empty() {
+                                dot.on_hover_text(hover);
+                            }
                             ui.add_enabled(
                                 enabled,
-                                Slider::new(&mut splitter.j, 0.2..=20.0)
+                                Slider::new(&mut splitter.j, Self::J_RANGE)
                                     .fixed_decimals(1)
                                     .smart_aim(false),
                             );
@@ -279,12 +1005,37 @@ impl Protonolysis {
                         row.col(|ui| {
                             // TODO: this has poor discoverability.
                             let ratios = splitter.peak_ratios().join(":");
-                            let hover = if let Some(name) = splitter.name_pattern() {
-                                format!("{name}, {ratios}")
+                            let hover =
+                                if let Some(name) = splitter.name_pattern(self.pattern_naming) {
+                                    format!("{name}, {ratios}")
+                                } else {
+                                    ratios
+                                };
+                            ui.label(splitter.abbreviate_pattern(self.pattern_naming))
+                                .on_hover_text(hover);
+                        });
+                        row.col(|ui| {
+                            let Some(stage) = stage else {
+                                ui.label("–").on_hover_text("This splitter is muted");
+                                return;
+                            };
+                            let (badge, hover) = if !full_cascade.is_stage_resolved(stage) {
+                                ("✗", "The lines produced by this splitting overlap")
+                            } else if min_displayed_separation.is_some_and(|separation| {
+                                !full_cascade.is_stage_resolved_at(stage, separation)
+                            }) {
+                                (
+                                    "≈",
+                                    "The lines produced by this splitting are resolved, but too \
+                                        close to be told apart as displayed; zoom in to see them",
+                                )
                             } else {
-                                ratios
+                                ("✓", "The lines produced by this splitting are resolved")
                             };
-                            ui.label(splitter.abbreviate_pattern()).on_hover_text(hover);
+                            ui.label(badge).on_hover_text(format!(
+                                "{hover} at the current FWHM. Resolution is estimated by comparing \
+                                    the spacing between neighboring lines to the peak FWHM.",
+                            ));
                         });
                         row.col(|ui| {
                             let mut button = |enabled2, text, hover| {
@@ -299,9 +1050,11 @@ impl Protonolysis {
                                 self.peak.splitters.swap(i, i + 1);
                             }
                             // U+2717 BALLOT X.
-                            if button(self.peak.splitters.len() > 1, "\u{2717}", "Delete") {
-                                self.peak.splitters.remove(i);
-                                self.view_stage.set_value_clamping(*self.view_stage - 1.);
+                            // Deleting the last splitter leaves a singlet.
+                            if button(true, "\u{2717}", "Delete") {
+                                if !self.peak.splitters.remove(i).muted {
+                                    self.view_stage.set_value_clamping(*self.view_stage - 1.);
+                                }
                                 self.update_animation_parameters();
                             }
                         });
@@ -310,20 +1063,84 @@ impl Protonolysis {
                     i += 1;
                 }
             });
+            if mute_toggled {
+                // Keep showing the full multiplet, if it was shown.
+                let was_complete = *self.view_stage >= *self.view_stage.range().end();
+                self.update_animation_parameters();
+                if was_complete {
+                    self.view_stage.set_value_clamping(f64::INFINITY);
+                }
+            }
 
             utils::vertical_space(ui);
 
-            ui.label(format!(
-                "Resulting pattern: {}",
-                self.peak.name().unwrap_or("<complex>".to_owned())
-            ));
+            let pattern = self
+                .peak
+                .name(self.pattern_naming)
+                .unwrap_or("<complex>".to_owned());
+            ui.label(if self.peak.label.trim().is_empty() {
+                format!("Resulting pattern: {pattern}, {}H", self.peak.proton_count)
+            } else {
+                format!(
+                    "Resulting pattern: {}: {pattern}, {}H",
+                    self.peak.label, self.peak.proton_count,
+                )
+            });
 
-            if self.peak.total_peaklet_count() > Self::TOO_COMPLEX_THRESHOLD {
+            ui.horizontal(|ui| {
+                let description = export::coupling_description(&self.peak);
+                ui.label("Coupling:");
+                ui.label(&description);
+                if ui.button("Copy").clicked() {
+                    ui.output_mut(|o| o.copied_text = description);
+                }
+            });
+
+            ui.horizontal(|ui| {
+                let descriptor = export::descriptor(&self.peak, self.pattern_naming);
+                ui.label("Descriptor:");
+                ui.monospace(&descriptor);
+                if ui.button("Copy").clicked() {
+                    ui.output_mut(|o| o.copied_text = descriptor);
+                }
+                let latex = export::descriptor_latex(&self.peak, self.pattern_naming);
+                if ui.button("Copy as LaTeX").on_hover_text(&latex).clicked() {
+                    ui.output_mut(|o| o.copied_text = latex);
+                }
+            });
+
+            self.center_of_mass_readout(ui);
+
+            if let Some(over_budget) = self.over_budget() {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!(
+                        "⚠ Drawing the spectrum took {:.0} ms, over the budget of {:.0} ms. Only \
+                            the positions of the lines are shown.",
+                        over_budget.elapsed_ms, self.render_budget_ms,
+                    ),
+                );
+            } else if self.peak.total_peaklet_count() > Self::MAX_DRAWN_PEAKLET_COUNT {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!(
+                        "⚠ The pattern has more than {} lines, too many to draw its spectrum. \
+                            Only the positions of the lines are shown.",
+                        Self::MAX_DRAWN_PEAKLET_COUNT,
+                    ),
+                );
+            } else if self.peak.total_peaklet_count() > Self::TOO_COMPLEX_THRESHOLD {
                 ui.label(
                     "⚠ The requested splitting pattern is highly complex and may result \
                     in performance degradation!",
                 );
             }
+
+            self.first_order_check(ui);
+
+            ui.collapsing("Line list", |ui| self.line_list(ui));
+            ui.collapsing("Intensity ratios", |ui| self.intensity_ratios(ui));
+            ui.collapsing("Integration regions", |ui| self.integration_region_list(ui));
         });
 
         ui.separator();
@@ -331,10 +1148,14 @@ impl Protonolysis {
         utils::two_column_grid("controls_view", ui, |ui| {
             ui.label("Apply splitting up to level:").on_hover_text(
                 "Draw the peak as if only the first n proton types were present. A fractional \
-                    value indicates partial application of the last splitting constant.",
+                    value indicates partial application of the last splitting constant. Step \
+                    through whole levels with ← and →.",
             );
             ui.horizontal(|ui| {
-                self.view_stage.tick(ui);
+                self.view_stage.tick(ui.ctx());
+                let stage_format = self.stage_format;
+                // Holding shift inverts the snapping mode.
+                let snap = self.snap_view_stage ^ ui.input(|i| i.modifiers.shift);
                 ui.style_mut().spacing.slider_width = 200.;
                 ui.add(
                     Slider::from_get_set(self.view_stage.range(), |value| {
@@ -343,13 +1164,8 @@ impl Protonolysis {
                         }
                         *self.view_stage
                     })
-                    .custom_formatter(|x, _| {
-                        if approx::abs_diff_eq!(x, x.round(), epsilon = 8e-3) {
-                            format!("{x:.0}")
-                        } else {
-                            format!("{x:.2}")
-                        }
-                    }),
+                    .step_by(if snap { 1. } else { 0. })
+                    .custom_formatter(|x, _| stage_format.format(x)),
                 );
                 let animate_text = if self.view_stage.is_animating() {
                     "Stop"
@@ -359,138 +1175,2541 @@ impl Protonolysis {
                 if ui.button(animate_text).clicked() {
                     self.view_stage.toggle_animation();
                 }
+                if ui
+                    .button("Play once")
+                    .on_hover_text(
+                        "Build up the pattern from level 0 in a single sweep, then hold at the \
+                            final level",
+                    )
+                    .clicked()
+                {
+                    self.view_stage.play_once(
+                        Self::ANIMATION_TIME_PER_STAGE * f64::from(self.peak.stage_count())
+                            / self.play_once_speed,
+                    );
+                }
             });
             ui.end_row();
 
-            ui.label("Show:");
-            ui.checkbox(&mut self.show_integral, "Peak integral");
+            ui.label("Observed pattern:").on_hover_text(
+                "The name of the multiplet observed at each level; the current level is \
+                    highlighted",
+            );
+            self.stage_breadcrumb(ui);
             ui.end_row();
 
             ui.label("");
-            ui.checkbox(&mut self.show_splitting_diagram, "Splitting diagram");
+            ui.checkbox(&mut self.snap_view_stage, "Snap to whole levels")
+                .on_hover_text("Hold shift while dragging to temporarily invert");
             ui.end_row();
 
-            ui.label("");
-            ui.checkbox(&mut self.show_peaklets, "Individual contributions")
-                .on_hover_text(
-                    "Draw the individual peaks making up the multiplet to elucidate overlap",
+            ui.label("Level display:").on_hover_text(
+                "Decimal places shown for fractional levels, and how close to a whole number a \
+                    level must be to be shown as one",
+            );
+            ui.horizontal(|ui| {
+                ui.add(
+                    DragValue::new(&mut self.stage_format.decimals)
+                        .clamp_range(0..=4)
+                        .suffix(" decimals"),
+                );
+                ui.add_enabled(
+                    self.stage_format.decimals > 0,
+                    DragValue::new(&mut self.stage_format.snap_epsilon)
+                        .clamp_range(0.0..=0.5)
+                        .speed(1e-3)
+                        .max_decimals(3)
+                        .prefix("snap within ±"),
                 );
+            });
             ui.end_row();
-        });
 
-        self.cached_partial_cascade = self
-            .peak
-            .nth_partial_peak(FractionalStageIndex::new(*self.view_stage))
-            .build_multiplet_cascade();
-    }
+            ui.label("Pattern names:").on_hover_text(
+                "The convention by which multiplets are named and abbreviated, here and in \
+                    descriptors. Combined patterns are only abbreviated by single letters.",
+            );
+            ComboBox::from_id_source("pattern_naming_selector")
+                .selected_text(self.pattern_naming.name())
+                .show_ui(ui, |ui| {
+                    for naming in PatternNaming::ALL {
+                        ui.selectable_value(&mut self.pattern_naming, naming, naming.name());
+                    }
+                });
+            ui.end_row();
 
-    fn peak_viewer(&mut self, ui: &mut Ui) {
-        utils::inner_bottom_panel("plot_interaction", ui, |ui| {
-            ui.horizontal_wrapped(|ui| {
-                ui.label("Controls:");
-                ui.code("drag");
-                ui.label("to pan,");
-                ui.code("scroll");
-                ui.label("to zoom vertically,");
-                ui.code("ctrl+scroll");
-                ui.label("to zoom horizontally.");
-            });
-        });
+            ui.label("Animation easing:")
+                .on_hover_text("How the animation accelerates and decelerates between ends");
+            let mut easing = self.view_stage.easing();
+            ComboBox::from_id_source("easing_selector")
+                .selected_text(easing.name())
+                .show_ui(ui, |ui| {
+                    for option in Easing::ALL {
+                        ui.selectable_value(&mut easing, option, option.name());
+                    }
+                });
+            self.view_stage.set_easing(easing);
+            ui.end_row();
 
-        let Vec2 {
-            x: available_width,
-            y: available_height,
-        } = ui.available_size();
-        let placement_origin = ui.next_widget_position();
-        let plot_height =
-            available_height - ui.text_style_height(&TextStyle::Body) - ui.spacing().item_spacing.y;
+            ui.label("Animation timing:").on_hover_text(
+                "Whether the animation keeps to real time, or advances by the same step every \
+                    frame. The latter is smoother when frames are drawn irregularly, and \
+                    reproducible, but slows down if the app cannot draw 60 frames per second.",
+            );
+            ui.horizontal(|ui| {
+                let mut clock = self.view_stage.clock();
+                for option in AnimationClock::ALL {
+                    ui.selectable_value(&mut clock, option, option.name());
+                }
+                self.view_stage.set_clock(clock);
+            });
+            ui.end_row();
 
-        let waveform = self
-            .cached_partial_cascade
-            .final_waveform::<PeakGeometry>(self.field_strength);
-        let plot_link_id = ui.id().with("link");
+            ui.label("Play-once speed:");
+            ui.add(
+                Slider::new(&mut self.play_once_speed, Self::PLAY_ONCE_SPEED_RANGE)
+                    .logarithmic(true)
+                    .max_decimals(2)
+                    .prefix("× "),
+            );
+            ui.end_row();
 
-        let peak_plot = utils::make_noninteractable_plot("peak_plot")
-            .include_x(-Self::DEFAULT_X)
-            .include_x(Self::DEFAULT_X)
-            .include_y(Self::DEFAULT_Y * -0.05)
-            .include_y(Self::DEFAULT_Y * 1.1)
-            .allow_double_click_reset(true)
-            .x_axis_formatter(|x, _| {
-                // Note that this is visual only!
-                eframe::emath::round_to_decimals(numerics::negate_nonzero(x), 4).to_string()
-            })
-            .link_axis(plot_link_id, true, false)
-            .height(plot_height);
-        peak_plot.show(ui, |plot_ui| {
-            utils::peak_viewer_interactions(plot_ui, true);
+            ui.label("Show:");
+            ui.checkbox(&mut self.show_integral, "Peak integral")
+                .on_hover_text("The integral rises by the proton count, ticked at each proton");
+            ui.end_row();
 
-            let waveform_clone = waveform.clone();
-            plot_ui.line(
-                Line::new(PlotPoints::from_explicit_callback(
-                    move |x| waveform_clone.evaluate(x),
-                    ..,
-                    Self::SAMPLES,
+            ui.label("");
+            ui.add_enabled_ui(self.show_integral, |ui| {
+                ui.horizontal(|ui| {
+                    for mode in IntegralMode::ALL {
+                        ui.selectable_value(&mut self.integral_mode, mode, mode.name());
+                    }
+                })
+                .response
+                .on_hover_text(
+                    "Draw the integral in a separate plot on top of the spectrum, or within the \
+                        plot of the spectrum itself",
+                );
+            });
+            ui.end_row();
+
+            ui.label("");
+            ui.add_enabled_ui(self.show_integral, |ui| {
+                let placement = &mut self.integral_placement;
+                ui.horizontal(|ui| {
+                    ui.label("height");
+                    ui.add(
+                        Slider::new(&mut placement.height, IntegralPlacement::HEIGHT_RANGE)
+                            .fixed_decimals(2),
+                    );
+                    ui.label("from top");
+                    ui.add(
+                        Slider::new(&mut placement.offset, 0.0..=(1. - placement.height))
+                            .fixed_decimals(2),
+                    );
+                })
+                .response
+                .on_hover_text(
+                    "The region occupied by the integral, as fractions of the height of the plot",
+                );
+            });
+            ui.end_row();
+
+            ui.label("");
+            ui.add_enabled(
+                self.show_integral,
+                Checkbox::new(&mut self.annotate_integral, "Explain the integral"),
+            )
+            .on_hover_text(
+                "Mark where the multiplet begins and ends, and label how much the integral rises \
+                    across it",
+            );
+            ui.end_row();
+
+            ui.label("");
+            ui.checkbox(&mut self.show_splitting_diagram, "Splitting diagram");
+            ui.end_row();
+
+            ui.label("");
+            ui.checkbox(&mut self.show_resolved_couplings, "Resolved couplings")
+                .on_hover_text(
+                    "Tabulate which coupling constants can be measured at the current field \
+                        strength and FWHM",
+                );
+            ui.end_row();
+
+            ui.label("");
+            ui.checkbox(&mut self.show_coupling_arcs, "Coupling network")
+                .on_hover_text(
+                    "Summarize the couplings as a chord diagram, with a chord to each group of \
+                        coupled protons",
+                );
+            ui.end_row();
+
+            ui.label("");
+            ui.checkbox(&mut self.show_peaklets, "Individual contributions")
+                .on_hover_text(
+                    "Draw the individual peaks making up the multiplet to elucidate overlap",
+                );
+            ui.end_row();
+
+            ui.label("");
+            ui.add_enabled(
+                self.show_peaklets,
+                Checkbox::new(
+                    &mut self.highlight_overlaps,
+                    "Highlight overlapping contributions",
+                ),
+            )
+            .on_hover_text(
+                "Color the contributions which are not resolved from their neighbors (i.e., are \
+                    closer than about one FWHM)",
+            );
+            ui.end_row();
+
+            ui.label("");
+            ui.checkbox(&mut self.show_sticks, "Stick positions")
+                .on_hover_text(
+                "Overlay a line at the position of each underlying peak, scaled by its integration",
+            );
+            ui.end_row();
+
+            ui.label("");
+            ui.checkbox(&mut self.show_merges, "Merged lines")
+                .on_hover_text(
+                "Mark each line by whether it has merged with a neighbor (i.e., is closer than \
+                    about one FWHM), as is always done while sweeping the FWHM",
+            );
+            ui.end_row();
+
+            ui.label("");
+            ui.checkbox(&mut self.show_integration_bars, "Line intensities")
+                .on_hover_text("Chart the relative integration of each line beside the spectrum");
+            ui.end_row();
+
+            ui.label("");
+            ui.add_enabled(
+                self.show_integration_bars,
+                Checkbox::new(&mut self.merge_coincident_lines, "Merge coincident lines"),
+            )
+            .on_hover_text(
+                "Chart lines at the same shift (e.g., from equal coupling constants) as one",
+            );
+            ui.end_row();
+
+            ui.label("Fill:")
+                .on_hover_text("Shade the area beneath the spectrum");
+            self.spectrum_fill.controls(ui);
+            ui.end_row();
+
+            ui.label("Overlay fill:").on_hover_text(
+                "Shade the area beneath the curves compared against the spectrum, i.e., those of \
+                    other FWHMs and the processed spectrum. Light fills keep overlapping curves \
+                    legible.",
+            );
+            self.overlay_fill.controls(ui);
+            ui.end_row();
+
+            ui.label("Density strip:").on_hover_text(
+                "Draw the intensity of the multiplet as a shaded strip beneath the spectrum, in \
+                    place of the individual contributions. By default, this is only done for \
+                    highly complex patterns.",
+            );
+            ui.horizontal(|ui| {
+                for mode in DensityStrip::ALL {
+                    ui.selectable_value(&mut self.density_strip, mode, mode.name());
+                }
+            });
+            ui.end_row();
+
+            ui.label("Resolution enhancement:").on_hover_text(
+                "Narrow the displayed lines below their true width, as can be done by processing \
+                    (apodization) at the expense of signal-to-noise. This is a processing \
+                    artifact, not a physical property of the peak; the FWHM above is unchanged.",
+            );
+            ui.add(
+                Slider::new(
+                    &mut self.resolution_enhancement,
+                    Self::RESOLUTION_ENHANCEMENT_RANGE,
+                )
+                .fixed_decimals(2)
+                .prefix("× ")
+                .suffix(" FWHM"),
+            );
+            ui.end_row();
+
+            if self.is_resolution_enhanced() {
+                ui.label("");
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!(
+                        "Displaying an apparent FWHM of {:.2} Hz",
+                        self.display_fwhm(),
+                    ),
+                );
+                ui.end_row();
+            }
+
+            ui.label("Digital resolution:").on_hover_text(
+                "Draw the spectrum as if it were acquired with too few points per Hz, by averaging \
+                    it over a coarse frequency grid. Narrow lines and small splittings are \
+                    flattened or lost, as they would be in a poorly digitized spectrum.",
+            );
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.limit_digital_resolution, "");
+                ui.add_enabled(
+                    self.limit_digital_resolution,
+                    DragValue::new(&mut self.points_per_hz)
+                        .clamp_range(Self::POINTS_PER_HZ_RANGE)
+                        .speed(0.01)
+                        .fixed_decimals(2)
+                        .suffix(" points/Hz"),
+                );
+            });
+            ui.end_row();
+
+            ui.label("Compare FWHMs:").on_hover_text(
+                "Overlay the multiplet as it would appear at two other line widths, all else \
+                    being equal",
+            );
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.compare_fwhms, "");
+                for fwhm in &mut self.comparison_fwhms {
+                    ui.add_enabled(
+                        self.compare_fwhms,
+                        DragValue::new(fwhm)
+                            .clamp_range(Self::MIN_FWHM..=Self::MAX_BROAD_FWHM)
+                            .speed(0.01)
+                            .fixed_decimals(2)
+                            .suffix(" Hz"),
+                    );
+                }
+            });
+            ui.end_row();
+
+            ui.label("Rendering budget:").on_hover_text(
+                "If drawing the spectrum takes longer than this for several frames in a row, only \
+                    the positions of its lines are drawn until the pattern changes, keeping the \
+                    app responsive",
+            );
+            ui.add(
+                DragValue::new(&mut self.render_budget_ms)
+                    .clamp_range(Self::RENDER_BUDGET_RANGE_MS)
+                    .speed(0.5)
+                    .fixed_decimals(0)
+                    .suffix(" ms"),
+            );
+            ui.end_row();
+
+            ui.label("Minimum separation:").on_hover_text(
+                "Resolved lines closer than this on screen are flagged (≈) as indistinguishable \
+                    at the current zoom",
+            );
+            ui.add(
+                DragValue::new(&mut self.min_display_separation)
+                    .clamp_range(Self::MIN_DISPLAY_SEPARATION_RANGE)
+                    .speed(0.1)
+                    .fixed_decimals(0)
+                    .suffix(" pt"),
+            );
+            ui.end_row();
+
+            ui.label("");
+            ui.checkbox(&mut self.show_plot_background, "Plot background");
+            ui.end_row();
+
+            ui.label("Pan axes:")
+                .on_hover_text("Which axes of the spectrum respond to dragging");
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.interaction_axes.pan[0], "δ");
+                ui.checkbox(&mut self.interaction_axes.pan[1], "Intensity");
+            });
+            ui.end_row();
+
+            ui.label("Zoom axes:")
+                .on_hover_text("Which axes of the spectrum respond to scrolling and pinching");
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.interaction_axes.zoom[0], "δ");
+                ui.checkbox(&mut self.interaction_axes.zoom[1], "Intensity");
+            });
+            ui.end_row();
+
+            ui.label("Fixed δ scale:").on_hover_text(
+                "Pin the horizontal scale of the spectrum such that figures captured at the same \
+                    setting are directly comparable. The window is centered on the current view.",
+            );
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.fixed_x_scale, "");
+                ui.add_enabled(
+                    self.fixed_x_scale,
+                    DragValue::new(&mut self.x_scale)
+                        .clamp_range(Self::X_SCALE_RANGE)
+                        .speed(1e-6)
+                        .fixed_decimals(6)
+                        .suffix(" ppm/px"),
+                );
+            });
+            ui.end_row();
+
+            ui.label("Pinned intensity scale:").on_hover_text(
+                "Keep the vertical scale of the spectrum across changes to the configuration, such \
+                    that splitting visibly spreads out the same total intensity. The current view \
+                    becomes the reference.",
+            );
+            ui.horizontal(|ui| {
+                let mut pinned = self.pinned_y_window.is_some();
+                if ui.checkbox(&mut pinned, "").changed() {
+                    self.pinned_y_window = pinned.then_some(self.linked_y_axis);
+                }
+                if let Some((_, y_max)) = self.pinned_y_window {
+                    ui.label(format!("up to {y_max:.0}"));
+                    if ui
+                        .button("Re-pin")
+                        .on_hover_text("Pin the current view")
+                        .clicked()
+                    {
+                        self.pinned_y_window = Some(self.linked_y_axis);
+                    }
+                }
+            });
+            ui.end_row();
+
+            ui.label("Presentation mode:").on_hover_text(format!(
+                "Show only the spectrum, enlarged. Toggle with {}; exit with {}.",
+                shortcuts::format_action(ui.ctx(), Action::TogglePresentation),
+                shortcuts::format_action(ui.ctx(), Action::Dismiss),
+            ));
+            ui.horizontal(|ui| {
+                if ui.button("Enter").clicked() {
+                    self.presentation_mode = true;
+                }
+                ui.checkbox(
+                    &mut self.presentation_show_diagram,
+                    "With splitting diagram",
+                );
+            });
+            ui.end_row();
+
+            ui.label("Session:").on_hover_text(
+                "Save the configuration and display settings to a file. To restore them, drop \
+                    the file onto this window.",
+            );
+            if ui.button("Save").clicked() {
+                let session = self.session().to_ron().into_bytes();
+                self.save_export(ui.ctx(), Self::SESSION_FILE_NAME, "session", Ok(session));
+            }
+            ui.end_row();
+
+            #[cfg(target_arch = "wasm32")]
+            {
+                ui.label("Link:").on_hover_text(
+                    "Copy a link which opens this configuration, zoomed and panned exactly as it \
+                        is now",
+                );
+                if ui.button("Copy").clicked() {
+                    let link = format!("{}#{}", self.link_base, self.view_link().to_fragment());
+                    ui.output_mut(|o| o.copied_text = link);
+                    self.toasts
+                        .push(ui.ctx(), "Copied link to the current view");
+                }
+                ui.end_row();
+            }
+        });
+
+        self.update_partial_cascade();
+
+        ui.separator();
+        ui.collapsing("Advanced processing", |ui| self.advanced_processing(ui));
+        ui.collapsing("Export images", |ui| self.export_animation(ui));
+        ui.collapsing("Figure sheet", |ui| self.figure_sheet_controls(ui));
+        ui.collapsing("Background", |ui| self.background_controls(ui));
+        ui.collapsing("Inspect components", |ui| self.component_inspector(ui));
+    }
+
+    /// The most often adjusted parameters of the instrument and the sample, in a single row.
+    /// The rest are in [`Self::instrument_controls`].
+    fn instrument_bar(&mut self, ui: &mut Ui, enabled: bool) {
+        let sweeping = self.fwhm_before_sweep.is_some();
+        let max_fwhm = if self.broad_lines {
+            Self::MAX_BROAD_FWHM
+        } else {
+            Self::MAX_FWHM
+        };
+        ui.horizontal_wrapped(|ui| {
+            ui.add_enabled(
+                enabled,
+                DragValue::new(&mut self.field_strength)
+                    .clamp_range(40.0..=1200.0)
+                    .speed(1.)
+                    .fixed_decimals(0)
+                    .suffix(" MHz"),
+            )
+            .on_hover_text("Instrument frequency");
+            ui.separator();
+            ui.label("FWHM:");
+            ui.add_enabled(
+                enabled && !sweeping,
+                DragValue::new(&mut self.peak.fwhm)
+                    .clamp_range(Self::MIN_FWHM..=max_fwhm)
+                    .speed(0.05)
+                    .fixed_decimals(1)
+                    .suffix(" Hz"),
+            );
+            ui.separator();
+            self.lineshape_selector(ui, enabled, "bar_lineshape_selector");
+        });
+    }
+
+    fn lineshape_selector(&mut self, ui: &mut Ui, enabled: bool, id_source: &str) {
+        ui.add_enabled_ui(enabled, |ui| {
+            ComboBox::from_id_source(id_source)
+                .selected_text(self.peak.lineshape.name())
+                .show_ui(ui, |ui| {
+                    for lineshape in Lineshape::ALL {
+                        let hint = match lineshape {
+                            Lineshape::Lorentzian => {
+                                "The natural shape of the lines of a freely decaying signal, which \
+                                    real spectra closely approach"
+                            }
+                            Lineshape::Gaussian => {
+                                "Lines broadened by the inhomogeneity of the field, or by heavy \
+                                    apodization"
+                            }
+                            Lineshape::PseudoVoigt => {
+                                "A blend of the two, as for real lines broadened both by \
+                                    relaxation and by the inhomogeneity of the field"
+                            }
+                            Lineshape::ExponentiallyModifiedGaussian => {
+                                "Simulate a poorly shimmed magnet, whose lines have a tail to one \
+                                    side. This is an artifact of the instrument, not a property \
+                                    of the sample."
+                            }
+                        };
+                        ui.selectable_value(&mut self.peak.lineshape, lineshape, lineshape.name())
+                            .on_hover_text(hint);
+                    }
+                });
+        });
+    }
+
+    /// The parameters of the instrument (its frequency and shimming) and those of the sample (the
+    /// width of its lines).
+    fn instrument_controls(&mut self, ui: &mut Ui, enabled: bool) {
+        utils::two_column_grid("controls_instrument", ui, |ui| {
+            ui.label("Instrument frequency:");
+            ui.add_enabled(
+                enabled,
+                Slider::new(&mut self.field_strength, 40.0..=1200.0)
+                    .fixed_decimals(0)
+                    .step_by(20.)
+                    .suffix(" MHz"),
+            );
+            ui.end_row();
+
+            ui.label("Standard frequency:")
+                .on_hover_text("Set the frequency of a common instrument exactly");
+            ui.add_enabled_ui(enabled, |ui| {
+                let selected = Self::FIELD_STRENGTH_PRESETS
+                    .contains(&self.field_strength)
+                    .then(|| format!("{:.0} MHz", self.field_strength));
+                ComboBox::from_id_source("field_strength_selector")
+                    .selected_text(selected.as_deref().unwrap_or("Custom"))
+                    .show_ui(ui, |ui| {
+                        for frequency in Self::FIELD_STRENGTH_PRESETS {
+                            ui.selectable_value(
+                                &mut self.field_strength,
+                                frequency,
+                                format!("{frequency:.0} MHz"),
+                            );
+                        }
+                    });
+            });
+            ui.end_row();
+
+            ui.label("Field strength:")
+                .on_hover_text("Strength of magnetic field of instrument");
+            ui.add_enabled(
+                false,
+                DragValue::new(&mut peak::mhz_to_tesla(self.field_strength))
+                    .max_decimals(1)
+                    .suffix(" T"),
+            )
+            .on_disabled_hover_text(format!(
+                "The magnetic field B₀ of the instrument's magnet. Instruments are named by the \
+                    frequency at which protons resonate in this field, which is proportional to \
+                    its strength: ν = γB₀, where γ = {:.3} MHz/T for ¹H.",
+                peak::γ_PROTON,
+            ));
+            ui.end_row();
+
+            ui.label("Peak FWHM:")
+                .on_hover_text("Full width at half maximum (i.e., broadness) of peaks");
+            ui.horizontal(|ui| {
+                let sweeping = self.fwhm_before_sweep.is_some();
+                let max_fwhm = if self.broad_lines {
+                    Self::MAX_BROAD_FWHM
+                } else {
+                    Self::MAX_FWHM
+                };
+                ui.add_enabled(
+                    enabled && !sweeping,
+                    Slider::new(&mut self.peak.fwhm, Self::MIN_FWHM..=max_fwhm)
+                        .logarithmic(true)
+                        .fixed_decimals(1)
+                        .smart_aim(false)
+                        .suffix(" Hz"),
+                );
+                if ui
+                    .add_enabled(enabled && !sweeping, Button::new("Default"))
+                    .on_hover_text(format!("Reset to {} Hz", self.default_fwhm))
+                    .clicked()
+                {
+                    self.peak.fwhm = self.default_fwhm;
+                    self.broad_lines |= self.default_fwhm > Self::MAX_FWHM;
+                }
+                if ui
+                    .add_enabled(
+                        enabled,
+                        Button::new(if sweeping { "Stop" } else { "Sweep" }),
+                    )
+                    .on_hover_text(
+                        "Broaden and narrow the lines back and forth, marking those which have \
+                            merged with a neighbor",
+                    )
+                    .clicked()
+                {
+                    self.toggle_fwhm_sweep(max_fwhm);
+                }
+            });
+            ui.end_row();
+
+            ui.label("Lineshape:")
+                .on_hover_text("Functional form of each line of the multiplet");
+            ui.horizontal(|ui| {
+                self.lineshape_selector(ui, enabled, "lineshape_selector");
+                ui.add_enabled(
+                    enabled && self.peak.lineshape == Lineshape::PseudoVoigt,
+                    Slider::new(&mut self.peak.mixing, 0.0..=1.0)
+                        .fixed_decimals(2)
+                        .text("η"),
+                )
+                .on_hover_text(
+                    "Fraction of the area of each line which is Lorentzian: one for a pure \
+                        Lorentzian, zero for a pure Gaussian",
+                )
+                .on_disabled_hover_text("Only pseudo-Voigt lines are mixed");
+            });
+            ui.end_row();
+
+            ui.label("Shimming:");
+            ui.horizontal(|ui| {
+                let skewed = self.peak.lineshape == Lineshape::ExponentiallyModifiedGaussian;
+                ui.add_enabled(
+                    enabled && skewed,
+                    Slider::new(&mut self.peak.skew, 0.0..=Self::MAX_SKEW)
+                        .fixed_decimals(2)
+                        .text("tail (FWHMs)"),
+                )
+                .on_hover_text(
+                    "How far the tail extends, in FWHMs of the underlying Gaussian; zero \
+                        recovers the Gaussian",
+                )
+                .on_disabled_hover_text("Only skewed lines have a tail");
+            });
+            ui.end_row();
+
+            ui.label("");
+            ui.add_enabled(enabled, Checkbox::new(&mut self.broad_lines, "Broad lines"))
+                .on_hover_text(format!(
+                "Extend the range of widths to {} Hz, as for exchangeable protons or at low field",
+                Self::MAX_BROAD_FWHM,
+            ));
+            ui.end_row();
+
+            ui.label("Default FWHM:")
+                .on_hover_text("The width used for new sessions, and restored by \"Default\"");
+            ui.add(
+                DragValue::new(&mut self.default_fwhm)
+                    .clamp_range(Self::MIN_FWHM..=Self::MAX_BROAD_FWHM)
+                    .speed(0.05)
+                    .fixed_decimals(1)
+                    .suffix(" Hz"),
+            );
+            ui.end_row();
+        });
+    }
+
+    /// Controls for rendering the build-up of the multiplet, as by "Play once", to a GIF, and the
+    /// complete multiplet to a PNG.
+    fn export_animation(&mut self, ui: &mut Ui) {
+        let options = &mut self.sweep_options;
+        utils::two_column_grid("export_animation", ui, |ui| {
+            ui.label("Size:");
+            ui.horizontal(|ui| {
+                ui.add(DragValue::new(&mut options.width).clamp_range(64..=1920));
+                ui.label("×");
+                ui.add(
+                    DragValue::new(&mut options.height)
+                        .clamp_range(64..=1080)
+                        .suffix(" px"),
+                );
+            });
+            ui.end_row();
+
+            ui.label("Frame rate:");
+            ui.add(
+                DragValue::new(&mut options.frame_rate)
+                    .clamp_range(1..=60)
+                    .suffix(" fps"),
+            );
+            ui.end_row();
+
+            ui.label("Duration:");
+            ui.add(
+                DragValue::new(&mut options.duration)
+                    .clamp_range(0.5..=30.0)
+                    .speed(0.1)
+                    .suffix(" s"),
+            );
+            ui.end_row();
+
+            ui.label("Quality:").on_hover_text(
+                "Print quality draws smooth lines, whose thickness is in proportion to the \
+                    height of the image",
+            );
+            ui.horizontal(|ui| {
+                for quality in RenderQuality::ALL {
+                    ui.selectable_value(&mut options.quality, quality, quality.name());
+                }
+            });
+            ui.end_row();
+
+            ui.label("Still image scale:").on_hover_text(
+                "Size of the still image relative to the animation, e.g., 4× for a figure at \
+                    print resolution",
+            );
+            ui.add(
+                DragValue::new(&mut self.still_scale)
+                    .clamp_range(1..=8)
+                    .suffix("×"),
+            );
+            ui.end_row();
+        });
+        ui.horizontal(|ui| {
+            self.save_gif_button(ui);
+            if ui
+                .button("Save PNG")
+                .on_hover_text("Render the complete multiplet as a still image")
+                .clicked()
+            {
+                let pixels = render::final_frame_pixel_count(self.sweep_options, self.still_scale);
+                if pixels > render::MAX_STILL_PIXELS {
+                    #[allow(clippy::cast_precision_loss)]
+                    let message = format!(
+                        "The image is too large to save: {:.0} megapixels, of at most {:.0}. \
+                            Reduce its size or scale.",
+                        pixels as f64 / 1e6,
+                        render::MAX_STILL_PIXELS as f64 / 1e6,
+                    );
+                    self.toasts.push(ui.ctx(), message);
+                    return;
+                }
+                let png = render::final_frame_png(
+                    &self.peak,
+                    self.field_strength,
+                    self.sweep_options,
+                    self.still_scale,
+                )
+                .map_err(|err| err.to_string());
+                self.save_export(ui.ctx(), Self::STILL_FILE_NAME, "image", png);
+            }
+        });
+    }
+
+    /// Controls for rendering a chosen set of presets side by side, as a reference chart.
+    fn figure_sheet_controls(&mut self, ui: &mut Ui) {
+        ui.label("Include:");
+        ui.indent("sheet_presets", |ui| {
+            for &preset in PEAK_PRESETS.keys().sorted() {
+                let mut included = self.sheet_presets.contains(&preset);
+                if ui.checkbox(&mut included, preset).changed() {
+                    if included {
+                        self.sheet_presets.push(preset);
+                        self.sheet_presets.sort_unstable();
+                    } else {
+                        self.sheet_presets.retain(|&other| other != preset);
+                    }
+                }
+            }
+            ui.checkbox(&mut self.sheet_includes_current, "Current configuration");
+        });
+        let options = &mut self.sheet_options;
+        utils::two_column_grid("figure_sheet", ui, |ui| {
+            ui.label("Columns:");
+            ui.add(DragValue::new(&mut options.columns).clamp_range(1..=8));
+            ui.end_row();
+
+            ui.label("Cell size:");
+            ui.horizontal(|ui| {
+                ui.add(DragValue::new(&mut options.cell_width).clamp_range(100..=1600));
+                ui.label("×");
+                ui.add(
+                    DragValue::new(&mut options.cell_height)
+                        .clamp_range(80..=1200)
+                        .suffix(" px"),
+                );
+            });
+            ui.end_row();
+
+            ui.label("Quality:");
+            ui.horizontal(|ui| {
+                for quality in RenderQuality::ALL {
+                    ui.selectable_value(&mut options.quality, quality, quality.name());
+                }
+            });
+            ui.end_row();
+        });
+        let entries = self.sheet_entries();
+        if ui
+            .add_enabled(!entries.is_empty(), Button::new("Save sheet"))
+            .on_hover_text(format!(
+                "Render each included pattern at {:.0} MHz, with the current line width and \
+                    shape, to a single labeled image",
+                Self::SHEET_FIELD_STRENGTH,
+            ))
+            .clicked()
+        {
+            let png =
+                render::figure_sheet_png(&entries, Self::SHEET_FIELD_STRENGTH, self.sheet_options)
+                    .map_err(|err| err.to_string());
+            self.save_export(ui.ctx(), Self::SHEET_FILE_NAME, "sheet", png);
+        }
+    }
+
+    /// Save the output of an export, `contents`, suggesting `file_name`, and report the outcome.
+    /// `what` names the export in the report.
+    fn save_export(
+        &mut self,
+        ctx: &Context,
+        file_name: &str,
+        what: &str,
+        contents: Result<Vec<u8>, String>,
+    ) {
+        let message = match contents.and_then(|contents| utils::save_file(file_name, &contents)) {
+            Ok(Some(path)) => format!("Saved {path}"),
+            // The user cancelled.
+            Ok(None) => return,
+            Err(err) => format!("Failed to save {what}: {err}"),
+        };
+        self.toasts.push(ctx, message);
+    }
+
+    /// The labeled peaks drawn by [`Self::figure_sheet_controls`]. Presets are drawn with the
+    /// width and shape of the current configuration.
+    fn sheet_entries(&self) -> Vec<(String, Peak)> {
+        let label = |name: &str, peak: &Peak| match peak.name(self.pattern_naming) {
+            Some(pattern) => format!("{name}: {pattern}"),
+            None => name.to_owned(),
+        };
+        let presets = self.sheet_presets.iter().map(|&name| {
+            let preset = &PEAK_PRESETS[name];
+            let peak = Peak {
+                label: String::new(),
+                proton_count: preset.proton_count,
+                splitters: preset.splitters.clone(),
+                ..self.resting_peak()
+            };
+            (label(name, &peak), peak)
+        });
+        let current = self.sheet_includes_current.then(|| {
+            let peak = self.resting_peak();
+            (label(&peak.display_label(self.pattern_naming), &peak), peak)
+        });
+        presets.chain(current).collect()
+    }
+
+    fn save_gif_button(&mut self, ui: &mut Ui) {
+        if ui
+            .button("Save GIF")
+            .on_hover_text("Render a single sweep from level 0 to the final level")
+            .clicked()
+        {
+            let pixels = self.sweep_options.pixel_count();
+            if pixels > SweepOptions::MAX_PIXELS {
+                #[allow(clippy::cast_precision_loss)]
+                let message = format!(
+                    "The animation is too large to save: {:.0} megapixels across its frames, of \
+                        at most {:.0}. Reduce its size, frame rate, or duration.",
+                    pixels as f64 / 1e6,
+                    SweepOptions::MAX_PIXELS as f64 / 1e6,
+                );
+                self.toasts.push(ui.ctx(), message);
+                return;
+            }
+            let gif = render::sweep_gif(&self.peak, self.field_strength, self.sweep_options)
+                .map_err(|err| err.to_string());
+            self.save_export(ui.ctx(), Self::SWEEP_FILE_NAME, "animation", gif);
+        }
+    }
+
+    /// Warn when the first-order (Pascal's triangle) treatment is questionable, given the
+    /// separation in shift to the coupling partners.
+    // TODO: once a second-order engine exists, offer to overlay the exact spectrum of the same
+    // spin system here, so that the breakdown (roofing, extra lines) can be seen directly.
+    fn first_order_check(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Shift separation to coupled protons:")
+                .on_hover_text(
+                    "Approximate difference in chemical shift between the observed protons and \
+                    those they couple to. This is not otherwise simulated.",
+                );
+            ui.add(
+                DragValue::new(&mut self.partner_separation)
+                    .clamp_range(Self::PARTNER_SEPARATION_RANGE)
+                    .speed(0.01)
+                    .fixed_decimals(2)
+                    .suffix(" ppm"),
+            );
+            ui.hyperlink_to("About first-order patterns", Self::FIRST_ORDER_DOCS_URL)
+                .on_hover_text(
+                    "The theory by which the spectrum is computed, as labeled on it, and when it \
+                        holds",
+                );
+        });
+        let separation_hz = self.partner_separation * self.field_strength;
+        let Some(ratio) = self.peak.weak_coupling_ratio(separation_hz) else {
+            return;
+        };
+        if ratio < Self::FIRST_ORDER_THRESHOLD {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                format!(
+                    "⚠ Δν/J = {ratio:.1}: coupling is strong, so the first-order pattern shown \
+                        is only approximate. Real spectra would show roofing (distorted \
+                        intensities leaning towards the partner) and possibly extra lines.",
+                ),
+            );
+        }
+        // The multiplet would overlap that of its partners.
+        // TODO: once several peaks can be simulated, report the lowest field at which neighboring
+        // multiplets stop overlapping: their separation in Hz grows with the field, while their
+        // widths in Hz do not.
+        let (min, max) = self.cached_partial_cascade.final_span().into_inner();
+        if 0.5 * (max - min) > separation_hz {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                "⚠ The multiplet is wider than its separation from the coupled protons.",
+            );
+        }
+    }
+
+    /// Controls for simulating the acquisition and processing of the displayed multiplet, along
+    /// with a plot of its FID.
+    fn advanced_processing(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.show_processed_spectrum, "Show processed spectrum")
+            .on_hover_text(
+                "Synthesize the free-induction decay (FID) implied by the line list, apply a \
+                    window function, and Fourier-transform it back into a spectrum",
+            );
+        ui.add_enabled_ui(self.show_processed_spectrum, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Window function:");
+                ComboBox::from_id_source("apodization_selector")
+                    .selected_text(self.apodization.name())
+                    .show_ui(ui, |ui| {
+                        for option in Apodization::ALL {
+                            // N.b. the variants carry parameters, so compare by name only.
+                            let selected = self.apodization.name() == option.name();
+                            if ui.selectable_label(selected, option.name()).clicked() && !selected {
+                                self.apodization = option;
+                            }
+                        }
+                    });
+                if let Some(line_broadening) = self.apodization.line_broadening_mut() {
+                    ui.add(
+                        DragValue::new(line_broadening)
+                            .clamp_range(0.0..=10.0)
+                            .speed(0.05)
+                            .fixed_decimals(2)
+                            .suffix(" Hz"),
+                    )
+                    .on_hover_text("Line broadening introduced by the window function");
+                }
+            });
+
+            let fid = self.processed_fid();
+            let envelope = self.apodization;
+            let fid_plot = utils::make_noninteractable_plot("fid_plot")
+                .height(100.)
+                .show_axes([true, false])
+                .include_y(-1.)
+                .include_y(1.)
+                .legend(Legend::default().position(Corner::RightTop));
+            fid_plot.show(ui, |plot_ui| {
+                let normalization = fid.samples.first().map_or(1., |s| s.norm()).max(1e-9);
+                let times = (0..fid.samples.len()).map(|i| i as f64 * fid.dwell_time);
+                plot_ui.line(
+                    Line::new(
+                        times
+                            .clone()
+                            .zip(&fid.samples)
+                            .map(|(t, s)| [t, s.re / normalization])
+                            .collect::<PlotPoints>(),
+                    )
+                    .color(Color32::LIGHT_BLUE)
+                    .name("FID (real)"),
+                );
+                plot_ui.line(
+                    Line::new(
+                        times
+                            .map(|t| [t, envelope.weight(t)])
+                            .collect::<PlotPoints>(),
+                    )
+                    .color(Self::OVERLAPPING_PEAKLET_COLOR)
+                    .style(LineStyle::dashed_loose())
+                    .name("Window"),
+                );
+            });
+            ui.vertical_centered(|ui| ui.label("t (s)"));
+        });
+    }
+
+    fn background_controls(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.background.enabled, "Add a broad background")
+            .on_hover_text(
+                "Sum a single broad line, such as from a polymer or an exchanging proton, into the \
+                    spectrum. It is included by the integrals, but is not part of the multiplet.",
+            );
+        ui.add_enabled_ui(self.background.enabled, |ui| {
+            utils::two_column_grid("background_controls", ui, |ui: &mut Ui| {
+                let background = &mut self.background;
+                ui.label("Lineshape:");
+                ui.horizontal(|ui| {
+                    for lineshape in Lineshape::ALL {
+                        ui.selectable_value(&mut background.lineshape, lineshape, lineshape.name());
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Offset:")
+                    .on_hover_text("Position relative to the center of the multiplet");
+                ui.add(
+                    DragValue::new(&mut background.offset)
+                        .clamp_range(-500.0..=500.0)
+                        .speed(0.5)
+                        .fixed_decimals(1)
+                        .suffix(" Hz"),
+                );
+                ui.end_row();
+
+                ui.label("FWHM:");
+                ui.add(
+                    DragValue::new(&mut background.fwhm)
+                        .clamp_range(Self::MAX_FWHM..=500.0)
+                        .speed(0.5)
+                        .fixed_decimals(1)
+                        .suffix(" Hz"),
+                );
+                ui.end_row();
+
+                ui.label("Intensity:")
+                    .on_hover_text("Area relative to that of the multiplet");
+                ui.add(
+                    DragValue::new(&mut background.intensity)
+                        .clamp_range(0.0..=10.0)
+                        .speed(0.01)
+                        .fixed_decimals(2)
+                        .prefix("× "),
+                );
+                ui.end_row();
+            });
+        });
+    }
+
+    /// Tabulate the parameters of each distribution summed into the displayed waveform, as they
+    /// are stored.
+    fn component_inspector(&self, ui: &mut Ui) {
+        let waveform = self.displayed_waveform();
+        let field_strength = self.field_strength;
+        let row_height = ui.text_style_height(&TextStyle::Body);
+        ui.label(format!(
+            "{} components; widths are given in Hz.",
+            waveform.components().count()
+        ));
+        TableBuilder::new(ui)
+            .striped(true)
+            .max_scroll_height(200.)
+            .cell_layout(Layout::right_to_left(Align::Center))
+            .columns(Column::auto_with_initial_suggestion(60.), 6)
+            .header(row_height, |mut header| {
+                for text in [
+                    "",
+                    "Lineshape",
+                    "μ (ppm)",
+                    "μ (Hz)",
+                    "Width",
+                    "Normalization",
+                ] {
+                    header.col(|ui| {
+                        ui.label(RichText::new(text).underline());
+                    });
+                }
+            })
+            .body(|body| {
+                let components = waveform.components().collect_vec();
+                body.rows(row_height, components.len(), |i, mut row| {
+                    let component = components[i];
+                    let width = match component {
+                        AnyDistribution::Lorentzian(d) => {
+                            format!("γ = {:.4}", d.γ * field_strength)
+                        }
+                        AnyDistribution::Gaussian(d) => format!("σ = {:.4}", d.σ * field_strength),
+                        AnyDistribution::PseudoVoigt(d) => {
+                            format!("FWHM = {:.4}, η = {:.2}", d.fwhm * field_strength, d.η)
+                        }
+                        AnyDistribution::ExponentiallyModifiedGaussian(d) => format!(
+                            "σ = {:.4}, τ = {:.4}",
+                            d.σ * field_strength,
+                            d.τ * field_strength
+                        ),
+                    };
+                    row.col(|ui| {
+                        ui.label((i + 1).to_string());
+                    });
+                    row.col(|ui| {
+                        ui.label(component.lineshape().name());
+                    });
+                    row.col(|ui| {
+                        ui.monospace(format!("{:.6}", component.μ()));
+                    });
+                    row.col(|ui| {
+                        ui.monospace(format!("{:.3}", component.μ() * field_strength));
+                    });
+                    row.col(|ui| {
+                        ui.monospace(width);
+                    });
+                    row.col(|ui| {
+                        ui.monospace(format!("{:.6}", component.normalization()));
+                    });
+                });
+            });
+    }
+
+    /// The center of mass of the displayed multiplet, which should lie at its center, as a check
+    /// of the symmetry of the splitting.
+    fn center_of_mass_readout(&self, ui: &mut Ui) {
+        let Some(center) = self
+            .cached_partial_cascade
+            .final_lineshape_waveform(self.field_strength)
+            .center_of_mass()
+        else {
+            return;
+        };
+        let center = center * self.field_strength;
+        ui.horizontal(|ui| {
+            ui.label("Center of mass:").on_hover_text(
+                "The intensity-weighted mean position of the displayed lines, relative to the \
+                    center of the peak. First-order multiplets are symmetric, so this should be \
+                    zero.",
+            );
+            if !self.peak.lineshape.is_symmetric() {
+                ui.monospace(format!("{center:+.4} Hz"))
+                    .on_hover_text("Skewed lines displace the center of mass by design");
+            } else if center.abs() < Self::CENTER_OF_MASS_TOLERANCE {
+                ui.monospace("0 Hz ✓");
+            } else {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!("{center:+.6} Hz (asymmetric!)"),
+                );
+            }
+        });
+    }
+
+    /// The FID implied by the displayed line list, weighted by the chosen window function.
+    fn processed_fid(&self) -> Fid {
+        let lines = self
+            .cached_partial_cascade
+            .final_peaklets()
+            .iter()
+            .map(|peaklet| (peaklet.δ, peaklet.integration))
+            .collect_vec();
+        let span = lines.iter().map(|(δ, _)| δ.abs()).fold(0., f64::max);
+        let spectral_width = (2. * span + 40. * self.peak.fwhm).max(Self::MIN_SPECTRAL_WIDTH);
+        Fid::synthesize(&lines, self.peak.fwhm, spectral_width).apodized(self.apodization)
+    }
+
+    /// A table of the individual lines making up the displayed multiplet.
+    fn line_list(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            if ui
+                .button("Copy as CSV")
+                .on_hover_text("Copy the line list, with shifts in both Hz and ppm")
+                .clicked()
+            {
+                let csv = export::line_list_csv(
+                    &self.cached_partial_cascade,
+                    &self.peak.display_label(self.pattern_naming),
+                    &export::coupling_description(&self.peak),
+                    self.field_strength,
+                    self.export_normalization,
+                );
+                ui.output_mut(|o| o.copied_text = csv);
+            }
+            if ui
+                .button("Copy line list")
+                .on_hover_text(
+                    "Copy the lines of the complete multiplet as plain text, one \"shift (Hz) \
+                        integration\" pair per line, for use in other programs",
+                )
+                .clicked()
+            {
+                let text = export::canonical_line_list(
+                    &self.peak.build_multiplet_cascade(),
+                    self.field_strength,
+                );
+                ui.output_mut(|o| o.copied_text = text);
+            }
+            if ui
+                .button("Copy spectrum as CSV")
+                .on_hover_text("Copy the displayed spectrum, sampled across the multiplet")
+                .clicked()
+            {
+                let waveform = self
+                    .cached_partial_cascade
+                    .final_lineshape_waveform(self.field_strength);
+                let points = waveform
+                    .adaptive_sample_grid(
+                        waveform.extent_by_fwhm(Self::INTEGRAL_WIDTH),
+                        Self::SAMPLES,
+                    )
+                    .into_iter()
+                    .map(|x| [x, waveform.evaluate(x)])
+                    .collect_vec();
+                let csv = export::spectrum_csv(
+                    &points,
+                    &self.peak.display_label(self.pattern_naming),
+                    self.field_strength,
+                    self.peak.fwhm,
+                    self.export_normalization,
+                );
+                ui.output_mut(|o| o.copied_text = csv);
+            }
+            if ui
+                .button("Export data")
+                .on_hover_text(format!(
+                    "Save the displayed spectrum, sampled evenly across the visible range, as \
+                        {}",
+                    Self::SPECTRUM_FILE_NAME,
                 ))
-                .width(2.)
-                .fill(0.),
+                .clicked()
+            {
+                let waveform = self
+                    .cached_partial_cascade
+                    .final_lineshape_waveform(self.field_strength);
+                let (min, max) = self.linked_x_axis;
+                #[allow(clippy::cast_precision_loss)]
+                let step = (max - min) / (Self::SAMPLES - 1) as f64;
+                let points = (0..Self::SAMPLES)
+                    .map(|i| {
+                        #[allow(clippy::cast_precision_loss)]
+                        let x = min + i as f64 * step;
+                        [x, waveform.evaluate(x)]
+                    })
+                    .collect_vec();
+                let csv = export::spectrum_csv(
+                    &points,
+                    &self.peak.display_label(self.pattern_naming),
+                    self.field_strength,
+                    self.peak.fwhm,
+                    self.export_normalization,
+                );
+                self.save_export(
+                    ui.ctx(),
+                    Self::SPECTRUM_FILE_NAME,
+                    "spectrum",
+                    Ok(csv.into_bytes()),
+                );
+            }
+            if ui
+                .button("Export JCAMP-DX")
+                .on_hover_text(format!(
+                    "Save the displayed spectrum across the visible range as {}, the interchange \
+                        format of NMR software",
+                    Self::JCAMP_DX_FILE_NAME,
+                ))
+                .clicked()
+            {
+                let waveform = self
+                    .cached_partial_cascade
+                    .final_lineshape_waveform(self.field_strength);
+                let (min, max) = self.linked_x_axis;
+                let jdx = export::jcamp_dx(
+                    &waveform,
+                    &self.peak.display_label(self.pattern_naming),
+                    self.field_strength,
+                    min..=max,
+                    Self::SAMPLES,
+                );
+                self.save_export(
+                    ui.ctx(),
+                    Self::JCAMP_DX_FILE_NAME,
+                    "spectrum",
+                    Ok(jdx.into_bytes()),
+                );
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Intensities:").on_hover_text(
+                "How copied intensities are scaled. Raw line integrations sum to one, and the raw \
+                    spectrum is a density per ppm.",
             );
+            for normalization in Normalization::ALL {
+                ui.selectable_value(
+                    &mut self.export_normalization,
+                    normalization,
+                    normalization.name(),
+                );
+            }
+        });
 
-            if !self.show_peaklets {
+        let lines = export::line_list(&self.cached_partial_cascade);
+        let row_height = ui.text_style_height(&TextStyle::Body);
+        TableBuilder::new(ui)
+            .striped(true)
+            .max_scroll_height(150.)
+            .cell_layout(Layout::right_to_left(Align::Center))
+            .columns(Column::auto_with_initial_suggestion(80.), 3)
+            .header(row_height, |mut header| {
+                for text in ["δ (Hz)", "δ (ppm)", "Integration"] {
+                    header.col(|ui| {
+                        ui.label(RichText::new(text).underline());
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(row_height, lines.len(), |i, mut row| {
+                    let peaklet = lines[i];
+                    row.col(|ui| {
+                        ui.monospace(format!("{:.2}", peaklet.δ));
+                    });
+                    row.col(|ui| {
+                        let ppm = peak::j_to_ppm(peaklet.δ, self.field_strength);
+                        ui.monospace(format!("{ppm:.4}"));
+                    });
+                    row.col(|ui| {
+                        ui.monospace(format!("{:.4}", peaklet.integration));
+                    });
+                });
+            });
+    }
+
+    /// The relative intensities of the lines of the complete multiplet, as whole numbers.
+    fn intensity_ratios(&self, ui: &mut Ui) {
+        let cascade = self.peak.build_multiplet_cascade();
+        // Consistently with the chart of line intensities.
+        let lines = if self.merge_coincident_lines {
+            export::merged_line_list(&cascade)
+        } else {
+            export::line_list(&cascade)
+        };
+        let Some(ratios) = export::integer_ratios(&lines) else {
+            ui.label("The lines are not in a whole-number ratio.");
+            return;
+        };
+        let joined = ratios.iter().join(":");
+        ui.horizontal(|ui| {
+            ui.monospace(&joined);
+            if ui.button("Copy").clicked() {
+                ui.output_mut(|o| o.copied_text = joined.clone());
+            }
+        });
+        ui.label(if self.merge_coincident_lines {
+            "Coincident lines are merged."
+        } else {
+            "Coincident lines are listed separately."
+        })
+        .on_hover_text("As in the chart of line intensities");
+
+        let row_height = ui.text_style_height(&TextStyle::Body);
+        TableBuilder::new(ui)
+            .striped(true)
+            .max_scroll_height(150.)
+            .cell_layout(Layout::right_to_left(Align::Center))
+            .columns(Column::auto_with_initial_suggestion(80.), 2)
+            .header(row_height, |mut header| {
+                for text in ["δ (Hz)", "Ratio"] {
+                    header.col(|ui| {
+                        ui.label(RichText::new(text).underline());
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(row_height, lines.len(), |i, mut row| {
+                    row.col(|ui| {
+                        ui.monospace(format!("{:.2}", lines[i].δ));
+                    });
+                    row.col(|ui| {
+                        ui.monospace(ratios[i].to_string());
+                    });
+                });
+            });
+    }
+
+    fn update_partial_cascade(&mut self) {
+        let stopwatch = utils::Stopwatch::start();
+        self.cached_partial_cascade = self
+            .peak
+            .nth_partial_peak(FractionalStageIndex::new(*self.view_stage))
+            .build_multiplet_cascade();
+        self.cascade_build_ms = stopwatch.elapsed_ms();
+    }
+
+    /// The waveform of the displayed stage, as drawn: in ppm, and at the display FWHM.
+    fn displayed_waveform(&self) -> DistributionSum<PeakGeometry> {
+        if self.is_resolution_enhanced() {
+            let cascade = &self.cached_partial_cascade;
+            cascade
+                .with_fwhm(self.display_fwhm())
+                .final_lineshape_waveform(self.field_strength)
+        } else {
+            self.cached_partial_cascade
+                .final_lineshape_waveform(self.field_strength)
+        }
+    }
+
+    /// The displayed waveform together with the background, if any, as drawn and integrated.
+    fn displayed_spectrum(
+        &self,
+        waveform: &DistributionSum<PeakGeometry>,
+    ) -> DistributionSum<PeakGeometry> {
+        let background = self
+            .background
+            .enabled
+            .then(|| self.background.distribution(self.field_strength));
+        waveform.components().copied().chain(background).collect()
+    }
+
+    /// The total area of the displayed spectrum, that of the multiplet being one.
+    fn spectrum_area(&self) -> f64 {
+        if self.background.enabled {
+            1. + self.background.intensity
+        } else {
+            1.
+        }
+    }
+
+    /// The record of the current configuration having exceeded the rendering budget, if it has
+    /// done so often enough to fall back to drawing only its lines' positions.
+    fn over_budget(&self) -> Option<&OverBudget> {
+        self.last_over_budget.as_ref().filter(|over_budget| {
+            over_budget.overruns >= Self::OVERRUNS_BEFORE_FALLBACK
+                && over_budget.elapsed_ms > self.render_budget_ms
+                && self.is_current(over_budget)
+        })
+    }
+
+    /// Whether `over_budget` records the current configuration.
+    fn is_current(&self, over_budget: &OverBudget) -> bool {
+        over_budget.peak == self.peak
+            && approx::relative_eq!(over_budget.field_strength, self.field_strength)
+            && over_budget.line_count == self.cached_partial_cascade.final_peaklets().len()
+    }
+
+    /// Whether only the positions of the lines are drawn, as the spectrum is too expensive.
+    fn draws_sticks_only(&self) -> bool {
+        self.peak.total_peaklet_count() > Self::MAX_DRAWN_PEAKLET_COUNT
+            || self.over_budget().is_some()
+    }
+
+    fn peak_viewer(&mut self, ui: &mut Ui) {
+        if !self.presentation_mode {
+            utils::inner_bottom_panel("plot_interaction", ui, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label("Controls:");
+                    ui.code("drag");
+                    ui.label("to pan,");
+                    ui.code("scroll");
+                    ui.label("to zoom vertically,");
+                    ui.code("ctrl+scroll");
+                    ui.label("to zoom horizontally,");
+                    ui.code("alt+click");
+                    ui.label("to copy a position,");
+                    ui.code("shift+drag");
+                    ui.label("to integrate a region;");
+                    ui.code(shortcuts::format_action(ui.ctx(), Action::ToggleHelp));
+                    ui.label("for keyboard shortcuts.");
+                    ui.separator();
+                    self.x_range_entry(ui);
+                    ui.separator();
+                    self.span_readout(ui);
+                });
+            });
+        }
+
+        if self.show_integration_bars {
+            SidePanel::right("integration_bars")
+                .resizable(false)
+                .exact_width(Self::INTEGRATION_BARS_WIDTH)
+                .show_separator_line(false)
+                .show_inside(ui, |ui| self.integration_bars(ui));
+        }
+
+        let Vec2 {
+            x: available_width,
+            y: available_height,
+        } = ui.available_size();
+        let placement_origin = ui.next_widget_position();
+        let plot_height =
+            available_height - ui.text_style_height(&TextStyle::Body) - ui.spacing().item_spacing.y;
+
+        let stopwatch = utils::Stopwatch::start();
+        let over_budget = self.draws_sticks_only();
+        let waveform = self.displayed_waveform();
+        let spectrum = self.displayed_spectrum(&waveform);
+        let integral_annotation = self.annotate_integral.then(|| {
+            let extent = waveform.extent_by_fwhm(Self::INTEGRAL_ANNOTATION_WIDTH);
+            let label = self.integral_annotation_label(&waveform, &extent);
+            (extent, label)
+        });
+        let plot_link_id = ui.id().with("link");
+
+        let peak_plot = utils::make_noninteractable_plot("peak_plot")
+            .include_x(-self.default_x())
+            .include_x(self.default_x())
+            .include_y(Self::DEFAULT_Y * -0.05)
+            .include_y(Self::DEFAULT_Y * 1.1)
+            .allow_double_click_reset(true)
+            .show_background(self.show_plot_background)
+            .x_axis_formatter(|x, _| {
+                // Note that this is visual only!
+                eframe::emath::round_to_decimals(numerics::negate_nonzero(x), 4).to_string()
+            })
+            .link_axis(plot_link_id, true, false)
+            .legend(Legend::default().position(Corner::RightTop))
+            .height(plot_height);
+        peak_plot.show(ui, |plot_ui| {
+            if self.requested_x_axis.is_some() || self.requested_y_axis.is_some() {
+                let bounds = plot_ui.plot_bounds();
+                let (x_min, x_max) = self
+                    .requested_x_axis
+                    .take()
+                    .unwrap_or((bounds.min()[0], bounds.max()[0]));
+                let (y_min, y_max) = self
+                    .requested_y_axis
+                    .take()
+                    .unwrap_or((bounds.min()[1], bounds.max()[1]));
+                plot_ui.set_plot_bounds(PlotBounds::from_min_max([x_min, y_min], [x_max, y_max]));
+            }
+            let mut interaction_axes = self.interaction_axes;
+            if self.fixed_x_scale {
+                interaction_axes.zoom[0] = false;
+                let bounds = plot_ui.plot_bounds();
+                let center = 0.5 * (bounds.min()[0] + bounds.max()[0]);
+                let half_width =
+                    0.5 * self.x_scale * f64::from(plot_ui.transform().frame().width());
+                if !approx::relative_eq!(bounds.max()[0] - center, half_width) {
+                    plot_ui.set_plot_bounds(PlotBounds::from_min_max(
+                        [center - half_width, bounds.min()[1]],
+                        [center + half_width, bounds.max()[1]],
+                    ));
+                }
+            }
+            if let Some((y_min, y_max)) = self.pinned_y_window {
+                interaction_axes.pan[1] = false;
+                interaction_axes.zoom[1] = false;
+                let bounds = plot_ui.plot_bounds();
+                if !approx::relative_eq!(bounds.min()[1], y_min)
+                    || !approx::relative_eq!(bounds.max()[1], y_max)
+                {
+                    plot_ui.set_plot_bounds(PlotBounds::from_min_max(
+                        [bounds.min()[0], y_min],
+                        [bounds.max()[0], y_max],
+                    ));
+                }
+            }
+            if self.drag_fwhm(plot_ui, &waveform) || self.select_integration_region(plot_ui) {
+                interaction_axes.pan = [false; 2];
+            }
+            if let Some(copied) =
+                utils::peak_viewer_interactions(plot_ui, interaction_axes, self.field_strength)
+            {
+                self.toasts.push(plot_ui.ctx(), format!("Copied {copied}"));
+            }
+            let bounds = plot_ui.plot_bounds();
+            self.displayed_ppm_per_point =
+                Some(bounds.width() / f64::from(plot_ui.transform().frame().width()))
+                    .filter(|scale| scale.is_finite() && *scale > 0.);
+            self.linked_x_axis = (bounds.min()[0], bounds.max()[0]);
+            self.linked_y_axis = (bounds.min()[1], bounds.max()[1]);
+            self.next_crosshair = self.next_crosshair.or(utils::hovered_x(plot_ui));
+            utils::draw_crosshair(plot_ui, self.crosshair);
+            self.draw_partial_stage_watermark(plot_ui);
+            self.draw_theory_badge(plot_ui);
+
+            self.draw_integration_regions(plot_ui, &spectrum);
+            if over_budget {
+                // Sticks are the only representation cheap enough to draw.
+                self.draw_sticks(plot_ui, &waveform);
                 return;
             }
-            for &peaklet in waveform.components() {
+
+            // There is currently only a single peak.
+            let peak_color = palette::peak_color(0);
+
+            let (samples, qualifier) = match self.digitized_bin_width(plot_ui.plot_bounds()) {
+                Some(bin_width) => (
+                    Self::digitize_waveform(&spectrum, plot_ui.plot_bounds(), bin_width),
+                    Some("digitized"),
+                ),
+                None => (
+                    Self::sample_waveform(&spectrum, plot_ui.plot_bounds()),
+                    self.is_resolution_enhanced()
+                        .then_some("resolution-enhanced"),
+                ),
+            };
+            self.spectrum_fill.draw(plot_ui, &samples, peak_color);
+            plot_ui.line(
+                Line::new(samples)
+                    .color(peak_color)
+                    .name(match qualifier {
+                        Some(qualifier) => format!(
+                            "{} ({qualifier})",
+                            self.peak.display_label(self.pattern_naming)
+                        ),
+                        None => self.peak.display_label(self.pattern_naming),
+                    })
+                    .width(2.),
+            );
+
+            if self.compare_fwhms {
+                self.draw_fwhm_comparison(plot_ui);
+            }
+            if self.show_sticks {
+                self.draw_sticks(plot_ui, &waveform);
+            }
+            if self.show_merges || self.fwhm_before_sweep.is_some() {
+                self.draw_merges(plot_ui, &waveform);
+            }
+            if self.background.enabled {
+                let background = self.background.distribution(self.field_strength);
                 plot_ui.line(
                     Line::new(PlotPoints::from_explicit_callback(
-                        move |x| peaklet.evaluate(x),
-                        peaklet.extent_by_fwhm(Self::PEAKLET_WIDTH),
+                        move |x| background.evaluate(x),
+                        background.extent_by_fwhm(Self::PEAKLET_WIDTH),
                         Self::SAMPLES / 10,
                     ))
-                    .color(Color32::LIGHT_BLUE),
+                    .color(peak_color)
+                    .style(LineStyle::dashed_loose())
+                    .name("Background"),
+                );
+            }
+            if self.show_processed_spectrum {
+                // Convert from Hz and density per Hz to ppm and density per ppm.
+                let field_strength = self.field_strength;
+                let spectrum = self
+                    .processed_fid()
+                    .spectrum()
+                    .into_iter()
+                    .map(|[ν, y]| [peak::j_to_ppm(ν, field_strength), y * field_strength])
+                    .collect_vec();
+                self.overlay_fill
+                    .draw(plot_ui, &spectrum, Self::OVERLAPPING_PEAKLET_COLOR);
+                plot_ui.line(
+                    Line::new(spectrum)
+                        .color(Self::OVERLAPPING_PEAKLET_COLOR)
+                        .width(1.5)
+                        .name("Processed spectrum"),
                 );
             }
+            if self.show_sample_overlay {
+                Self::draw_sample_overlay(plot_ui, &spectrum);
+            }
+            if self.show_integral && self.integral_mode == IntegralMode::Combined {
+                self.draw_combined_integral(plot_ui, &spectrum, integral_annotation.clone());
+            }
+
+            if self.is_density_strip_shown() {
+                Self::draw_density_strip(plot_ui, &waveform, peak_color);
+                return;
+            }
+            if !self.show_peaklets {
+                return;
+            }
+            // Both are sorted by shift.
+            let overlaps = self
+                .cached_partial_cascade
+                .final_peaklets_with_overlap()
+                .into_iter()
+                .map(|(_, overlaps)| overlaps);
+            for (&peaklet, overlaps) in waveform.components().zip(overlaps) {
+                let mut line = Line::new(PlotPoints::from_explicit_callback(
+                    move |x| peaklet.evaluate(x),
+                    peaklet.extent_by_fwhm(Self::PEAKLET_WIDTH),
+                    Self::SAMPLES / 10,
+                ));
+                if self.highlight_overlaps {
+                    line = if overlaps {
+                        line.color(Self::OVERLAPPING_PEAKLET_COLOR)
+                            .name("Overlapping contribution")
+                    } else {
+                        line.color(Color32::LIGHT_BLUE)
+                            .name("Resolved contribution")
+                    };
+                } else {
+                    line = line
+                        .color(Color32::LIGHT_BLUE)
+                        .name("Individual contributions");
+                }
+                plot_ui.line(line);
+            }
+            if let Some(pinned) = self.pinned_peaklet {
+                self.draw_lineage_contributions(plot_ui, pinned);
+            }
         });
         ui.vertical_centered(|ui| ui.label("δ (ppm)"));
 
-        if !self.show_integral {
+        if !over_budget {
+            let elapsed_ms = stopwatch.elapsed_ms() + std::mem::take(&mut self.cascade_build_ms);
+            self.last_over_budget = (elapsed_ms > self.render_budget_ms).then(|| {
+                let overruns = self
+                    .last_over_budget
+                    .as_ref()
+                    .filter(|last| self.is_current(last))
+                    .map_or(0, |last| last.overruns);
+                OverBudget {
+                    peak: self.peak.clone(),
+                    field_strength: self.field_strength,
+                    line_count: self.cached_partial_cascade.final_peaklets().len(),
+                    elapsed_ms,
+                    overruns: overruns + 1,
+                }
+            });
+        }
+
+        if over_budget || !self.show_integral || self.integral_mode != IntegralMode::Overlaid {
             return;
         }
 
+        // The integral is in protons, rising to the proton count.
+        let protons = f64::from(self.peak.proton_count);
         let integral_plot = utils::make_noninteractable_plot("integral_plot")
-            .include_x(-Self::DEFAULT_X)
-            .include_x(Self::DEFAULT_X)
-            .include_y(-0.05)
-            .include_y(1.05)
+            .include_x(-self.default_x())
+            .include_x(self.default_x())
+            .include_y(-0.05 * protons)
+            .include_y(1.05 * protons)
             .show_axes([false; 2])
             .show_background(false)
+            // The peak plot's legend occupies the right.
+            .legend(Legend::default().position(Corner::LeftTop))
             .link_axis(plot_link_id, true, false);
+        let proton_count = self.peak.proton_count;
+        let spectrum_area = self.spectrum_area();
+        let interaction_axes = self.interaction_axes;
+        let field_strength = self.field_strength;
+        let crosshair = self.crosshair;
+        let mut copied = None;
+        let mut hovered = None;
         let draw_integral_plot = |ui: &mut Ui| {
             integral_plot
                 .show(ui, |plot_ui: &mut PlotUi| {
-                    let extent = waveform.extent_by_fwhm(Self::INTEGRAL_WIDTH);
+                    let extent = spectrum.extent_by_fwhm(Self::INTEGRAL_WIDTH);
+                    let label_x = extent.end().min(plot_ui.plot_bounds().max()[0]);
+                    plot_ui.text(
+                        Text::new(PlotPoint::new(label_x, protons), format!("{proton_count}H"))
+                            .anchor(Align2::RIGHT_BOTTOM)
+                            .color(Color32::LIGHT_GREEN)
+                            .name("Integral"),
+                    );
+                    Self::draw_proton_scale(plot_ui, label_x, proton_count, |cdf| cdf * protons);
+                    if let Some((extent, label)) = integral_annotation {
+                        Self::draw_integral_annotation(
+                            plot_ui,
+                            &spectrum,
+                            extent,
+                            spectrum_area,
+                            &label,
+                            |cdf| cdf * protons,
+                        );
+                    }
                     plot_ui.line(
                         Line::new(PlotPoints::from_explicit_callback(
-                            move |x| waveform.evaluate_cdf(x),
+                            move |x| protons * spectrum.evaluate_cdf(x) / spectrum_area,
                             extent,
                             Self::SAMPLES / 2,
                         ))
                         .width(2.)
-                        .color(Color32::LIGHT_GREEN),
+                        .color(Color32::LIGHT_GREEN)
+                        .name("Integral"),
+                    );
+                    copied = utils::peak_viewer_interactions(
+                        plot_ui,
+                        interaction_axes.horizontal_only(),
+                        field_strength,
                     );
-                    utils::peak_viewer_interactions(plot_ui, false);
+                    hovered = utils::hovered_x(plot_ui);
+                    utils::draw_crosshair(plot_ui, crosshair);
                 })
                 .response
         };
         ui.put(
             Rect::from_min_size(
-                (placement_origin.x, placement_origin.y + plot_height * 0.025).into(),
-                (available_width, plot_height * 0.2).into(),
+                (
+                    placement_origin.x,
+                    placement_origin.y + plot_height * self.integral_placement.offset,
+                )
+                    .into(),
+                (
+                    available_width,
+                    plot_height * self.integral_placement.height,
+                )
+                    .into(),
             ),
             draw_integral_plot,
         );
+        if let Some(copied) = copied {
+            self.toasts.push(ui.ctx(), format!("Copied {copied}"));
+        }
+        self.next_crosshair = self.next_crosshair.or(hovered);
+    }
+
+    /// Inputs for precisely setting the window of the peak viewer. Note that, per convention, the
+    /// displayed δ decreases from left to right.
+    fn x_range_entry(&mut self, ui: &mut Ui) {
+        const MIN_WIDTH: f64 = 1e-4;
+        let (x_min, x_max) = self.linked_x_axis;
+        let mut left = numerics::negate_nonzero(x_min);
+        let mut right = numerics::negate_nonzero(x_max);
+
+        ui.label("Window:");
+        let left_response = ui.add(
+            DragValue::new(&mut left)
+                .clamp_range((right + MIN_WIDTH)..=Self::MAX_X_WINDOW)
+                .speed(1e-3)
+                .fixed_decimals(4)
+                .suffix(" ppm"),
+        );
+        ui.label("to");
+        let right_response = ui.add(
+            DragValue::new(&mut right)
+                .clamp_range(-Self::MAX_X_WINDOW..=(left - MIN_WIDTH))
+                .speed(1e-3)
+                .fixed_decimals(4)
+                .suffix(" ppm"),
+        );
+        if left_response.changed() || right_response.changed() {
+            self.requested_x_axis = Some((-left, -right));
+        }
+    }
+
+    /// The width of the multiplet in Hz, with a button to frame the viewer to it.
+    fn span_readout(&mut self, ui: &mut Ui) {
+        let (min, max) = self.cached_partial_cascade.final_span().into_inner();
+        let padding = Self::FRAME_PADDING * self.peak.fwhm;
+        let (min, max) = (min - padding, max + padding);
+        ui.label(format!("Span: {:.1} Hz", max - min))
+            .on_hover_text(format!(
+                "Distance between the outermost lines, plus {} FWHM on either side",
+                Self::FRAME_PADDING,
+            ));
+        if ui
+            .button("Frame")
+            .on_hover_text("Set the window to exactly this span")
+            .clicked()
+        {
+            self.requested_x_axis = Some((
+                peak::j_to_ppm(min, self.field_strength),
+                peak::j_to_ppm(max, self.field_strength),
+            ));
+        }
+    }
+
+    /// Adaptively sample the waveform for display within `bounds`. Half a window is
+    /// additionally sampled on either side, as the bounds lag by a frame while panning.
+    fn sample_waveform(
+        waveform: &DistributionSum<PeakGeometry>,
+        bounds: PlotBounds,
+    ) -> Vec<[f64; 2]> {
+        let (min, max) = (bounds.min()[0], bounds.max()[0]);
+        let padding = 0.5 * (max - min);
+        waveform
+            .adaptive_sample_grid((min - padding)..=(max + padding), Self::SAMPLES)
+            .into_iter()
+            .map(|x| [x, waveform.evaluate(x)])
+            .collect()
+    }
+
+    /// The width (ppm) of the bins into which the waveform is digitized, if limited digital
+    /// resolution is enabled and the bins would be distinguishable within `bounds`.
+    fn digitized_bin_width(&self, bounds: PlotBounds) -> Option<f64> {
+        let bin_width = peak::j_to_ppm(1. / self.points_per_hz, self.field_strength);
+        let bin_count = (bounds.max()[0] - bounds.min()[0]) / bin_width;
+        (self.limit_digital_resolution && bin_count < Self::SAMPLES as f64).then_some(bin_width)
+    }
+
+    /// Digitize the waveform into bins of `bin_width` within (and half a window beyond) `bounds`.
+    /// The bins are joined by straight lines, as a spectrum with limited digital resolution is
+    /// drawn.
+    fn digitize_waveform(
+        waveform: &DistributionSum<PeakGeometry>,
+        bounds: PlotBounds,
+        bin_width: f64,
+    ) -> Vec<[f64; 2]> {
+        let (min, max) = (bounds.min()[0], bounds.max()[0]);
+        let padding = 0.5 * (max - min);
+        waveform.rebin((min - padding)..=(max + padding), bin_width)
+    }
+
+    /// Chart the relative integration of each line of the displayed stage, normalized to the most
+    /// intense.
+    fn integration_bars(&self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| ui.label("Line intensities"));
+        let lines = if self.merge_coincident_lines {
+            export::merged_line_list(&self.cached_partial_cascade)
+        } else {
+            export::line_list(&self.cached_partial_cascade)
+        };
+        let max_integration = lines
+            .iter()
+            .map(|peaklet| peaklet.integration)
+            .reduce(f64::max)
+            .unwrap_or(1.);
+        let color = palette::peak_color(0);
+        let bars = lines
+            .iter()
+            .enumerate()
+            .map(|(i, peaklet)| {
+                let relative = peaklet.integration / max_integration;
+                Bar::new(i as f64, relative)
+                    .width(0.6)
+                    .name(format!("{:.1} Hz: {relative:.3}", peaklet.δ))
+                    .fill(color.linear_multiply(0.5))
+                    .stroke(Stroke::new(1., color))
+            })
+            .collect();
+        utils::make_noninteractable_plot("integration_bars")
+            .include_x(-0.5)
+            .include_x(lines.len() as f64 - 0.5)
+            .include_y(0.)
+            .include_y(1.05)
+            .show_axes([false, true])
+            .show_background(self.show_plot_background)
+            .show(ui, |plot_ui| {
+                plot_ui.bar_chart(
+                    BarChart::new(bars)
+                        .color(color)
+                        .name("Relative integration"),
+                );
+            });
+    }
+
+    /// Draw the integral within the peak plot, occupying the same region of the plot as it would in
+    /// [`IntegralMode::Overlaid`].
+    fn draw_combined_integral(
+        &self,
+        plot_ui: &mut PlotUi,
+        waveform: &DistributionSum<PeakGeometry>,
+        integral_annotation: Option<(RangeInclusive<f64>, String)>,
+    ) {
+        // Span the same range as the overlaid plot, which includes `-0.05..=1.05`.
+        const LOW: f64 = -0.05;
+        const HIGH: f64 = 1.05;
+        let bounds = plot_ui.plot_bounds();
+        let [_, y_min] = bounds.min();
+        let [_, y_max] = bounds.max();
+        let height = y_max - y_min;
+        let top = y_max - f64::from(self.integral_placement.offset) * height;
+        let scale = f64::from(self.integral_placement.height) * height / (HIGH - LOW);
+        let to_plot = |cdf: f64| top - (HIGH - cdf) * scale;
+
+        let extent = waveform.extent_by_fwhm(Self::INTEGRAL_WIDTH);
+        let points = waveform
+            .adaptive_sample_grid(extent.clone(), Self::SAMPLES / 2)
+            .into_iter()
+            .map(|x| [x, to_plot(waveform.evaluate_cdf(x) / self.spectrum_area())])
+            .collect::<PlotPoints>();
+        let label_x = extent.end().min(bounds.max()[0]);
+        plot_ui.text(
+            Text::new(
+                PlotPoint::new(label_x, to_plot(1.)),
+                format!("{}H", self.peak.proton_count),
+            )
+            .anchor(Align2::RIGHT_BOTTOM)
+            .color(Color32::LIGHT_GREEN)
+            .name("Integral"),
+        );
+        Self::draw_proton_scale(plot_ui, label_x, self.peak.proton_count, to_plot);
+        plot_ui.line(
+            Line::new(points)
+                .width(2.)
+                .color(Color32::LIGHT_GREEN)
+                .name("Integral"),
+        );
+        if let Some((extent, label)) = integral_annotation {
+            Self::draw_integral_annotation(
+                plot_ui,
+                waveform,
+                extent,
+                self.spectrum_area(),
+                &label,
+                to_plot,
+            );
+        }
+    }
+
+    /// Mark each whole proton on the integral with a tick to the left of `x`, such that its rise can
+    /// be read in protons. `to_plot` maps the (normalized) integral to the height at which it is
+    /// drawn.
+    fn draw_proton_scale(
+        plot_ui: &mut PlotUi,
+        x: f64,
+        proton_count: u32,
+        to_plot: impl Fn(f64) -> f64,
+    ) {
+        const TICK_LENGTH: f64 = 0.01;
+        let length =
+            TICK_LENGTH * (plot_ui.plot_bounds().max()[0] - plot_ui.plot_bounds().min()[0]);
+        let color = Color32::LIGHT_GREEN.linear_multiply(0.6);
+        plot_ui.line(Line::new(vec![[x, to_plot(0.)], [x, to_plot(1.)]]).color(color));
+        for protons in 0..=proton_count {
+            let y = to_plot(f64::from(protons) / f64::from(proton_count));
+            plot_ui.line(Line::new(vec![[x - length, y], [x, y]]).color(color));
+        }
+    }
+
+    /// The share of the multiplet, `waveform`, within `extent`, in terms of the proton count. The
+    /// background is left out, such that the share is of the multiplet alone.
+    fn integral_annotation_label(
+        &self,
+        waveform: &DistributionSum<PeakGeometry>,
+        extent: &RangeInclusive<f64>,
+    ) -> String {
+        let share = waveform.evaluate_cdf(*extent.end()) - waveform.evaluate_cdf(*extent.start());
+        let proton_count = self.peak.proton_count;
+        let label = format!(
+            "+{:.0}% ≈ {:.2}H of {proton_count}H",
+            100. * share,
+            share * f64::from(proton_count)
+        );
+        if self.background.enabled {
+            format!("{label} (excluding background)")
+        } else {
+            label
+        }
+    }
+
+    /// Mark the `extent` of the multiplet on the integral of `spectrum`, and label the rise of the
+    /// integral across it with `label`; see [`Self::integral_annotation_label`]. `to_plot` maps
+    /// the (normalized) integral to the height at which it is drawn.
+    fn draw_integral_annotation(
+        plot_ui: &mut PlotUi,
+        spectrum: &DistributionSum<PeakGeometry>,
+        extent: RangeInclusive<f64>,
+        spectrum_area: f64,
+        label: &str,
+        to_plot: impl Fn(f64) -> f64,
+    ) {
+        let (start, end) = (*extent.start(), *extent.end());
+        let below = spectrum.evaluate_cdf(start) / spectrum_area;
+        let above = spectrum.evaluate_cdf(end) / spectrum_area;
+        let color = Color32::LIGHT_GREEN.linear_multiply(0.6);
+        for x in [extent.start(), extent.end()] {
+            plot_ui.line(
+                Line::new(vec![[*x, to_plot(0.)], [*x, to_plot(1.)]])
+                    .color(color)
+                    .style(LineStyle::dashed_dense()),
+            );
+        }
+        for level in [below, above] {
+            plot_ui.line(
+                Line::new(vec![[start, to_plot(level)], [end, to_plot(level)]])
+                    .color(color)
+                    .style(LineStyle::dotted_dense()),
+            );
+        }
+        plot_ui.text(
+            Text::new(PlotPoint::new(start, to_plot(0.5 * (below + above))), label)
+                .anchor(Align2::RIGHT_CENTER)
+                .color(Color32::LIGHT_GREEN),
+        );
+    }
+
+    /// Let the width of the peak be set by dragging the half-maximum points of any of the lines of
+    /// `waveform`. Returns whether the pointer is interacting with such a point, in which case it
+    /// should not otherwise pan the plot.
+    fn drag_fwhm(
+        &mut self,
+        plot_ui: &mut PlotUi,
+        waveform: &DistributionSum<PeakGeometry>,
+    ) -> bool {
+        const GRAB_RADIUS: f32 = 8.;
+
+        if !self.can_modify_configuration() || self.presentation_mode {
+            self.fwhm_grab = None;
+            return false;
+        }
+        let Some(pointer) = plot_ui.pointer_coordinate() else {
+            return self.fwhm_grab.is_some();
+        };
+        let (pressed, down) = plot_ui
+            .ctx()
+            .input(|i| (i.pointer.primary_pressed(), i.pointer.primary_down()));
+
+        if let Some(μ) = self.fwhm_grab {
+            if !down {
+                self.fwhm_grab = None;
+                return false;
+            }
+            // The drawn width may be narrowed by resolution enhancement.
+            let displayed = 2. * (pointer.x - μ).abs() * self.field_strength;
+            let max_fwhm = if self.broad_lines {
+                Self::MAX_BROAD_FWHM
+            } else {
+                Self::MAX_FWHM
+            };
+            self.peak.fwhm =
+                (displayed / self.resolution_enhancement).clamp(Self::MIN_FWHM, max_fwhm);
+            plot_ui.ctx().set_cursor_icon(CursorIcon::ResizeHorizontal);
+            return true;
+        }
+
+        if !plot_ui.plot_hovered() {
+            return false;
+        }
+        let pointer_pos = plot_ui.screen_from_plot(pointer);
+        let handle = waveform.components().find_map(|line| {
+            let μ = line.μ();
+            let half_width = 0.5 * line.fwhm();
+            [μ - half_width, μ + half_width]
+                .into_iter()
+                .map(|x| PlotPoint::new(x, waveform.evaluate(x)))
+                .find(|&point| plot_ui.screen_from_plot(point).distance(pointer_pos) < GRAB_RADIUS)
+                .map(|point| (μ, point))
+        });
+        let Some((μ, point)) = handle else {
+            return false;
+        };
+        plot_ui.ctx().set_cursor_icon(CursorIcon::ResizeHorizontal);
+        plot_ui.points(
+            Points::new(vec![[point.x, point.y]])
+                .radius(4.)
+                .color(plot_ui.ctx().style().visuals.text_color())
+                .name("Drag to change the FWHM"),
+        );
+        if pressed {
+            self.fwhm_grab = Some(μ);
+        }
+        true
+    }
+
+    /// Shift-drag across the spectrum to add an integration region. Returns whether a region is
+    /// being selected, in which case the plot should not pan.
+    fn select_integration_region(&mut self, plot_ui: &mut PlotUi) -> bool {
+        let Some(pointer) = plot_ui.pointer_coordinate() else {
+            return self.region_anchor.is_some();
+        };
+        let (pressed, down, shift) = plot_ui.ctx().input(|i| {
+            (
+                i.pointer.primary_pressed(),
+                i.pointer.primary_down(),
+                i.modifiers.shift,
+            )
+        });
+
+        let Some(anchor) = self.region_anchor else {
+            if pressed && shift && plot_ui.plot_hovered() {
+                self.region_anchor = Some(pointer.x);
+                return true;
+            }
+            return false;
+        };
+        let region = (anchor.min(pointer.x), anchor.max(pointer.x));
+        if down {
+            plot_ui.ctx().set_cursor_icon(CursorIcon::ResizeColumn);
+            let bounds = plot_ui.plot_bounds();
+            plot_ui.polygon(Self::region_span(region, bounds).name("Integration region"));
+            return true;
+        }
+        self.region_anchor = None;
+        if region.1 > region.0 {
+            self.integration_regions.push(region);
+        }
+        false
+    }
+
+    /// A shaded span covering `region` across the full height of `bounds`.
+    fn region_span((min, max): (f64, f64), bounds: PlotBounds) -> Polygon {
+        let (bottom, top) = (bounds.min()[1], bounds.max()[1]);
+        Polygon::new(vec![[min, bottom], [max, bottom], [max, top], [min, top]])
+            .color(Self::INTEGRATION_REGION_COLOR)
+            .fill_alpha(0.15)
+            .width(0.)
+    }
+
+    /// The area of the waveform within each integration region, as a fraction of the total.
+    fn integration_region_areas(&self, waveform: &DistributionSum<PeakGeometry>) -> Vec<f64> {
+        self.integration_regions
+            .iter()
+            .map(|&(min, max)| waveform.evaluate_cdf(max) - waveform.evaluate_cdf(min))
+            .collect()
+    }
+
+    /// `areas`, relative to the first of them which is not negligible ([`Self::NEGLIGIBLE_AREA`]),
+    /// or `None` if all are.
+    fn relative_areas(areas: &[f64]) -> Vec<Option<f64>> {
+        let reference = areas.iter().find(|&&area| area > Self::NEGLIGIBLE_AREA);
+        areas
+            .iter()
+            .map(|area| reference.map(|reference| area / reference))
+            .collect()
+    }
+
+    /// Shade each integration region, labeled with its area relative to that of the first region
+    /// that has any; see [`Self::relative_areas`].
+    fn draw_integration_regions(
+        &self,
+        plot_ui: &mut PlotUi,
+        waveform: &DistributionSum<PeakGeometry>,
+    ) {
+        let bounds = plot_ui.plot_bounds();
+        let areas = Self::relative_areas(&self.integration_region_areas(waveform));
+        for (&region, area) in self.integration_regions.iter().zip(areas) {
+            plot_ui.polygon(Self::region_span(region, bounds).name("Integration regions"));
+            plot_ui.text(
+                Text::new(
+                    PlotPoint::new(0.5 * (region.0 + region.1), bounds.max()[1]),
+                    area.map_or_else(|| "—".to_owned(), |area| format!("{area:.2}")),
+                )
+                .anchor(Align2::CENTER_TOP)
+                .color(Self::INTEGRATION_REGION_COLOR)
+                .name("Integration regions"),
+            );
+        }
+    }
+
+    /// Tabulate the integration regions: their bounds, their areas, and the ratios between them.
+    fn integration_region_list(&mut self, ui: &mut Ui) {
+        if self.integration_regions.is_empty() {
+            ui.label("Shift-drag across the spectrum to integrate a region.");
+            return;
+        }
+        let areas =
+            self.integration_region_areas(&self.displayed_spectrum(&self.displayed_waveform()));
+        let relative_areas = Self::relative_areas(&areas);
+        let proton_count = f64::from(self.peak.proton_count);
+        let row_height = ui.text_style_height(&TextStyle::Body) + ui.spacing().item_spacing.y;
+        let mut removed = None;
+        TableBuilder::new(ui)
+            .striped(true)
+            .cell_layout(Layout::right_to_left(Align::Center))
+            .columns(Column::auto_with_initial_suggestion(60.), 5)
+            .header(row_height, |mut header| {
+                let mut col = |text: &str| {
+                    header
+                        .col(|ui| {
+                            ui.label(RichText::new(text).underline());
+                        })
+                        .1
+                };
+                col("δ (ppm)");
+                col("Area").on_hover_text("As a fraction of that of the whole peak");
+                col("Protons").on_hover_text("The area, in proportion to the peak's proton count");
+                col("Relative").on_hover_text(
+                    "The area, in proportion to that of the first region which has any",
+                );
+                col("");
+            })
+            .body(|mut body| {
+                for (i, ((&(min, max), &area), relative_area)) in self
+                    .integration_regions
+                    .iter()
+                    .zip(&areas)
+                    .zip(&relative_areas)
+                    .enumerate()
+                {
+                    body.row(row_height, |mut row| {
+                        row.col(|ui| {
+                            // The x-axis is negated for display; see the peak viewer's axis formatter.
+                            let (left, right) =
+                                (numerics::negate_nonzero(min), numerics::negate_nonzero(max));
+                            ui.monospace(format!("{left:.4} – {right:.4}"));
+                        });
+                        row.col(|ui| {
+                            ui.monospace(format!("{area:.4}"));
+                        });
+                        row.col(|ui| {
+                            ui.monospace(format!("{:.2}", area * proton_count));
+                        });
+                        row.col(|ui| {
+                            ui.monospace(relative_area.map_or_else(
+                                || "—".to_owned(),
+                                |relative_area| format!("{relative_area:.3}"),
+                            ));
+                        });
+                        row.col(|ui| {
+                            // U+2717 BALLOT X.
+                            if ui.button("\u{2717}").on_hover_text("Delete").clicked() {
+                                removed = Some(i);
+                            }
+                        });
+                    });
+                }
+            });
+        if let Some(i) = removed {
+            self.integration_regions.remove(i);
+        }
+        if ui.button("Clear").clicked() {
+            self.integration_regions.clear();
+        }
+    }
+
+    fn toggle_fwhm_sweep(&mut self, max_fwhm: f64) {
+        if let Some(fwhm) = self.fwhm_before_sweep.take() {
+            self.fwhm_sweep.stop_animating();
+            self.peak.fwhm = fwhm;
+        } else {
+            self.fwhm_before_sweep = Some(self.peak.fwhm);
+            self.fwhm_sweep
+                .set_range_clamping(Self::MIN_FWHM..=max_fwhm);
+            self.fwhm_sweep.set_value_clamping(self.peak.fwhm);
+            self.fwhm_sweep.start_animating();
+        }
+    }
+
+    /// The peak as configured, which differs from [`Self::peak`] in its FWHM while sweeping.
+    fn resting_peak(&self) -> Peak {
+        Peak {
+            fwhm: self.fwhm_before_sweep.unwrap_or(self.peak.fwhm),
+            ..self.peak.clone()
+        }
+    }
+
+    fn tick_fwhm_sweep(&mut self, ctx: &Context) {
+        if self.fwhm_before_sweep.is_some() {
+            self.fwhm_sweep.tick(ctx);
+            self.peak.fwhm = *self.fwhm_sweep;
+        }
+    }
+
+    /// Mark the top of each line of the displayed stage by whether it has merged with a neighbor
+    /// at the displayed FWHM.
+    fn draw_merges(&self, plot_ui: &mut PlotUi, waveform: &DistributionSum<PeakGeometry>) {
+        let (merged, resolved): (Vec<_>, Vec<_>) = self
+            .cached_partial_cascade
+            .with_fwhm(self.display_fwhm())
+            .final_peaklets_with_overlap()
+            .into_iter()
+            .map(|(peaklet, overlaps)| {
+                let x = peak::j_to_ppm(peaklet.δ, self.field_strength);
+                ([x, waveform.evaluate(x)], overlaps)
+            })
+            .partition(|&(_, overlaps)| overlaps);
+        let points = |lines: Vec<([f64; 2], bool)>| {
+            Points::new(lines.into_iter().map(|(point, _)| point).collect_vec()).radius(4.)
+        };
+        plot_ui.points(
+            points(merged)
+                .color(Self::OVERLAPPING_PEAKLET_COLOR)
+                .name("Merged line"),
+        );
+        plot_ui.points(
+            points(resolved)
+                .color(Color32::LIGHT_BLUE)
+                .name("Resolved line"),
+        );
+    }
+
+    /// Overlay the displayed stage as it would appear at each of the comparison FWHMs, all else
+    /// being equal.
+    fn draw_fwhm_comparison(&self, plot_ui: &mut PlotUi) {
+        for (i, &fwhm) in self.comparison_fwhms.iter().enumerate() {
+            let waveform = self
+                .cached_partial_cascade
+                .with_fwhm(fwhm)
+                .final_lineshape_waveform(self.field_strength);
+            let samples = Self::sample_waveform(&waveform, plot_ui.plot_bounds());
+            self.overlay_fill
+                .draw(plot_ui, &samples, palette::peak_color(i + 1));
+            plot_ui.line(
+                Line::new(samples)
+                    .color(palette::peak_color(i + 1))
+                    .width(1.5)
+                    .name(format!("FWHM {fwhm:.2} Hz")),
+            );
+        }
+    }
+
+    /// Label the spectrum as intermediate while a splitting is only partially applied, such that
+    /// captures of it are not mistaken for a real pattern.
+    fn draw_partial_stage_watermark(&self, plot_ui: &mut PlotUi) {
+        // Levels displayed as whole are not flagged either.
+        let stage = FractionalStageIndex::new(*self.view_stage);
+        if !stage.has_significant_partial() || self.stage_format.is_whole(*self.view_stage) {
+            return;
+        }
+        let bounds = plot_ui.plot_bounds();
+        plot_ui.text(
+            Text::new(
+                PlotPoint::new(bounds.min()[0], bounds.max()[1]),
+                RichText::new(format!(
+                    "Partial splitting: level {}",
+                    self.stage_format.format(*self.view_stage)
+                ))
+                .italics(),
+            )
+            .anchor(Align2::LEFT_TOP)
+            .color(Color32::GRAY),
+        );
+    }
+
+    /// Label the spectrum with the level of theory by which it is computed, warning when this is
+    /// inaccurate given the separation to the coupling partners; see [`Self::first_order_check`],
+    /// which links to an explanation, as text within the plot cannot be hovered.
+    // TODO: distinguish the engines once a second-order one exists.
+    fn draw_theory_badge(&self, plot_ui: &mut PlotUi) {
+        let ratio = self
+            .peak
+            .weak_coupling_ratio(self.partner_separation * self.field_strength)
+            .filter(|&ratio| ratio < Self::FIRST_ORDER_THRESHOLD);
+        let (text, color) = match ratio {
+            Some(ratio) => (
+                format!("⚠ First-order, approximate at Δν/J = {ratio:.1}"),
+                plot_ui.ctx().style().visuals.warn_fg_color,
+            ),
+            None => ("First-order".to_owned(), Color32::GRAY),
+        };
+        let bounds = plot_ui.plot_bounds();
+        plot_ui.text(
+            Text::new(
+                PlotPoint::new(bounds.max()[0], bounds.min()[1]),
+                RichText::new(text).small(),
+            )
+            .anchor(Align2::RIGHT_BOTTOM)
+            .color(color),
+        );
+    }
+
+    /// Debugging aid: mark each point at which the waveform is sampled, to reveal under-resolved
+    /// features.
+    fn draw_sample_overlay(plot_ui: &mut PlotUi, waveform: &DistributionSum<PeakGeometry>) {
+        let bounds = plot_ui.plot_bounds();
+        let samples = Self::sample_waveform(waveform, bounds)
+            .into_iter()
+            .filter(|[x, _]| (bounds.min()[0]..=bounds.max()[0]).contains(x))
+            .collect::<Vec<_>>();
+        plot_ui.points(
+            Points::new(samples)
+                .radius(1.5)
+                .color(Color32::RED)
+                .name("Samples"),
+        );
+    }
+
+    /// Overlay the stick spectrum on the waveform. The sticks are scaled by integration such that
+    /// the most intense coincides with the (estimated) maximum of the waveform.
+    fn draw_sticks(&self, plot_ui: &mut PlotUi, waveform: &DistributionSum<PeakGeometry>) {
+        let peaklets = self.cached_partial_cascade.final_peaklets();
+        let max_integration = peaklets
+            .iter()
+            .map(|peaklet| peaklet.integration)
+            .reduce(f64::max)
+            .unwrap_or(1.);
+        let scale = waveform.max() / max_integration;
+        let color = plot_ui.ctx().style().visuals.text_color();
+        for peaklet in peaklets {
+            let x = peak::j_to_ppm(peaklet.δ, self.field_strength);
+            plot_ui.line(
+                Line::new(vec![[x, 0.], [x, peaklet.integration * scale]])
+                    .color(color)
+                    .width(1.)
+                    .name("Sticks"),
+            );
+        }
+    }
+
+    /// Highlight the contributions to the displayed stage which are in the lineage of the `pinned`
+    /// peaklet of the splitting diagram.
+    fn draw_lineage_contributions(&self, plot_ui: &mut PlotUi, pinned: (usize, usize)) {
+        let full_cascade = self.peak.build_multiplet_cascade();
+        let partial_cascade = &self.cached_partial_cascade;
+        let stage = partial_cascade.child_stages_count();
+        let fwhm = peak::j_to_ppm(self.display_fwhm(), self.field_strength);
+        for (i, peaklet) in partial_cascade.final_peaklets().iter().enumerate() {
+            if !full_cascade.is_in_lineage(pinned, (stage, i)) {
+                continue;
+            }
+            let distribution = partial_cascade.lineshape().with_fwhm_shape_normalized(
+                peak::j_to_ppm(peaklet.δ, self.field_strength),
+                fwhm,
+                partial_cascade.skew(),
+                partial_cascade.mixing(),
+                peaklet.integration,
+            );
+            plot_ui.line(
+                Line::new(PlotPoints::from_explicit_callback(
+                    move |x| distribution.evaluate(x),
+                    distribution.extent_by_fwhm(Self::PEAKLET_WIDTH),
+                    Self::SAMPLES / 10,
+                ))
+                .color(palette::LINEAGE_COLOR)
+                .width(2.)
+                .name("Pinned lineage"),
+            );
+        }
+    }
+
+    /// The FWHM (Hz) with which lines are drawn, which differs from that of the peak under
+    /// resolution enhancement.
+    fn display_fwhm(&self) -> f64 {
+        self.peak.fwhm * self.resolution_enhancement
+    }
+
+    fn is_resolution_enhanced(&self) -> bool {
+        self.resolution_enhancement < 1.
+    }
+
+    fn is_density_strip_shown(&self) -> bool {
+        match self.density_strip {
+            DensityStrip::Auto => self.peak.total_peaklet_count() > Self::TOO_COMPLEX_THRESHOLD,
+            DensityStrip::On => true,
+            DensityStrip::Off => false,
+        }
+    }
+
+    /// Draw the waveform as a 1D heatmap strip along the bottom of the plot, with opacity
+    /// proportional to intensity.
+    fn draw_density_strip(
+        plot_ui: &mut PlotUi,
+        waveform: &DistributionSum<PeakGeometry>,
+        color: Color32,
+    ) {
+        let bounds = plot_ui.plot_bounds();
+        let [x_min, y_min] = bounds.min();
+        let [x_max, y_max] = bounds.max();
+        let bin_width = (x_max - x_min) / Self::DENSITY_STRIP_BINS as f64;
+        // Fill the space below the baseline if possible.
+        let height = if y_min < 0. {
+            -y_min
+        } else {
+            0.05 * (y_max - y_min)
+        };
+        let max = waveform.max();
+        let bars = (0..Self::DENSITY_STRIP_BINS)
+            .map(|i| {
+                let x = x_min + (i as f64 + 0.5) * bin_width;
+                #[allow(clippy::cast_possible_truncation)]
+                let intensity = (waveform.evaluate(x) / max).clamp(0., 1.) as f32;
+                Bar::new(x, height)
+                    .base_offset(y_min)
+                    .width(bin_width)
+                    .stroke(Stroke::NONE)
+                    .fill(color.linear_multiply(intensity))
+            })
+            .collect();
+        plot_ui.bar_chart(BarChart::new(bars).color(color).name("Density"));
     }
 
-    fn splitting_diagram(&self, ui: &mut Ui) {
+    fn splitting_diagram(&mut self, ui: &mut Ui) {
         let plot = utils::make_noninteractable_plot("splitting_diagram")
             .show_axes([false; 2])
             .show_background(false)
@@ -504,14 +3723,65 @@ impl Protonolysis {
             )
             .data_aspect(15.);
 
-        plot.show(ui, |plot_ui| {
-            splitting_diagram::draw_splitting_diagram(
-                plot_ui,
-                &self.peak.build_multiplet_cascade(),
-                &self.cached_partial_cascade,
-                FractionalStageIndex::new(*self.view_stage),
-            );
-        });
+        let full_cascade = self.peak.build_multiplet_cascade();
+        // The configuration may have changed since the peaklet was pinned.
+        self.pinned_peaklet = self
+            .pinned_peaklet
+            .filter(|&pinned| full_cascade.contains_peaklet(pinned));
+
+        // The diagram is drawn in Hz, relative to the center of the peak, unless it is rescaled per
+        // stage.
+        let to_scale = self.diagram_options.scale.is_to_scale();
+        let (hovered, clicked) = plot
+            .show(ui, |plot_ui| {
+                splitting_diagram::draw_splitting_diagram(
+                    plot_ui,
+                    &full_cascade,
+                    &self.cached_partial_cascade,
+                    FractionalStageIndex::new(*self.view_stage),
+                    self.pinned_peaklet,
+                    self.diagram_options,
+                );
+                // Positions are only meaningful in Hz when the diagram is to scale.
+                if to_scale {
+                    utils::draw_crosshair(plot_ui, self.crosshair.map(|x| x * self.field_strength));
+                }
+                let clicked = plot_ui
+                    .plot_clicked()
+                    .then(|| plot_ui.pointer_coordinate())
+                    .flatten();
+                (utils::hovered_x(plot_ui).filter(|_| to_scale), clicked)
+            })
+            .inner;
+        if let Some(hz) = hovered {
+            self.next_crosshair = Some(peak::j_to_ppm(hz, self.field_strength));
+        }
+        if let Some(point) = clicked {
+            // Clicking the pinned peaklet again, or away from any peaklet, unpins.
+            let peaklet =
+                splitting_diagram::peaklet_at(&full_cascade, self.diagram_options.scale, point);
+            self.pinned_peaklet = if peaklet == self.pinned_peaklet {
+                None
+            } else {
+                peaklet
+            };
+        }
+
+        let final_stage = full_cascade.child_stages_count();
+        let pinned_final = self
+            .pinned_peaklet
+            .filter(|&(stage, _)| stage == final_stage);
+        if let Some((_, index)) = pinned_final {
+            let path = full_cascade.peaklet_path(index).unwrap();
+            let steps = path
+                .peaklets
+                .iter()
+                .skip(1)
+                .zip(&path.js)
+                .map(|(peaklet, j)| format!("{:+.1} Hz (J = {j:.1} Hz)", peaklet.δ))
+                .join(" → ");
+            ui.label(format!("Lineage: 0 Hz → {steps}"));
+        }
     }
 
     fn side_panel_contents(&mut self, ui: &mut Ui) {
@@ -519,17 +3789,155 @@ impl Protonolysis {
 
         ui.separator();
 
+        if self.show_coupling_arcs {
+            ui.label("Coupling network:");
+            coupling_arcs::draw_coupling_arcs(ui, &self.peak);
+            ui.separator();
+        }
+
+        if self.show_resolved_couplings {
+            // The table sits below the diagram, and so must reserve its space before the diagram
+            // fills the panel.
+            utils::inner_bottom_panel("resolved_couplings", ui, |ui| {
+                self.resolved_couplings(ui);
+            });
+        }
+
         if self.show_splitting_diagram {
-            ui.label("Splitting diagram:");
+            ui.horizontal(|ui| {
+                ui.label("Splitting diagram:").on_hover_text(
+                    "Click a line to highlight its lineage, here and among the individual \
+                        contributions to the spectrum",
+                );
+                ui.separator();
+                ui.label("Connectors:");
+                for style in ConnectorStyle::ALL {
+                    ui.selectable_value(
+                        &mut self.diagram_options.connector_style,
+                        style,
+                        style.name(),
+                    );
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Spacing:");
+                for scale in DiagramScale::ALL {
+                    ui.selectable_value(&mut self.diagram_options.scale, scale, scale.name());
+                }
+                if !self.diagram_options.scale.is_to_scale() {
+                    ui.label(RichText::new("Not to scale").italics())
+                        .on_hover_text(
+                            "The spread of each level is rescaled to separate its groups; \
+                                the order of lines within each group is preserved",
+                        );
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Opacity:");
+                ui.label("markers");
+                ui.add(
+                    Slider::new(&mut self.diagram_options.marker_opacity, 0.1..=1.0)
+                        .fixed_decimals(1),
+                );
+                ui.label("connectors");
+                ui.add(
+                    Slider::new(&mut self.diagram_options.connector_opacity, 0.1..=1.0)
+                        .fixed_decimals(1),
+                );
+            });
             self.splitting_diagram(ui);
         }
     }
 
-    fn footer(ui: &mut Ui) {
+    /// The sequence of names of the patterns observed as splittings are applied, with that of the
+    /// last whole level reached highlighted.
+    fn stage_breadcrumb(&self, ui: &mut Ui) {
+        let current = FractionalStageIndex::new(*self.view_stage).full();
+        ui.horizontal_wrapped(|ui| {
+            ui.spacing_mut().item_spacing.x = 0.;
+            for (i, name) in self
+                .peak
+                .stage_names(self.pattern_naming)
+                .into_iter()
+                .enumerate()
+            {
+                if i > 0 {
+                    ui.weak(" → ");
+                }
+                if i == current {
+                    ui.label(RichText::new(name).strong().color(palette::peak_color(0)));
+                } else {
+                    ui.weak(name);
+                }
+            }
+        });
+    }
+
+    /// Tabulate, for each coupling, whether the splitting it introduces is resolved at the current
+    /// field strength and FWHM, both within each group of lines it splits and among all lines of
+    /// its stage.
+    fn resolved_couplings(&self, ui: &mut Ui) {
+        ui.label(format!(
+            "Couplings resolved at {} MHz, FWHM {:.2} Hz:",
+            self.field_strength,
+            self.display_fwhm(),
+        ));
+        let cascade = self.peak.build_multiplet_cascade();
+        let row_height = ui.text_style_height(&TextStyle::Body);
+        TableBuilder::new(ui)
+            .striped(true)
+            .cell_layout(Layout::left_to_right(Align::Center))
+            .columns(Column::auto_with_initial_suggestion(60.), 4)
+            .header(row_height, |mut header| {
+                let mut col = |text: &str| {
+                    header
+                        .col(|ui| {
+                            ui.label(RichText::new(text).underline());
+                        })
+                        .1
+                };
+                col("Coupled to");
+                col("J (Hz)");
+                col("Splitting").on_hover_text(
+                    "Whether the lines split from each parent line are resolved from each other",
+                );
+                col("Stage").on_hover_text(
+                    "Whether every line of the stage is also resolved from those split from other \
+                        parent lines",
+                );
+            })
+            .body(|body| {
+                let splitters = self.peak.active_splitters().collect_vec();
+                body.rows(row_height, splitters.len(), |i, mut row| {
+                    let splitter = splitters[i];
+                    let verdict = |resolved| if resolved { "✓" } else { "✗" };
+                    row.col(|ui| {
+                        ui.label(format!("{}H", splitter.n));
+                    });
+                    row.col(|ui| {
+                        ui.monospace(format!("{:.1}", splitter.j));
+                    });
+                    // Note that the stages include the base peaklet.
+                    row.col(|ui| {
+                        ui.label(verdict(cascade.is_stage_resolved(i + 1)));
+                    });
+                    row.col(|ui| {
+                        ui.label(verdict(cascade.is_stage_fully_resolved(i + 1)));
+                    });
+                });
+            });
+    }
+
+    fn footer(&mut self, ui: &mut Ui) {
         utils::inner_bottom_panel("about_footer", ui, |ui| {
             // Right-alignment disabled due to `exact_width` bug.
             // ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
             ui.horizontal(|ui| {
+                ui.menu_button("Learn", |ui| {
+                    ui.checkbox(&mut self.show_lineshape_gallery, "Lineshape gallery");
+                    ui.checkbox(&mut self.show_shortcuts, "Keyboard shortcuts");
+                });
+                ui.separator();
                 ui.hyperlink_to("Source", env!("CARGO_PKG_REPOSITORY"));
                 ui.separator();
                 ui.label(concat!(app_name!(), " v", version!()));
@@ -543,7 +3951,7 @@ impl Protonolysis {
             side_panel = side_panel.exact_width(width.max(ctx.available_rect().width() * 0.25));
         }
         let response = side_panel.show(ctx, |ui| {
-            Self::footer(ui);
+            self.footer(ui);
             self.side_panel_contents(ui);
         });
         // Note that the table contained within does sizing on the first frame. Thus we take the
@@ -575,18 +3983,119 @@ impl Protonolysis {
                     ui.set_height(ctx.screen_rect().width() * 0.8);
                     self.peak_viewer(ui);
                 });
-                Self::footer(ui);
+                self.footer(ui);
             });
         });
     }
+
+    /// A distraction-free layout for projecting: only the (enlarged) spectrum, optionally
+    /// accompanied by the splitting diagram.
+    fn presentation_layout(&mut self, ctx: &Context) {
+        // The controls, which normally drive the animation, are hidden.
+        self.view_stage.tick(ctx);
+        self.tick_fwhm_sweep(ctx);
+        self.update_partial_cascade();
+
+        if self.presentation_show_diagram {
+            SidePanel::right("presentation_diagram")
+                .resizable(false)
+                .exact_width(ctx.available_rect().width() * 0.3)
+                .show(ctx, |ui| {
+                    ui.with_layout(Layout::top_down(Align::Center), |ui| {
+                        ui.add_space(ui.available_height() * 0.1);
+                        self.splitting_diagram(ui);
+                    });
+                });
+        }
+        CentralPanel::default().show(ctx, |ui| {
+            utils::scale_text_styles(ui, Self::PRESENTATION_TEXT_SCALE);
+            self.peak_viewer(ui);
+        });
+    }
+
+    /// Half-width of the default window of the viewer, in ppm. This covers the same range in Hz at
+    /// any field strength.
+    fn default_x(&self) -> f64 {
+        peak::rescale_ppm(Self::DEFAULT_X, Self::DEFAULT_X_FIELD, self.field_strength)
+    }
+
+    /// Rescale the window of the viewer when the field strength changes, such that the multiplet
+    /// occupies the same portion of it.
+    fn follow_field_strength(&mut self, ctx: &Context) {
+        if approx::relative_eq!(self.windowed_field_strength, self.field_strength) {
+            return;
+        }
+        let (x_min, x_max) = self.requested_x_axis.unwrap_or(self.linked_x_axis);
+        let rescale =
+            |ppm| peak::rescale_ppm(ppm, self.windowed_field_strength, self.field_strength);
+        self.requested_x_axis = Some((rescale(x_min), rescale(x_max)));
+        self.windowed_field_strength = self.field_strength;
+        ctx.request_repaint();
+    }
+
+    fn handle_hotkeys(&mut self, ctx: &Context) {
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        for action in shortcuts::pressed(ctx) {
+            match action {
+                Action::ToggleHelp => self.show_shortcuts = !self.show_shortcuts,
+                Action::Dismiss => {
+                    if self.show_shortcuts {
+                        self.show_shortcuts = false;
+                    } else {
+                        self.presentation_mode = false;
+                    }
+                }
+                Action::TogglePresentation => self.presentation_mode = !self.presentation_mode,
+                // Step through whole levels; a running animation has precedence.
+                Action::StepBack | Action::StepForward if !self.view_stage.is_animating() => {
+                    let step = if action == Action::StepBack { -1. } else { 1. };
+                    self.view_stage
+                        .set_value_clamping(self.view_stage.round() + step);
+                }
+                Action::StepBack | Action::StepForward => {}
+                Action::ToggleSampleOverlay => {
+                    self.show_sample_overlay = !self.show_sample_overlay;
+                }
+            }
+        }
+    }
 }
 
 impl eframe::App for Protonolysis {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, Self::FIELD_STRENGTH_KEY, &self.field_strength);
+        eframe::set_value(storage, Self::PEAK_KEY, &self.resting_peak());
+        eframe::set_value(storage, Self::DIAGRAM_OPTIONS_KEY, &self.diagram_options);
+        eframe::set_value(storage, Self::DEFAULT_FWHM_KEY, &self.default_fwhm);
+        eframe::set_value(
+            storage,
+            Self::INTEGRAL_PLACEMENT_KEY,
+            &self.integral_placement,
+        );
+        eframe::set_value(storage, Self::SPECTRUM_FILL_KEY, &self.spectrum_fill);
+        eframe::set_value(storage, Self::OVERLAY_FILL_KEY, &self.overlay_fill);
+    }
+
     fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
-        if ctx.screen_rect().width() < 850. {
+        self.crosshair = self.next_crosshair.take();
+        self.handle_hotkeys(ctx);
+        self.load_dropped_session(ctx);
+        if self.presentation_mode {
+            self.presentation_layout(ctx);
+        } else if ctx.screen_rect().width() < 850. {
             self.compressed_layout(ctx);
         } else {
             self.full_layout(ctx);
         }
+        shortcuts::show_help(ctx, &mut self.show_shortcuts);
+        lineshape_gallery::show(ctx, &mut self.show_lineshape_gallery);
+        self.toasts.show(ctx);
+        self.follow_field_strength(ctx);
+        // Plots record the hovered position for display on the next frame.
+        if self.next_crosshair != self.crosshair {
+            ctx.request_repaint();
+        }
     }
 }
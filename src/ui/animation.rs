@@ -1,8 +1,9 @@
 use std::ops::{Deref, RangeInclusive};
+use std::time::Duration;
 
-use eframe::egui::Ui;
+use eframe::egui::Context;
 
-use crate::numerics;
+use crate::numerics::Easing;
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 enum AnimationDirection {
@@ -10,13 +11,48 @@ enum AnimationDirection {
     Reverse,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+/// The source of the time step by which [`CyclicallyAnimatedF64::tick`] advances.
+pub(super) enum AnimationClock {
+    /// The duration of the previous frame, such that the animation keeps to wall-clock time.
+    #[default]
+    WallClock,
+    /// A constant step per frame, such that the animation passes through the same values however
+    /// irregularly the frames are drawn.
+    FixedStep,
+}
+
+impl AnimationClock {
+    pub(super) const ALL: [Self; 2] = [Self::WallClock, Self::FixedStep];
+    /// That of a frame at 60 fps.
+    const FIXED_STEP: f64 = 1. / 60.;
+
+    pub(super) fn name(self) -> &'static str {
+        match self {
+            Self::WallClock => "Wall clock",
+            Self::FixedStep => "Fixed step",
+        }
+    }
+
+    fn dt(self, ctx: &Context) -> f64 {
+        match self {
+            Self::WallClock => ctx.input(|i| f64::from(i.stable_dt)).min(0.1),
+            Self::FixedStep => Self::FIXED_STEP,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(super) struct CyclicallyAnimatedF64 {
     value: f64,
     range: (f64, f64),
     duration: f64,
     direction: AnimationDirection,
+    easing: Easing,
+    clock: AnimationClock,
     anim_factor: Option<f64>,
+    /// If set, the animation is a single forward sweep of this duration which stops at the end.
+    once_duration: Option<f64>,
 }
 
 impl AnimationDirection {
@@ -37,13 +73,18 @@ impl Deref for CyclicallyAnimatedF64 {
 }
 
 impl CyclicallyAnimatedF64 {
+    const UNFOCUSED_FRAME_TIME: Duration = Duration::from_millis(100);
+
     pub(super) fn new(value: f64, range: RangeInclusive<f64>, duration: f64) -> Self {
         let mut ret = Self {
             value,
             range: range.into_inner(),
             duration,
             direction: AnimationDirection::Forward,
+            easing: Easing::default(),
+            clock: AnimationClock::default(),
             anim_factor: None,
+            once_duration: None,
         };
         ret.set_value_clamping(value);
         ret
@@ -57,6 +98,36 @@ impl CyclicallyAnimatedF64 {
         self.duration = duration;
     }
 
+    pub(super) fn easing(&self) -> Easing {
+        self.easing
+    }
+
+    /// Change the easing curve, re-seeding any ongoing animation so that it continues from the
+    /// current value.
+    pub(super) fn set_easing(&mut self, easing: Easing) {
+        if easing == self.easing {
+            return;
+        }
+        self.easing = easing;
+        if self.is_animating() {
+            self.anim_factor = Some(self.seed_factor());
+        }
+    }
+
+    pub(super) fn clock(&self) -> AnimationClock {
+        self.clock
+    }
+
+    pub(super) fn set_clock(&mut self, clock: AnimationClock) {
+        self.clock = clock;
+    }
+
+    fn seed_factor(&self) -> f64 {
+        // N.b. a degenerate range yields NaN, which the easing maps to zero.
+        self.easing
+            .inverse((self.value - self.range.0) / (self.range.1 - self.range.0))
+    }
+
     fn set_value_inner(&mut self, value: f64) {
         self.value = value.clamp(self.range.0, self.range.1);
     }
@@ -73,14 +144,22 @@ impl CyclicallyAnimatedF64 {
 
     pub(super) fn start_animating(&mut self) {
         if self.anim_factor.is_none() {
-            self.anim_factor = Some(numerics::ease_transition_inverse(
-                (self.value - self.range.0) / (self.range.1 - self.range.0),
-            ));
+            self.anim_factor = Some(self.seed_factor());
         }
     }
 
     pub(super) fn stop_animating(&mut self) {
         self.anim_factor = None;
+        self.once_duration = None;
+    }
+
+    /// Restart from the beginning of the range and sweep forward once over `duration`, holding
+    /// exactly at the end of the range.
+    pub(super) fn play_once(&mut self, duration: f64) {
+        self.value = self.range.0;
+        self.direction = AnimationDirection::Forward;
+        self.anim_factor = Some(0.);
+        self.once_duration = Some(duration);
     }
 
     pub(super) fn is_animating(&self) -> bool {
@@ -95,27 +174,143 @@ impl CyclicallyAnimatedF64 {
         }
     }
 
-    pub(super) fn tick(&mut self, ui: &mut Ui) {
-        let Some(factor) = &mut self.anim_factor else {
+    pub(super) fn tick(&mut self, ctx: &Context) {
+        if !self.advance(self.clock.dt(ctx)) {
             return;
+        }
+
+        // Only animations drive continuous repaints; idle frames are otherwise event-driven. When
+        // the window is in the background, throttle to a low frame rate.
+        if ctx.input(|i| i.focused) {
+            ctx.request_repaint();
+        } else {
+            ctx.request_repaint_after(Self::UNFOCUSED_FRAME_TIME);
+        }
+    }
+
+    /// Advance the animation, if any, by `dt` seconds, returning whether it is still ongoing.
+    fn advance(&mut self, dt: f64) -> bool {
+        let Some(factor) = &mut self.anim_factor else {
+            return false;
         };
 
-        let dt = ui.ctx().input(|i| f64::from(i.stable_dt)).min(0.1)
+        let dt = dt
             * match self.direction {
                 AnimationDirection::Forward => 1.0,
                 AnimationDirection::Reverse => -1.0,
             };
-        *factor += dt / self.duration;
+        *factor += dt / self.once_duration.unwrap_or(self.duration);
         let reached_end = !(0.0..=1.0).contains(factor);
         *factor = factor.clamp(0.0, 1.0);
 
-        let new_normalized = numerics::ease_transition(*factor);
+        let new_normalized = self.easing.ease(*factor);
         self.value = new_normalized * (self.range.1 - self.range.0) + self.range.0;
 
         if reached_end {
+            if self.once_duration.is_some() {
+                self.value = self.range.1;
+                self.stop_animating();
+                return false;
+            }
             self.direction.flip();
         }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnimationDirection, CyclicallyAnimatedF64};
+    use crate::numerics::Easing;
+
+    fn linear(value: f64, range: std::ops::RangeInclusive<f64>) -> CyclicallyAnimatedF64 {
+        let mut animated = CyclicallyAnimatedF64::new(value, range, 1.);
+        animated.set_easing(Easing::Linear);
+        animated
+    }
+
+    #[test]
+    fn animation_cycles_between_endpoints() {
+        let mut animated = linear(0., 0.0..=2.0);
+        assert!(!animated.advance(0.5));
+        approx::assert_abs_diff_eq!(*animated, 0.);
+
+        animated.start_animating();
+        let mut trajectory = vec![];
+        for _ in 0..12 {
+            assert!(animated.advance(0.25));
+            trajectory.push(*animated);
+        }
+        // Landing exactly on an endpoint holds there for a step, as the overshoot of the next step
+        // is clamped rather than reflected.
+        approx::assert_abs_diff_eq!(
+            &trajectory[..],
+            &[0.5, 1., 1.5, 2., 2., 1.5, 1., 0.5, 0., 0., 0.5, 1.][..],
+            epsilon = 1e-12
+        );
+
+        // Overshooting an endpoint holds there, then reverses.
+        let mut animated = linear(1.5, 0.0..=2.0);
+        animated.start_animating();
+        animated.advance(10.);
+        approx::assert_abs_diff_eq!(*animated, 2.);
+        assert_eq!(animated.direction, AnimationDirection::Reverse);
+        animated.advance(0.25);
+        approx::assert_abs_diff_eq!(*animated, 1.5, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn animation_resumes_from_current_value() {
+        for easing in Easing::ALL {
+            let mut animated = CyclicallyAnimatedF64::new(0.3, 0.0..=1.0, 1.);
+            animated.set_easing(easing);
+            animated.start_animating();
+            animated.advance(0.);
+            approx::assert_relative_eq!(*animated, 0.3, max_relative = 1e-9);
+
+            // Changing the easing mid-animation does not jump.
+            animated.advance(0.1);
+            let before = *animated;
+            let next =
+                Easing::ALL[(Easing::ALL.iter().position(|&e| e == easing).unwrap() + 1) % 3];
+            animated.set_easing(next);
+            animated.advance(0.);
+            approx::assert_relative_eq!(*animated, before, max_relative = 1e-9);
+        }
+    }
+
+    #[test]
+    fn animation_clamps_to_range() {
+        let mut animated = linear(5., 0.0..=2.0);
+        approx::assert_abs_diff_eq!(*animated, 2.);
+        animated.set_range_clamping(0.0..=1.0);
+        approx::assert_abs_diff_eq!(*animated, 1.);
+        animated.set_value_clamping(-1.);
+        approx::assert_abs_diff_eq!(*animated, 0.);
+
+        // A degenerate range stays put without producing NaN.
+        let mut degenerate = CyclicallyAnimatedF64::new(1., 1.0..=1.0, 1.);
+        degenerate.start_animating();
+        for _ in 0..10 {
+            degenerate.advance(0.3);
+            approx::assert_abs_diff_eq!(*degenerate, 1.);
+        }
+    }
 
-        ui.ctx().request_repaint();
+    #[test]
+    fn play_once_ends_exactly() {
+        let mut animated = linear(0.7, 0.0..=3.0);
+        animated.play_once(2.);
+        approx::assert_abs_diff_eq!(*animated, 0.);
+        assert!(animated.advance(1.));
+        approx::assert_abs_diff_eq!(*animated, 1.5, epsilon = 1e-12);
+        assert!(!animated.advance(1.3));
+        assert!(!animated.is_animating());
+        approx::assert_abs_diff_eq!(*animated, 3.);
+        // Subsequent cycles use the usual duration.
+        animated.set_value_clamping(0.);
+        animated.start_animating();
+        animated.advance(0.5);
+        approx::assert_abs_diff_eq!(*animated, 1.5, epsilon = 1e-12);
     }
 }
@@ -0,0 +1,93 @@
+use std::f32::consts::PI;
+
+use eframe::egui::{Align2, Sense, TextStyle, Ui};
+use eframe::epaint::{Color32, Pos2, QuadraticBezierShape, Stroke, Vec2};
+use itertools::Itertools;
+
+use super::palette;
+use crate::peak::Peak;
+
+const HEIGHT: f32 = 180.;
+const NODE_RADIUS: f32 = 14.;
+/// Angular extent of the partners on the right of the circle, as a fraction of a half-turn.
+const PARTNER_SPREAD: f32 = 0.8;
+/// Chord width per Hz of coupling, and its bounds.
+const WIDTH_PER_HZ: f32 = 0.3;
+const MIN_CHORD_WIDTH: f32 = 1.;
+const MAX_CHORD_WIDTH: f32 = 5.;
+
+/// Draw the coupling network of `peak` as a chord diagram: the observed protons sit on the left
+/// of a circle, and each group of coupled protons is placed around its right, connected to the
+/// former by a chord labeled with the coupling constant. Stronger couplings have thicker chords.
+pub(super) fn draw_coupling_arcs(ui: &mut Ui, peak: &Peak) {
+    let splitters = peak.active_splitters().collect_vec();
+    if splitters.is_empty() {
+        ui.label("The observed protons are not coupled to any others.");
+        return;
+    }
+
+    let (response, painter) =
+        ui.allocate_painter(Vec2::new(ui.available_width(), HEIGHT), Sense::hover());
+    let rect = response.rect;
+    let center = rect.center();
+    let radius = 0.5 * rect.width().min(rect.height()) - NODE_RADIUS - 2.;
+    let on_circle = |angle: f32| center + radius * Vec2::angled(angle);
+
+    let font = TextStyle::Body.resolve(ui.style());
+    let text_color = ui.visuals().text_color();
+    let observed = on_circle(PI);
+
+    let count = splitters.len();
+    for (i, splitter) in splitters.into_iter().enumerate() {
+        // Angles are measured clockwise from the right, as the y-axis points down.
+        let angle = if count == 1 {
+            0.
+        } else {
+            PI * PARTNER_SPREAD * (i as f32 / (count - 1) as f32 - 0.5)
+        };
+        let partner = on_circle(angle);
+
+        #[allow(clippy::cast_possible_truncation)]
+        let width = (splitter.j as f32 * WIDTH_PER_HZ).clamp(MIN_CHORD_WIDTH, MAX_CHORD_WIDTH);
+        let stroke = Stroke::new(width, text_color.linear_multiply(0.6));
+        painter.add(QuadraticBezierShape::from_points_stroke(
+            [observed, center, partner],
+            false,
+            Color32::TRANSPARENT,
+            stroke,
+        ));
+        // The midpoint of the chord.
+        let midpoint = Pos2::new(
+            0.25 * observed.x + 0.5 * center.x + 0.25 * partner.x,
+            0.25 * observed.y + 0.5 * center.y + 0.25 * partner.y,
+        );
+        painter.text(
+            midpoint,
+            Align2::CENTER_BOTTOM,
+            format!("{:.1} Hz", splitter.j),
+            font.clone(),
+            text_color,
+        );
+
+        painter.circle_filled(partner, NODE_RADIUS, ui.visuals().extreme_bg_color);
+        painter.circle_stroke(partner, NODE_RADIUS, Stroke::new(1., text_color));
+        painter.text(
+            partner,
+            Align2::CENTER_CENTER,
+            format!("{}H", splitter.n),
+            font.clone(),
+            text_color,
+        );
+    }
+
+    let observed_color = palette::peak_color(0);
+    painter.circle_filled(observed, NODE_RADIUS, ui.visuals().extreme_bg_color);
+    painter.circle_stroke(observed, NODE_RADIUS, Stroke::new(2., observed_color));
+    painter.text(
+        observed,
+        Align2::CENTER_CENTER,
+        format!("{}H", peak.proton_count),
+        font,
+        observed_color,
+    );
+}
@@ -0,0 +1,80 @@
+use eframe::egui::plot::{Legend, Line, LineStyle, PlotPoints};
+use eframe::egui::{self, Align2, Context, Ui};
+use eframe::epaint::Vec2;
+
+use super::{palette, utils};
+use crate::numerics::distribution::distribution_sum::DistributionSum;
+use crate::numerics::distribution::lineshape::Lineshape;
+
+/// Half of the range shown, in FWHMs.
+const HALF_RANGE: f64 = 4.;
+const SAMPLES: usize = 400;
+
+/// How to tell each lineshape apart by eye.
+fn hint(lineshape: Lineshape) -> &'static str {
+    match lineshape {
+        Lineshape::Lorentzian => "Sharp tip, with broad wings that persist far from the center",
+        Lineshape::Gaussian => "Rounded shoulders, with wings that vanish quickly",
+        Lineshape::PseudoVoigt => "Between the two: a Gaussian core, with Lorentzian wings",
+        Lineshape::ExponentiallyModifiedGaussian => {
+            "Leans to one side, with a long tail; a sign of poor shimming"
+        }
+    }
+}
+
+/// A window comparing every [`Lineshape`] drawn as a single line of the same FWHM and area, such
+/// that they can be learned to be recognized.
+pub(super) fn show(ctx: &Context, open: &mut bool) {
+    egui::Window::new("Lineshape gallery")
+        .open(open)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, [0., 0.])
+        .show(ctx, |ui| {
+            ui.label("A single line of each shape, all of the same width at half height and area.");
+            utils::vertical_space(ui);
+            gallery_plot(ui);
+            utils::vertical_space(ui);
+            utils::two_column_grid("lineshape_hints", ui, |ui: &mut Ui| {
+                for (i, lineshape) in Lineshape::ALL.into_iter().enumerate() {
+                    ui.colored_label(palette::peak_color(i), lineshape.name());
+                    ui.label(hint(lineshape));
+                    ui.end_row();
+                }
+            });
+        });
+}
+
+fn gallery_plot(ui: &mut Ui) {
+    utils::make_noninteractable_plot("lineshape_gallery")
+        .show_axes([true, false])
+        .legend(Legend::default())
+        .min_size(Vec2::new(360., 200.))
+        .view_aspect(1.8)
+        .show(ui, |plot_ui| {
+            for (i, lineshape) in Lineshape::ALL.into_iter().enumerate() {
+                let line: DistributionSum<_> =
+                    std::iter::once(lineshape.with_fwhm_normalized(0., 1., 1.)).collect();
+                let points = line
+                    .adaptive_sample_grid(-HALF_RANGE..=HALF_RANGE, SAMPLES)
+                    .into_iter()
+                    .map(|x| [x, line.evaluate(x)])
+                    .collect::<PlotPoints>();
+                plot_ui.line(
+                    Line::new(points)
+                        .color(palette::peak_color(i))
+                        .name(lineshape.name()),
+                );
+                // Mark the width at half height. That of a skewed line is of its Gaussian alone, so
+                // its apparent width is larger.
+                if lineshape.is_symmetric() {
+                    let half_height = 0.5 * line.evaluate(0.);
+                    plot_ui.line(
+                        Line::new(vec![[-0.5, half_height], [0.5, half_height]])
+                            .color(palette::peak_color(i))
+                            .style(LineStyle::dotted_dense()),
+                    );
+                }
+            }
+        });
+}
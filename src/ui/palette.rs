@@ -0,0 +1,28 @@
+use eframe::epaint::Color32;
+
+/// The colorblind-safe palette of Okabe and Ito (2008), less black (which is illegible against
+/// the dark theme). Orange is placed last, as it is also used to flag overlapping peaklets.
+const PEAK_COLORS: [Color32; 7] = [
+    Color32::from_rgb(240, 228, 66),  // Yellow.
+    Color32::from_rgb(86, 180, 233),  // Sky blue.
+    Color32::from_rgb(213, 94, 0),    // Vermillion.
+    Color32::from_rgb(0, 158, 115),   // Bluish green.
+    Color32::from_rgb(204, 121, 167), // Reddish purple.
+    Color32::from_rgb(0, 114, 178),   // Blue.
+    Color32::from_rgb(230, 159, 0),   // Orange.
+];
+
+/// Highlights the lineage of a pinned peaklet. This is a color not used for the first few peaks.
+pub(super) const LINEAGE_COLOR: Color32 = PEAK_COLORS[4];
+
+/// The color with which to draw the `index`-th peak in every view. Colors repeat only after
+/// [`PEAK_COLORS`] is exhausted.
+pub(super) fn peak_color(index: usize) -> Color32 {
+    PEAK_COLORS[index % PEAK_COLORS.len()]
+}
+
+/// Marks the `index`-th group of splitters sharing a coupling constant. Orange is excluded, lest
+/// the group be mistaken for overlapping lines.
+pub(super) fn equal_j_color(index: usize) -> Color32 {
+    PEAK_COLORS[index % (PEAK_COLORS.len() - 1)]
+}
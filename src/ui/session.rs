@@ -0,0 +1,522 @@
+use std::ops::RangeInclusive;
+
+use eframe::egui::Context;
+use serde::{Deserialize, Serialize};
+
+use super::splitting_diagram::DiagramOptions;
+use super::{
+    Background, DensityStrip, FillStyle, IntegralMode, IntegralPlacement, Protonolysis,
+    PEAK_PRESETS,
+};
+use crate::numerics;
+use crate::numerics::fid::Apodization;
+use crate::peak::{self, PatternNaming, Peak, SortOrder};
+
+/// Incremented whenever the meaning of a field of [`Session`] changes. Sessions of other versions
+/// are rejected: none older than the first exist.
+const SESSION_VERSION: u32 = 1;
+
+/// The version of a session that does not record one, which is not a version of the format.
+const fn unversioned() -> u32 {
+    0
+}
+
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+/// The persistent state of the app, _i.e._, the configuration and display settings, but not
+/// transient state such as the progress of an animation.
+pub(super) struct Session {
+    #[serde(default = "unversioned")]
+    version: u32,
+    field_strength: f64,
+    default_fwhm: f64,
+    broad_lines: bool,
+    selected_preset: String,
+    peak: Peak,
+    view_stage: f64,
+    snap_view_stage: bool,
+    play_once_speed: f64,
+    partner_separation: f64,
+    show_integral: bool,
+    integral_mode: IntegralMode,
+    integral_placement: IntegralPlacement,
+    annotate_integral: bool,
+    show_splitting_diagram: bool,
+    show_resolved_couplings: bool,
+    show_coupling_arcs: bool,
+    show_peaklets: bool,
+    show_sticks: bool,
+    show_merges: bool,
+    show_integration_bars: bool,
+    merge_coincident_lines: bool,
+    highlight_overlaps: bool,
+    density_strip: DensityStrip,
+    spectrum_fill: FillStyle,
+    overlay_fill: FillStyle,
+    diagram_options: DiagramOptions,
+    /// The visible range of the viewer, in ppm.
+    x_window: (f64, f64),
+    background: Background,
+    compare_fwhms: bool,
+    comparison_fwhms: [f64; 2],
+    /// Regions of the spectrum whose integrals are read out, in ppm.
+    integration_regions: Vec<(f64, f64)>,
+    show_plot_background: bool,
+    render_budget_ms: f64,
+    min_display_separation: f32,
+    resolution_enhancement: f64,
+    limit_digital_resolution: bool,
+    points_per_hz: f64,
+    show_processed_spectrum: bool,
+    apodization: Apodization,
+    fixed_x_scale: bool,
+    x_scale: f64,
+    /// The pinned range of intensities, if any.
+    pinned_y_window: Option<(f64, f64)>,
+    sort_order: SortOrder,
+    pattern_naming: PatternNaming,
+    bulk_j: f64,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            version: SESSION_VERSION,
+            field_strength: 600.,
+            default_fwhm: Peak::default().fwhm,
+            broad_lines: false,
+            selected_preset: Protonolysis::DEFAULT_PATTERN.to_owned(),
+            peak: Peak::default(),
+            view_stage: f64::INFINITY,
+            snap_view_stage: false,
+            play_once_speed: 1.,
+            partner_separation: 1.,
+            show_integral: true,
+            integral_mode: IntegralMode::Overlaid,
+            integral_placement: IntegralPlacement::default(),
+            annotate_integral: false,
+            show_splitting_diagram: true,
+            show_resolved_couplings: false,
+            show_coupling_arcs: false,
+            show_peaklets: false,
+            show_sticks: false,
+            show_merges: false,
+            show_integration_bars: false,
+            merge_coincident_lines: true,
+            highlight_overlaps: true,
+            density_strip: DensityStrip::Auto,
+            spectrum_fill: FillStyle::default(),
+            overlay_fill: FillStyle::disabled(),
+            diagram_options: DiagramOptions::default(),
+            x_window: (-Protonolysis::DEFAULT_X, Protonolysis::DEFAULT_X),
+            background: Background::default(),
+            compare_fwhms: false,
+            comparison_fwhms: [0.5, 2.],
+            integration_regions: Vec::new(),
+            show_plot_background: true,
+            render_budget_ms: Protonolysis::DEFAULT_RENDER_BUDGET_MS,
+            min_display_separation: Protonolysis::DEFAULT_MIN_DISPLAY_SEPARATION,
+            resolution_enhancement: 1.,
+            limit_digital_resolution: false,
+            points_per_hz: 0.5,
+            show_processed_spectrum: false,
+            apodization: Apodization::default(),
+            fixed_x_scale: false,
+            x_scale: 2e-4,
+            pinned_y_window: None,
+            sort_order: SortOrder::default(),
+            pattern_naming: PatternNaming::default(),
+            bulk_j: 7.,
+        }
+    }
+}
+
+impl Session {
+    pub(super) fn to_ron(&self) -> String {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .expect("session is serializable")
+    }
+
+    /// Parse a session saved by [`Self::to_ron`]. Settings beyond the range of their controls are
+    /// clamped to it.
+    ///
+    /// # Errors
+    /// If `text` is not a session, was saved by a newer version of the app, or describes a peak
+    /// beyond the limits of the controls.
+    pub(super) fn from_ron(text: &str) -> Result<Self, String> {
+        let mut session = ron::from_str::<Self>(text).map_err(|err| err.to_string())?;
+        match session.version {
+            SESSION_VERSION => {
+                peak::validate_frequency(session.field_strength)
+                    .map_err(|err| format!("invalid field strength: {err}"))?;
+                Protonolysis::check_peak_limits(&mut session.peak)
+                    .map_err(|err| format!("invalid peak: {err}"))?;
+                session.clamp_settings();
+                Ok(session)
+            }
+            0 => Err("the session does not record its format".to_owned()),
+            version if version > SESSION_VERSION => Err(format!(
+                "the session was saved by a newer version of {} (format {version})",
+                app_name!(),
+            )),
+            version => Err(format!("unknown session format {version}")),
+        }
+    }
+
+    /// Bring every setting within the range of its control, replacing those that are not numbers
+    /// with their defaults.
+    fn clamp_settings(&mut self) {
+        fn clamp(value: &mut f64, range: RangeInclusive<f64>, default: f64) {
+            *value = numerics::finite_or(*value, default).clamp(*range.start(), *range.end());
+        }
+
+        let defaults = Self::default();
+        let fwhms = Protonolysis::MIN_FWHM..=Protonolysis::MAX_BROAD_FWHM;
+        clamp(&mut self.default_fwhm, fwhms.clone(), defaults.default_fwhm);
+        self.integral_placement = self.integral_placement.clamped();
+        self.view_stage =
+            Protonolysis::valid_view_stage(self.view_stage).unwrap_or(defaults.view_stage);
+        clamp(
+            &mut self.play_once_speed,
+            Protonolysis::PLAY_ONCE_SPEED_RANGE,
+            defaults.play_once_speed,
+        );
+        clamp(
+            &mut self.partner_separation,
+            Protonolysis::PARTNER_SEPARATION_RANGE,
+            defaults.partner_separation,
+        );
+        let is_window = |(min, max): (f64, f64)| {
+            let bounds = -Protonolysis::MAX_X_WINDOW..=Protonolysis::MAX_X_WINDOW;
+            bounds.contains(&min) && bounds.contains(&max) && min < max
+        };
+        if !is_window(self.x_window) {
+            self.x_window = defaults.x_window;
+        }
+        for (fwhm, default) in self
+            .comparison_fwhms
+            .iter_mut()
+            .zip(defaults.comparison_fwhms)
+        {
+            clamp(fwhm, fwhms.clone(), default);
+        }
+        self.integration_regions
+            .retain(|&(min, max)| min.is_finite() && max.is_finite() && min < max);
+        clamp(
+            &mut self.render_budget_ms,
+            Protonolysis::RENDER_BUDGET_RANGE_MS,
+            defaults.render_budget_ms,
+        );
+        let separations = Protonolysis::MIN_DISPLAY_SEPARATION_RANGE;
+        self.min_display_separation = if self.min_display_separation.is_nan() {
+            defaults.min_display_separation
+        } else {
+            self.min_display_separation
+                .clamp(*separations.start(), *separations.end())
+        };
+        clamp(
+            &mut self.resolution_enhancement,
+            Protonolysis::RESOLUTION_ENHANCEMENT_RANGE,
+            defaults.resolution_enhancement,
+        );
+        clamp(
+            &mut self.points_per_hz,
+            Protonolysis::POINTS_PER_HZ_RANGE,
+            defaults.points_per_hz,
+        );
+        clamp(
+            &mut self.x_scale,
+            Protonolysis::X_SCALE_RANGE,
+            defaults.x_scale,
+        );
+        clamp(&mut self.bulk_j, Protonolysis::J_RANGE, defaults.bulk_j);
+    }
+}
+
+impl Protonolysis {
+    pub(super) fn session(&self) -> Session {
+        Session {
+            version: SESSION_VERSION,
+            field_strength: self.field_strength,
+            default_fwhm: self.default_fwhm,
+            broad_lines: self.broad_lines,
+            selected_preset: self.selected_preset.to_owned(),
+            peak: self.resting_peak(),
+            view_stage: *self.view_stage,
+            snap_view_stage: self.snap_view_stage,
+            play_once_speed: self.play_once_speed,
+            partner_separation: self.partner_separation,
+            show_integral: self.show_integral,
+            integral_mode: self.integral_mode,
+            integral_placement: self.integral_placement,
+            annotate_integral: self.annotate_integral,
+            show_splitting_diagram: self.show_splitting_diagram,
+            show_resolved_couplings: self.show_resolved_couplings,
+            show_coupling_arcs: self.show_coupling_arcs,
+            show_peaklets: self.show_peaklets,
+            show_sticks: self.show_sticks,
+            show_merges: self.show_merges,
+            show_integration_bars: self.show_integration_bars,
+            merge_coincident_lines: self.merge_coincident_lines,
+            highlight_overlaps: self.highlight_overlaps,
+            density_strip: self.density_strip,
+            spectrum_fill: self.spectrum_fill,
+            overlay_fill: self.overlay_fill,
+            diagram_options: self.diagram_options,
+            x_window: self.linked_x_axis,
+            background: self.background,
+            compare_fwhms: self.compare_fwhms,
+            comparison_fwhms: self.comparison_fwhms,
+            integration_regions: self.integration_regions.clone(),
+            show_plot_background: self.show_plot_background,
+            render_budget_ms: self.render_budget_ms,
+            min_display_separation: self.min_display_separation,
+            resolution_enhancement: self.resolution_enhancement,
+            limit_digital_resolution: self.limit_digital_resolution,
+            points_per_hz: self.points_per_hz,
+            show_processed_spectrum: self.show_processed_spectrum,
+            apodization: self.apodization,
+            fixed_x_scale: self.fixed_x_scale,
+            x_scale: self.x_scale,
+            pinned_y_window: self.pinned_y_window,
+            sort_order: self.sort_order,
+            pattern_naming: self.pattern_naming,
+            bulk_j: self.bulk_j,
+        }
+    }
+
+    pub(super) fn restore_session(&mut self, session: Session) {
+        self.field_strength = session.field_strength;
+        self.default_fwhm = session.default_fwhm;
+        self.broad_lines = session.broad_lines;
+        // Presets are keyed by static strings; an unknown preset (e.g., from a newer version)
+        // leaves the selection unchanged.
+        if let Some((&name, _)) = PEAK_PRESETS.get_key_value(session.selected_preset.as_str()) {
+            self.selected_preset = name;
+        }
+        self.fwhm_before_sweep = None;
+        self.fwhm_sweep.stop_animating();
+        self.peak = session.peak;
+        self.update_animation_parameters();
+        self.view_stage.set_value_clamping(session.view_stage);
+        self.snap_view_stage = session.snap_view_stage;
+        self.play_once_speed = session.play_once_speed;
+        self.partner_separation = session.partner_separation;
+        self.show_integral = session.show_integral;
+        self.integral_mode = session.integral_mode;
+        self.integral_placement = session.integral_placement;
+        self.annotate_integral = session.annotate_integral;
+        self.show_splitting_diagram = session.show_splitting_diagram;
+        self.show_resolved_couplings = session.show_resolved_couplings;
+        self.show_coupling_arcs = session.show_coupling_arcs;
+        self.show_peaklets = session.show_peaklets;
+        self.show_sticks = session.show_sticks;
+        self.show_merges = session.show_merges;
+        self.show_integration_bars = session.show_integration_bars;
+        self.merge_coincident_lines = session.merge_coincident_lines;
+        self.highlight_overlaps = session.highlight_overlaps;
+        self.density_strip = session.density_strip;
+        self.spectrum_fill = session.spectrum_fill;
+        self.overlay_fill = session.overlay_fill;
+        self.diagram_options = session.diagram_options;
+        self.requested_x_axis = Some(session.x_window);
+        self.background = session.background;
+        self.compare_fwhms = session.compare_fwhms;
+        self.comparison_fwhms = session.comparison_fwhms;
+        self.integration_regions = session.integration_regions;
+        self.windowed_field_strength = session.field_strength;
+        self.show_plot_background = session.show_plot_background;
+        self.render_budget_ms = session.render_budget_ms;
+        self.min_display_separation = session.min_display_separation;
+        self.resolution_enhancement = session.resolution_enhancement;
+        self.limit_digital_resolution = session.limit_digital_resolution;
+        self.points_per_hz = session.points_per_hz;
+        self.show_processed_spectrum = session.show_processed_spectrum;
+        self.apodization = session.apodization;
+        self.fixed_x_scale = session.fixed_x_scale;
+        self.x_scale = session.x_scale;
+        self.pinned_y_window = session
+            .pinned_y_window
+            .filter(|&(min, max)| min.is_finite() && max.is_finite() && min < max);
+        self.sort_order = session.sort_order;
+        self.pattern_naming = session.pattern_naming;
+        self.bulk_j = session.bulk_j;
+        self.pinned_peaklet = None;
+        self.update_partial_cascade();
+    }
+
+    /// Restore a session from a file dropped onto the window, if any.
+    pub(super) fn load_dropped_session(&mut self, ctx: &Context) {
+        let Some(file) = ctx.input(|i| i.raw.dropped_files.first().cloned()) else {
+            return;
+        };
+        let text = if let Some(bytes) = &file.bytes {
+            String::from_utf8(bytes.to_vec()).map_err(|err| err.to_string())
+        } else if let Some(path) = &file.path {
+            std::fs::read_to_string(path).map_err(|err| err.to_string())
+        } else {
+            Err("the file is empty".to_owned())
+        };
+        let message = match text.and_then(|text| Session::from_ron(&text)) {
+            Ok(session) => {
+                self.restore_session(session);
+                format!("Restored session from {}", file.name)
+            }
+            Err(err) => format!("Failed to restore session: {err}"),
+        };
+        self.toasts.push(ctx, message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IntegralPlacement, Session, SESSION_VERSION};
+    use crate::peak::{Peak, Splitter};
+    use crate::ui::Protonolysis;
+
+    #[test]
+    fn session_round_trip() {
+        let session = Session {
+            field_strength: 80.,
+            peak: Peak {
+                label: "CH₂".to_owned(),
+                proton_count: 2,
+                splitters: vec![Splitter::new(3, 7.1)],
+                ..Default::default()
+            },
+            view_stage: 0.5,
+            ..Default::default()
+        };
+        assert_eq!(Session::from_ron(&session.to_ron()), Ok(session));
+
+        let newer = Session {
+            version: SESSION_VERSION + 1,
+            ..Default::default()
+        };
+        assert!(Session::from_ron(&newer.to_ron()).is_err());
+        assert!(Session::from_ron("not a session").is_err());
+
+        let no_field = Session {
+            field_strength: 0.,
+            ..Default::default()
+        };
+        assert!(Session::from_ron(&no_field.to_ron()).is_err());
+    }
+
+    #[test]
+    fn session_too_many_splitters() {
+        let peak = |count| Peak {
+            splitters: vec![Splitter::new(1, 7.); count],
+            ..Default::default()
+        };
+        let at_limit = Session {
+            peak: peak(Protonolysis::MAX_SPLITTERS),
+            ..Default::default()
+        };
+        assert_eq!(Session::from_ron(&at_limit.to_ron()), Ok(at_limit));
+
+        let over_limit = Session {
+            peak: peak(Protonolysis::MAX_SPLITTERS + 1),
+            ..Default::default()
+        };
+        let err = Session::from_ron(&over_limit.to_ron()).unwrap_err();
+        assert!(err.contains("at most"), "{err}");
+    }
+
+    #[test]
+    fn session_invalid_peak() {
+        let many_neighbors = Session {
+            peak: Peak {
+                splitters: vec![Splitter::new(u32::MAX, 7.)],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(Session::from_ron(&many_neighbors.to_ron()).is_err());
+
+        let no_width = Session {
+            peak: Peak {
+                fwhm: f64::NAN,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let err = Session::from_ron(&no_width.to_ron()).unwrap_err();
+        assert!(err.contains("FWHM"), "{err}");
+
+        for j in [-5., 1e12, f64::NAN] {
+            let wide = Session {
+                peak: Peak {
+                    splitters: vec![Splitter::new(1, j)],
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let err = Session::from_ron(&wide.to_ron()).unwrap_err();
+            assert!(err.contains("coupling constant"), "{err}");
+        }
+        let unobserved = Session {
+            peak: Peak {
+                proton_count: 0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let err = Session::from_ron(&unobserved.to_ron()).unwrap_err();
+        assert!(err.contains("between 1 and"), "{err}");
+
+        // Shape parameters are clamped rather than rejected.
+        let overly_skewed = Session {
+            peak: Peak {
+                skew: 1e9,
+                mixing: -1.,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let parsed = Session::from_ron(&overly_skewed.to_ron()).unwrap();
+        approx::assert_relative_eq!(parsed.peak.skew, Protonolysis::MAX_SKEW);
+        approx::assert_abs_diff_eq!(parsed.peak.mixing, 0.);
+    }
+
+    #[test]
+    fn session_clamped_settings() {
+        let out_of_range = Session {
+            resolution_enhancement: 0.,
+            view_stage: -2.,
+            points_per_hz: f64::NAN,
+            x_window: (0.1, -0.1),
+            x_scale: f64::INFINITY,
+            render_budget_ms: -1.,
+            min_display_separation: f32::NAN,
+            comparison_fwhms: [f64::NAN, 1e6],
+            integration_regions: vec![(0.1, 0.2), (f64::NAN, 0.3), (0.3, 0.2)],
+            integral_placement: IntegralPlacement {
+                height: 50.,
+                offset: 0.9,
+            },
+            ..Default::default()
+        };
+        let parsed = Session::from_ron(&out_of_range.to_ron()).unwrap();
+        let defaults = Session::default();
+        approx::assert_relative_eq!(parsed.resolution_enhancement, 0.1);
+        assert!(parsed.view_stage.is_infinite() && parsed.view_stage > 0.);
+        approx::assert_relative_eq!(parsed.points_per_hz, defaults.points_per_hz);
+        assert_eq!(parsed.x_window, defaults.x_window);
+        approx::assert_relative_eq!(parsed.x_scale, 1e-2);
+        approx::assert_relative_eq!(parsed.render_budget_ms, 4.);
+        approx::assert_relative_eq!(
+            parsed.min_display_separation,
+            defaults.min_display_separation
+        );
+        approx::assert_relative_eq!(parsed.comparison_fwhms[0], defaults.comparison_fwhms[0]);
+        approx::assert_relative_eq!(parsed.comparison_fwhms[1], Protonolysis::MAX_BROAD_FWHM);
+        assert_eq!(parsed.integration_regions, vec![(0.1, 0.2)]);
+        approx::assert_relative_eq!(parsed.integral_placement.height, 0.5);
+        approx::assert_relative_eq!(parsed.integral_placement.offset, 0.5);
+
+        // A file without a version is not taken to be of the current one.
+        assert!(Session::from_ron("(field_strength: 400.)").is_err());
+    }
+}
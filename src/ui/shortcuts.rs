@@ -0,0 +1,173 @@
+use eframe::egui::{
+    self, Align2, Context, Event, InputState, Key, KeyboardShortcut, Modifiers, Ui,
+};
+use itertools::Itertools;
+
+use super::utils;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+/// Something done in response to a [`Shortcut`].
+pub(super) enum Action {
+    ToggleHelp,
+    /// Close the shortcut overlay, or else leave presentation mode.
+    Dismiss,
+    TogglePresentation,
+    StepBack,
+    StepForward,
+    ToggleSampleOverlay,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+/// What the user types to trigger a [`Shortcut`].
+enum Trigger {
+    Key(KeyboardShortcut),
+    /// A typed character, for those without a [`Key`], such as `?`.
+    Text(&'static str),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(super) struct Shortcut {
+    trigger: Trigger,
+    action: Action,
+    category: &'static str,
+    description: &'static str,
+    /// Whether the shortcut is only available in debug builds.
+    debug_only: bool,
+}
+
+impl Shortcut {
+    const fn key(
+        modifiers: Modifiers,
+        key: Key,
+        action: Action,
+        category: &'static str,
+        description: &'static str,
+    ) -> Self {
+        Self {
+            trigger: Trigger::Key(KeyboardShortcut::new(modifiers, key)),
+            action,
+            category,
+            description,
+            debug_only: false,
+        }
+    }
+
+    fn is_available(&self) -> bool {
+        !self.debug_only || cfg!(debug_assertions)
+    }
+
+    fn consume(&self, input: &mut InputState) -> bool {
+        match self.trigger {
+            Trigger::Key(shortcut) => input.consume_shortcut(&shortcut),
+            Trigger::Text(text) => {
+                let count = input.events.len();
+                input
+                    .events
+                    .retain(|event| !matches!(event, Event::Text(typed) if typed == text));
+                input.events.len() < count
+            }
+        }
+    }
+
+    fn format(&self, ctx: &Context) -> String {
+        match self.trigger {
+            Trigger::Key(shortcut) => ctx.format_shortcut(&shortcut),
+            Trigger::Text(text) => text.to_owned(),
+        }
+    }
+}
+
+/// Every keyboard shortcut of the app, in the order in which they are listed by [`show_help`].
+pub(super) const SHORTCUTS: &[Shortcut] = &[
+    Shortcut {
+        trigger: Trigger::Text("?"),
+        action: Action::ToggleHelp,
+        category: "General",
+        description: "Show or hide this list",
+        debug_only: false,
+    },
+    Shortcut::key(
+        Modifiers::NONE,
+        Key::Escape,
+        Action::Dismiss,
+        "General",
+        "Close this list, or leave presentation mode",
+    ),
+    Shortcut::key(
+        Modifiers::NONE,
+        Key::P,
+        Action::TogglePresentation,
+        "Display",
+        "Enter or leave presentation mode",
+    ),
+    Shortcut::key(
+        Modifiers::NONE,
+        Key::ArrowLeft,
+        Action::StepBack,
+        "Splitting level",
+        "Step back one level (when not animating)",
+    ),
+    Shortcut::key(
+        Modifiers::NONE,
+        Key::ArrowRight,
+        Action::StepForward,
+        "Splitting level",
+        "Step forward one level (when not animating)",
+    ),
+    Shortcut {
+        trigger: Trigger::Key(KeyboardShortcut::new(
+            Modifiers::CTRL.plus(Modifiers::SHIFT),
+            Key::D,
+        )),
+        action: Action::ToggleSampleOverlay,
+        category: "Debugging",
+        description: "Mark the points at which the spectrum is sampled",
+        debug_only: true,
+    },
+];
+
+/// The shortcut triggering `action`, for display.
+pub(super) fn format_action(ctx: &Context, action: Action) -> String {
+    SHORTCUTS
+        .iter()
+        .find(|shortcut| shortcut.action == action)
+        .map(|shortcut| shortcut.format(ctx))
+        .unwrap_or_default()
+}
+
+/// Consume the input of every available shortcut which was pressed, returning their actions.
+pub(super) fn pressed(ctx: &Context) -> Vec<Action> {
+    ctx.input_mut(|input| {
+        SHORTCUTS
+            .iter()
+            .filter(|shortcut| shortcut.is_available() && shortcut.consume(input))
+            .map(|shortcut| shortcut.action)
+            .collect()
+    })
+}
+
+/// An overlay listing the available shortcuts, grouped by category.
+pub(super) fn show_help(ctx: &Context, open: &mut bool) {
+    egui::Window::new("Keyboard shortcuts")
+        .open(open)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, [0., 0.])
+        .show(ctx, |ui| {
+            let groups = SHORTCUTS
+                .iter()
+                .filter(|shortcut| shortcut.is_available())
+                .group_by(|shortcut| shortcut.category);
+            for (category, shortcuts) in &groups {
+                ui.strong(category);
+                utils::two_column_grid(category, ui, |ui: &mut Ui| {
+                    for shortcut in shortcuts {
+                        ui.code(shortcut.format(ctx));
+                        ui.label(shortcut.description);
+                        ui.end_row();
+                    }
+                });
+                utils::vertical_space(ui);
+            }
+        });
+}
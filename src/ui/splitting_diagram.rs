@@ -1,10 +1,150 @@
-use eframe::egui::plot::{Line, LineStyle, PlotUi};
+use eframe::egui::plot::{Line, LineStyle, PlotPoint, PlotUi};
 use eframe::epaint::Color32;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 
+use super::palette;
 use crate::peak::{FractionalStageIndex, MultipletCascade, Peaklet, SplittingRelationship};
 
 const STAGE_ORIGIN: f64 = 0.;
 const MAX_PEAKLET_HEIGHT: f64 = 0.7;
+const CURVE_SAMPLES: usize = 16;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub(super) enum ConnectorStyle {
+    /// Connectors bend sharply directly above each child.
+    #[default]
+    Straight,
+    /// Connectors follow a smooth S-curve from parent to child.
+    Curved,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub(super) enum DiagramScale {
+    /// Peaklets are placed at their true offsets, in Hz.
+    #[default]
+    TrueScale,
+    /// The spread of each stage is rescaled such that its groups fill the space between their
+    /// parents, for legibility of cascades with very different coupling constants.
+    PerStage,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct DiagramOptions {
+    pub(super) connector_style: ConnectorStyle,
+    pub(super) scale: DiagramScale,
+    /// Opacity of enabled peaklet markers, in `[0, 1]`.
+    pub(super) marker_opacity: f32,
+    /// Opacity of enabled connectors, in `[0, 1]`.
+    pub(super) connector_opacity: f32,
+}
+
+impl Default for DiagramOptions {
+    fn default() -> Self {
+        Self {
+            connector_style: ConnectorStyle::default(),
+            scale: DiagramScale::default(),
+            marker_opacity: 0.5,
+            connector_opacity: 1.,
+        }
+    }
+}
+
+impl ConnectorStyle {
+    pub(super) const ALL: [Self; 2] = [Self::Straight, Self::Curved];
+
+    pub(super) fn name(self) -> &'static str {
+        match self {
+            Self::Straight => "Straight",
+            Self::Curved => "Curved",
+        }
+    }
+}
+
+impl DiagramScale {
+    pub(super) const ALL: [Self; 2] = [Self::TrueScale, Self::PerStage];
+
+    pub(super) fn name(self) -> &'static str {
+        match self {
+            Self::TrueScale => "True scale",
+            Self::PerStage => "Per stage",
+        }
+    }
+
+    pub(super) fn is_to_scale(self) -> bool {
+        self == Self::TrueScale
+    }
+}
+
+/// The horizontal positions at which the peaklets of `cascade` are drawn, indexed by stage and by
+/// index within the stage.
+///
+/// In [`DiagramScale::PerStage`], the children of each group keep their order and relative spacing
+/// about their parent, but every stage is scaled such that adjacent groups are separated by twice
+/// the spacing within a group. The first stage spans the width of the true multiplet.
+fn layout(cascade: &MultipletCascade, scale: DiagramScale) -> Vec<Vec<f64>> {
+    let base = cascade.base_peaklet();
+    let mut positions = vec![vec![base.δ]];
+    let span = cascade.final_span();
+    // The smallest distance between adjacent peaklets of the previous stage.
+    let mut gap = span.end() - span.start();
+    for stage in 1..=cascade.child_stages_count() {
+        let groups = cascade.iter_nth_stage(stage).collect_vec();
+        let stage_positions = match scale {
+            DiagramScale::TrueScale => groups
+                .iter()
+                .flat_map(|group| group.children.iter().map(|child| child.δ))
+                .collect_vec(),
+            DiagramScale::PerStage => {
+                let offsets_of = |group: &SplittingRelationship| {
+                    group
+                        .children
+                        .iter()
+                        .map(|child| child.δ - group.parent.δ)
+                        .collect_vec()
+                };
+                let max_offset = groups
+                    .iter()
+                    .flat_map(offsets_of)
+                    .map(f64::abs)
+                    .fold(0., f64::max);
+                let group_size = groups
+                    .first()
+                    .map_or(1, SplittingRelationship::children_count);
+                let half_spread = if stage == 1 {
+                    0.5 * gap
+                } else {
+                    #[allow(clippy::cast_precision_loss)]
+                    let m = group_size as f64;
+                    0.5 * gap * (m - 1.) / (m + 1.)
+                };
+                let factor = if max_offset > 0. {
+                    half_spread / max_offset
+                } else {
+                    0.
+                };
+                if factor > 0. && group_size > 1 {
+                    #[allow(clippy::cast_precision_loss)]
+                    let intervals = (group_size - 1) as f64;
+                    gap = 2. * half_spread / intervals;
+                }
+                let parents = &positions[stage - 1];
+                groups
+                    .iter()
+                    .zip(parents)
+                    .flat_map(|(group, parent)| {
+                        offsets_of(group)
+                            .into_iter()
+                            .map(move |offset| parent + factor * offset)
+                    })
+                    .collect_vec()
+            }
+        };
+        positions.push(stage_positions);
+    }
+    positions
+}
 
 fn base_height_of(stage: usize) -> f64 {
     STAGE_ORIGIN - stage as f64
@@ -14,21 +154,40 @@ fn tip_height_of(peaklet: &Peaklet, stage: usize, max_integration: f64) -> f64 {
     base_height_of(stage) + (peaklet.integration / max_integration) * MAX_PEAKLET_HEIGHT
 }
 
+/// Whether a peaklet (or the connector leading to it) is enabled, and whether it is in the lineage
+/// of the pinned peaklet.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct MarkerState {
+    enabled: bool,
+    highlighted: bool,
+}
+
 fn draw_peaklet_marker(
     plot_ui: &mut PlotUi,
     peaklet: &Peaklet,
+    x: f64,
     stage: usize,
     max_integration: f64,
-    enabled: bool,
+    marker: MarkerState,
+    options: DiagramOptions,
 ) {
+    let MarkerState {
+        enabled,
+        highlighted,
+    } = marker;
     plot_ui.line(
         Line::new(vec![
-            [peaklet.δ, base_height_of(stage)],
-            [peaklet.δ, tip_height_of(peaklet, stage, max_integration)],
+            [x, base_height_of(stage)],
+            [x, tip_height_of(peaklet, stage, max_integration)],
         ])
-        .color(if enabled {
+        .color(if highlighted {
+            palette::LINEAGE_COLOR
+        } else if enabled {
             let mut color = Color32::LIGHT_BLUE;
-            color[3] = 127;
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            {
+                color[3] = (options.marker_opacity.clamp(0., 1.) * 255.) as u8;
+            }
             color
         } else {
             Color32::DARK_GRAY
@@ -37,22 +196,54 @@ fn draw_peaklet_marker(
     );
 }
 
+/// Sample a cubic Bézier curve from `start` to `end` whose tangents at both ends are vertical.
+fn vertical_s_curve(start: [f64; 2], end: [f64; 2]) -> impl Iterator<Item = [f64; 2]> {
+    let mid_y = 0.5 * (start[1] + end[1]);
+    let control_points = [start, [start[0], mid_y], [end[0], mid_y], end];
+    (0..=CURVE_SAMPLES).map(move |i| {
+        let t = i as f64 / CURVE_SAMPLES as f64;
+        let weights = [
+            (1. - t).powi(3),
+            3. * (1. - t).powi(2) * t,
+            3. * (1. - t) * t.powi(2),
+            t.powi(3),
+        ];
+        let mut point = [0.; 2];
+        for (weight, control) in weights.iter().zip(control_points) {
+            point[0] += weight * control[0];
+            point[1] += weight * control[1];
+        }
+        point
+    })
+}
+
 fn draw_group_children_and_connectors(
     plot_ui: &mut PlotUi,
     group: SplittingRelationship,
+    (parent_x, children_x): (f64, &[f64]),
     stage: usize,
     max_integration: f64,
-    enabled: bool,
+    marker_of: impl Fn(usize) -> MarkerState,
+    options: DiagramOptions,
 ) {
-    let parent_base = [group.parent.δ, base_height_of(stage - 1)];
-    for child in group.children {
-        draw_peaklet_marker(plot_ui, child, stage, max_integration, enabled);
-        let child_tip = [child.δ, tip_height_of(child, stage, max_integration)];
-        let corner = [child.δ, base_height_of(stage) + MAX_PEAKLET_HEIGHT];
+    let parent_base = [parent_x, base_height_of(stage - 1)];
+    for (i, (child, &x)) in group.children.iter().zip(children_x).enumerate() {
+        let marker = marker_of(i);
+        draw_peaklet_marker(plot_ui, child, x, stage, max_integration, marker, options);
+        let child_tip = [x, tip_height_of(child, stage, max_integration)];
+        let corner = [x, base_height_of(stage) + MAX_PEAKLET_HEIGHT];
+        let connector = match options.connector_style {
+            ConnectorStyle::Straight => vec![child_tip, corner, parent_base],
+            ConnectorStyle::Curved => std::iter::once(child_tip)
+                .chain(vertical_s_curve(corner, parent_base))
+                .collect(),
+        };
         plot_ui.line(
-            Line::new(vec![child_tip, corner, parent_base])
-                .color(if enabled {
-                    Color32::GRAY
+            Line::new(connector)
+                .color(if marker.highlighted {
+                    palette::LINEAGE_COLOR
+                } else if marker.enabled {
+                    Color32::GRAY.gamma_multiply(options.connector_opacity.clamp(0., 1.))
                 } else {
                     Color32::DARK_GRAY
                 })
@@ -62,13 +253,33 @@ fn draw_group_children_and_connectors(
     }
 }
 
+/// Draw the splitting diagram of `full_cascade`, with stages beyond `view_stage` disabled. The
+/// lineage of the `pinned` peaklet (as `(stage, index within stage)`), if any, is highlighted.
+/// Horizontal positions are in Hz only if the scale of `options` is to scale.
 pub(super) fn draw_splitting_diagram(
     plot_ui: &mut PlotUi,
     full_cascade: &MultipletCascade,
     partial_cascade: &MultipletCascade,
     view_stage: FractionalStageIndex,
+    pinned: Option<(usize, usize)>,
+    options: DiagramOptions,
 ) {
-    draw_peaklet_marker(plot_ui, &full_cascade.base_peaklet(), 0, 1., true);
+    let is_highlighted =
+        |peaklet| pinned.is_some_and(|pinned| full_cascade.is_in_lineage(pinned, peaklet));
+    let base_marker = MarkerState {
+        enabled: true,
+        highlighted: is_highlighted((0, 0)),
+    };
+    let positions = layout(full_cascade, options.scale);
+    draw_peaklet_marker(
+        plot_ui,
+        &full_cascade.base_peaklet(),
+        positions[0][0],
+        0,
+        1.,
+        base_marker,
+        options,
+    );
 
     let last_full = view_stage.full();
     let maybe_partial = view_stage.partial_and_index();
@@ -80,8 +291,96 @@ pub(super) fn draw_splitting_diagram(
             enabled |= stage == partial_idx
                 && (partial_cascade.is_stage_resolved(partial_idx) || part > 0.9);
         }
-        for group in full_cascade.iter_nth_stage(stage) {
-            draw_group_children_and_connectors(plot_ui, group, stage, max_integration, enabled);
+        let mut first_child = 0;
+        for (group_index, group) in full_cascade.iter_nth_stage(stage).enumerate() {
+            let children_count = group.children_count();
+            draw_group_children_and_connectors(
+                plot_ui,
+                group,
+                (
+                    positions[stage - 1][group_index],
+                    &positions[stage][first_child..first_child + children_count],
+                ),
+                stage,
+                max_integration,
+                |i| MarkerState {
+                    enabled,
+                    highlighted: is_highlighted((stage, first_child + i)),
+                },
+                options,
+            );
+            first_child += children_count;
+        }
+    }
+}
+
+/// The peaklet (as `(stage, index within stage)`) whose marker is nearest to `point`, if `point`
+/// lies within the band of a stage. The diagram is assumed to be drawn at `scale`.
+pub(super) fn peaklet_at(
+    cascade: &MultipletCascade,
+    scale: DiagramScale,
+    point: PlotPoint,
+) -> Option<(usize, usize)> {
+    let stage = (-point.y).ceil();
+    if stage < 0. || point.y > base_height_of(0) + MAX_PEAKLET_HEIGHT {
+        return None;
+    }
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let stage = stage as usize;
+    let positions = layout(cascade, scale);
+    let index = positions
+        .get(stage)?
+        .iter()
+        .map(|x| (x - point.x).abs())
+        .position_min_by(f64::total_cmp)?;
+    Some((stage, index))
+}
+
+#[cfg(test)]
+mod tests {
+    use eframe::egui::plot::PlotPoint;
+    use itertools::Itertools;
+
+    use super::{layout, peaklet_at, DiagramScale, MAX_PEAKLET_HEIGHT};
+    use crate::peak::{Peak, Splitter};
+
+    #[test]
+    fn per_stage_layout() {
+        // A small doublet of a large doublet of a tiny doublet.
+        let peak = Peak {
+            splitters: vec![
+                Splitter::new(1, 0.5),
+                Splitter::new(1, 15.),
+                Splitter::new(1, 2.),
+            ],
+            ..Default::default()
+        };
+        let cascade = peak.build_multiplet_cascade();
+        let true_scale = layout(&cascade, DiagramScale::TrueScale);
+        let per_stage = layout(&cascade, DiagramScale::PerStage);
+        assert_eq!(true_scale.len(), per_stage.len());
+        for (stage, (truth, scaled)) in true_scale.iter().zip(&per_stage).enumerate() {
+            assert_eq!(truth.len(), scaled.len());
+            // Groups are laid out in order, without overlapping their neighbors.
+            assert!(
+                scaled.iter().tuple_windows().all(|(a, b)| a < b),
+                "stage {stage}: {scaled:?}"
+            );
+        }
+        // The first stage spans the true multiplet.
+        let span = cascade.final_span();
+        approx::assert_relative_eq!(per_stage[1][1] - per_stage[1][0], span.end() - span.start());
+    }
+
+    #[test]
+    fn singlet_layout() {
+        let cascade = Peak::default().build_multiplet_cascade();
+        for scale in DiagramScale::ALL {
+            assert_eq!(layout(&cascade, scale), [[0.]]);
+            // Only the base peaklet can be picked.
+            let point = PlotPoint::new(3., 0.5 * MAX_PEAKLET_HEIGHT);
+            assert_eq!(peaklet_at(&cascade, scale, point), Some((0, 0)));
+            assert_eq!(peaklet_at(&cascade, scale, PlotPoint::new(0., -1.5)), None);
         }
     }
 }
@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use eframe::egui::{Align2, Area, Context, Frame, Id, Order};
+use eframe::epaint::Vec2;
+
+#[derive(Clone, Debug)]
+struct Toast {
+    text: String,
+    /// Time (in seconds, per [`eframe::egui::InputState::time`]) after which the toast is hidden.
+    expires_at: f64,
+}
+
+#[derive(Clone, Debug, Default)]
+/// Brief, non-interactive notifications stacked at the bottom of the window.
+pub(super) struct Toasts {
+    toasts: Vec<Toast>,
+}
+
+impl Toasts {
+    const DURATION: f64 = 2.5;
+    const MARGIN: f32 = 40.;
+
+    pub(super) fn push(&mut self, ctx: &Context, text: impl Into<String>) {
+        let now = ctx.input(|i| i.time);
+        self.toasts.push(Toast {
+            text: text.into(),
+            expires_at: now + Self::DURATION,
+        });
+        ctx.request_repaint();
+    }
+
+    pub(super) fn show(&mut self, ctx: &Context) {
+        let now = ctx.input(|i| i.time);
+        self.toasts.retain(|toast| toast.expires_at > now);
+        let Some(next_expiry) = self
+            .toasts
+            .iter()
+            .map(|toast| toast.expires_at)
+            .reduce(f64::min)
+        else {
+            return;
+        };
+
+        Area::new(Id::new("toasts"))
+            .anchor(Align2::CENTER_BOTTOM, Vec2::new(0., -Self::MARGIN))
+            .order(Order::Foreground)
+            .interactable(false)
+            .show(ctx, |ui| {
+                for toast in &self.toasts {
+                    Frame::popup(ui.style()).show(ui, |ui| ui.label(&toast.text));
+                }
+            });
+        ctx.request_repaint_after(Duration::from_secs_f64(next_expiry - now));
+    }
+}
@@ -1,11 +1,57 @@
-use eframe::egui::plot::{Plot, PlotBounds, PlotUi};
+use eframe::egui::plot::{Plot, PlotBounds, PlotUi, VLine};
 use eframe::egui::{self, CursorIcon, Grid, InputState, Margin, TopBottomPanel, Ui};
-use eframe::epaint::Vec2;
+use eframe::epaint::{Color32, Vec2};
+
+use crate::numerics;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+/// Which axes of a peak plot respond to panning and zooming, as `[x, y]`.
+pub struct InteractionAxes {
+    pub pan: [bool; 2],
+    pub zoom: [bool; 2],
+}
+
+impl Default for InteractionAxes {
+    fn default() -> Self {
+        Self {
+            pan: [true, false],
+            zoom: [true, true],
+        }
+    }
+}
+
+impl InteractionAxes {
+    #[must_use]
+    pub fn horizontal_only(self) -> Self {
+        Self {
+            pan: [self.pan[0], false],
+            zoom: [self.zoom[0], false],
+        }
+    }
+}
 
 /// Apply custom zoom and pan interactions for peak plots.
-pub fn peak_viewer_interactions(plot_ui: &mut PlotUi, allow_vertical: bool) {
+///
+/// Additionally, alt-clicking copies the shift under the cursor (in ppm and Hz, with the latter
+/// computed at `field_strength`) to the clipboard. The copied text is returned.
+pub fn peak_viewer_interactions(
+    plot_ui: &mut PlotUi,
+    axes: InteractionAxes,
+    field_strength: f64,
+) -> Option<String> {
     if !plot_ui.plot_hovered() && !plot_ui.plot_clicked() {
-        return;
+        return None;
+    }
+
+    let mut copied = None;
+    if plot_ui.plot_clicked() && plot_ui.ctx().input(|i| i.modifiers.alt) {
+        if let Some(coordinate) = plot_ui.pointer_coordinate() {
+            // The x-axis is negated for display; see the peak viewer's axis formatter.
+            let ppm = numerics::negate_nonzero(coordinate.x);
+            let text = format!("δ = {ppm:.4} ppm ({:.2} Hz)", ppm * field_strength);
+            plot_ui.ctx().output_mut(|o| o.copied_text = text.clone());
+            copied = Some(text);
+        }
     }
 
     let multitouch = plot_ui.ctx().input(InputState::multi_touch);
@@ -15,7 +61,7 @@ pub fn peak_viewer_interactions(plot_ui: &mut PlotUi, allow_vertical: bool) {
     let (mut bounds_min, mut bounds_max) = (bounds.min(), bounds.max());
     // scroll wheel y zoom:
     let raw_scroll_y = plot_ui.ctx().input(|i| f64::from(i.scroll_delta.y));
-    if allow_vertical && raw_scroll_y != 0. {
+    if axes.zoom[1] && raw_scroll_y != 0. {
         let scroll_y = (raw_scroll_y / 200.).exp();
         bounds_min[1] /= scroll_y;
         bounds_max[1] /= scroll_y;
@@ -23,10 +69,12 @@ pub fn peak_viewer_interactions(plot_ui: &mut PlotUi, allow_vertical: bool) {
     // ctrl-scroll x-zoom or pinch-to-zoom (x and y):
     // This seems to eat the raw scroll delta in the former case.
     let zoom_delta = plot_ui.ctx().input(InputState::zoom_delta_2d);
-    let zoom_x = f64::from(zoom_delta.x);
-    bounds_min[0] /= zoom_x;
-    bounds_max[0] /= zoom_x;
-    if allow_vertical && multitouch.is_some() {
+    if axes.zoom[0] {
+        let zoom_x = f64::from(zoom_delta.x);
+        bounds_min[0] /= zoom_x;
+        bounds_max[0] /= zoom_x;
+    }
+    if axes.zoom[1] && multitouch.is_some() {
         let pinch_y = f64::from(zoom_delta.y);
         bounds_min[1] /= pinch_y;
         bounds_max[1] /= pinch_y;
@@ -42,15 +90,45 @@ pub fn peak_viewer_interactions(plot_ui: &mut PlotUi, allow_vertical: bool) {
         .input(|i| i.pointer.primary_down().then(|| i.pointer.delta()));
     if let Some(drag) = drag {
         // Don't allow drag-to-pan while in pinch-to-zoom.
-        if multitouch.is_none() {
-            plot_ui.ctx().set_cursor_icon(CursorIcon::ResizeHorizontal);
+        if multitouch.is_none() && (axes.pan[0] || axes.pan[1]) {
+            plot_ui.ctx().set_cursor_icon(match axes.pan {
+                [true, false] => CursorIcon::ResizeHorizontal,
+                [false, true] => CursorIcon::ResizeVertical,
+                _ => CursorIcon::Move,
+            });
+            let dvalue_dpos = plot_ui.transform().dvalue_dpos();
+            let mask = |allowed: bool| if allowed { 1. } else { 0. };
             #[allow(clippy::cast_possible_truncation)]
             plot_ui.translate_bounds(Vec2 {
-                x: -drag.x * plot_ui.transform().dvalue_dpos()[0] as f32,
-                y: 0.,
+                x: -drag.x * dvalue_dpos[0] as f32 * mask(axes.pan[0]),
+                y: -drag.y * dvalue_dpos[1] as f32 * mask(axes.pan[1]),
             });
         }
     }
+
+    copied
+}
+
+#[must_use]
+/// The x-coordinate under the pointer, if the plot is hovered.
+pub fn hovered_x(plot_ui: &PlotUi) -> Option<f64> {
+    plot_ui
+        .plot_hovered()
+        .then(|| plot_ui.pointer_coordinate())
+        .flatten()
+        .map(|point| point.x)
+}
+
+/// Draw a vertical crosshair at `x`, if it lies within the current bounds (so as not to affect
+/// automatic bounds).
+pub fn draw_crosshair(plot_ui: &mut PlotUi, x: Option<f64>) {
+    let Some(x) = x else {
+        return;
+    };
+    let bounds = plot_ui.plot_bounds();
+    if (bounds.min()[0]..=bounds.max()[0]).contains(&x) {
+        plot_ui.vline(VLine::new(x).color(Color32::from_gray(160)).width(1.));
+    }
 }
 
 pub fn inner_bottom_panel(id: &'static str, ui: &mut Ui, add_contents: impl FnOnce(&mut Ui)) {
@@ -67,6 +145,13 @@ pub fn vertical_space(ui: &mut Ui) {
     ui.add_space(ui.style().spacing.item_spacing.y);
 }
 
+/// Scale the font size of every text style used within `ui`.
+pub fn scale_text_styles(ui: &mut Ui, factor: f32) {
+    for font_id in ui.style_mut().text_styles.values_mut() {
+        font_id.size *= factor;
+    }
+}
+
 pub fn two_column_grid(id: &'static str, ui: &mut Ui, add_contents: impl FnOnce(&mut Ui)) {
     Grid::new(id).num_columns(2).show(ui, add_contents);
 }
@@ -82,3 +167,73 @@ pub fn make_noninteractable_plot(id: &'static str) -> Plot {
         .allow_zoom(false)
         .allow_double_click_reset(false)
 }
+
+/// Measures elapsed wall-clock time, also on the web, where [`std::time::Instant`] is unavailable.
+pub struct Stopwatch {
+    #[cfg(not(target_arch = "wasm32"))]
+    start: std::time::Instant,
+    /// Milliseconds since the epoch.
+    #[cfg(target_arch = "wasm32")]
+    start: f64,
+}
+
+impl Stopwatch {
+    #[must_use]
+    pub fn start() -> Self {
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            start: std::time::Instant::now(),
+            #[cfg(target_arch = "wasm32")]
+            start: js_sys::Date::now(),
+        }
+    }
+
+    #[must_use]
+    pub fn elapsed_ms(&self) -> f64 {
+        #[cfg(not(target_arch = "wasm32"))]
+        let elapsed = self.start.elapsed().as_secs_f64() * 1000.;
+        #[cfg(target_arch = "wasm32")]
+        let elapsed = js_sys::Date::now() - self.start;
+        elapsed
+    }
+}
+
+/// Ask where to save `contents`, suggesting `file_name`, returning the path written to, or `None`
+/// if the user cancels.
+///
+/// # Errors
+/// If the file cannot be written; the message is suitable for display.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_file(file_name: &str, contents: &[u8]) -> Result<Option<String>, String> {
+    let Some(path) = rfd::FileDialog::new().set_file_name(file_name).save_file() else {
+        return Ok(None);
+    };
+    std::fs::write(&path, contents).map_err(|err| err.to_string())?;
+    Ok(Some(path.display().to_string()))
+}
+
+/// Offer `contents` to the browser as a download named `file_name`, returning that name.
+///
+/// # Errors
+/// If the download cannot be started; the message is suitable for display.
+#[cfg(target_arch = "wasm32")]
+pub fn save_file(file_name: &str, contents: &[u8]) -> Result<Option<String>, String> {
+    use wasm_bindgen::JsCast;
+
+    let describe = |err: wasm_bindgen::JsValue| format!("{err:?}");
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(contents));
+    let blob = web_sys::Blob::new_with_u8_array_sequence(&parts).map_err(describe)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(describe)?;
+    let anchor = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("no document")?
+        .create_element("a")
+        .map_err(describe)?
+        .dyn_into::<web_sys::HtmlAnchorElement>()
+        .map_err(|_| "not an anchor element")?;
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+    web_sys::Url::revoke_object_url(&url).map_err(describe)?;
+    Ok(Some(file_name.to_owned()))
+}
@@ -0,0 +1,190 @@
+// Links are only meaningful on the web, but their encoding is tested natively.
+#![cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+
+use std::fmt::Write;
+
+use eframe::egui::Context;
+use serde::{Deserialize, Serialize};
+
+use super::Protonolysis;
+use crate::peak::{self, Peak};
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+/// The state encoded in the fragment of a shareable link: the configuration, and exactly which
+/// part of the spectrum is in view.
+pub(super) struct ViewLink {
+    field_strength: f64,
+    peak: Peak,
+    view_stage: f64,
+    /// The visible ranges of the viewer, in ppm and intensity. Absent or invalid windows fall back
+    /// to the default framing.
+    x_window: Option<(f64, f64)>,
+    y_window: Option<(f64, f64)>,
+}
+
+/// Whether `window` is a non-empty range of finite numbers.
+fn is_valid_window(&(min, max): &(f64, f64)) -> bool {
+    min.is_finite() && max.is_finite() && min < max
+}
+
+/// Escape all but the unreserved characters of a URL (RFC 3986).
+fn percent_encode(text: &str) -> String {
+    text.bytes().fold(String::new(), |mut encoded, byte| {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(char::from(byte));
+        } else {
+            write!(encoded, "%{byte:02X}").unwrap();
+        }
+        encoded
+    })
+}
+
+fn percent_decode(text: &str) -> Result<String, String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = tail
+                .get(..2)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or("malformed escape")?;
+            bytes.push(hex);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).map_err(|err| err.to_string())
+}
+
+impl ViewLink {
+    pub(super) fn to_fragment(&self) -> String {
+        percent_encode(&ron::to_string(self).expect("view is serializable"))
+    }
+
+    /// Parse a fragment produced by [`Self::to_fragment`], with or without its leading `#`.
+    /// Invalid windows are dropped, as is an invalid stage, in favor of the complete multiplet.
+    ///
+    /// # Errors
+    /// If `fragment` does not describe a view, its field strength is invalid, or its peak is beyond
+    /// the limits of the controls.
+    pub(super) fn from_fragment(fragment: &str) -> Result<Self, String> {
+        let text = percent_decode(fragment.trim_start_matches('#'))?;
+        let mut link = ron::from_str::<Self>(&text).map_err(|err| err.to_string())?;
+        peak::validate_frequency(link.field_strength)
+            .map_err(|err| format!("invalid field strength: {err}"))?;
+        Protonolysis::check_peak_limits(&mut link.peak)
+            .map_err(|err| format!("invalid peak: {err}"))?;
+        link.x_window = link.x_window.filter(is_valid_window);
+        link.y_window = link.y_window.filter(is_valid_window);
+        link.view_stage = Protonolysis::valid_view_stage(link.view_stage).unwrap_or(f64::INFINITY);
+        Ok(link)
+    }
+}
+
+impl Protonolysis {
+    pub(super) fn view_link(&self) -> ViewLink {
+        ViewLink {
+            field_strength: self.field_strength,
+            peak: self.resting_peak(),
+            view_stage: *self.view_stage,
+            x_window: Some(self.linked_x_axis),
+            y_window: Some(self.linked_y_axis),
+        }
+    }
+
+    /// Restore the view described by the fragment of the URL the app was loaded from, if any.
+    pub(super) fn load_view_link(&mut self, ctx: &Context, fragment: &str) {
+        if fragment.trim_start_matches('#').is_empty() {
+            return;
+        }
+        match ViewLink::from_fragment(fragment) {
+            Ok(link) => {
+                self.field_strength = link.field_strength;
+                self.windowed_field_strength = link.field_strength;
+                self.peak = link.peak;
+                self.broad_lines = self.peak.fwhm > Self::MAX_FWHM;
+                self.update_animation_parameters();
+                self.view_stage.set_value_clamping(link.view_stage);
+                self.requested_x_axis = link.x_window;
+                self.requested_y_axis = link.y_window;
+                self.update_partial_cascade();
+            }
+            Err(err) => self.toasts.push(ctx, format!("Failed to open link: {err}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ViewLink;
+    use crate::peak::{Peak, Splitter};
+    use crate::ui::Protonolysis;
+
+    fn link() -> ViewLink {
+        ViewLink {
+            field_strength: 400.,
+            peak: Peak {
+                label: "CH₃ (ethyl)".to_owned(),
+                proton_count: 3,
+                splitters: vec![Splitter::new(2, 7.2)],
+                ..Default::default()
+            },
+            view_stage: 0.75,
+            x_window: Some((-0.02, 0.015)),
+            y_window: Some((-5., 120.)),
+        }
+    }
+
+    #[test]
+    fn view_link_round_trip() {
+        let link = link();
+        let fragment = link.to_fragment();
+        assert!(fragment
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || b"-._~%".contains(&byte)));
+        assert_eq!(ViewLink::from_fragment(&fragment), Ok(link.clone()));
+        assert_eq!(ViewLink::from_fragment(&format!("#{fragment}")), Ok(link));
+    }
+
+    #[test]
+    fn view_link_invalid() {
+        let inverted = ViewLink {
+            x_window: Some((0.1, -0.1)),
+            y_window: Some((f64::NAN, 1.)),
+            ..link()
+        };
+        let parsed = ViewLink::from_fragment(&inverted.to_fragment()).unwrap();
+        assert_eq!((parsed.x_window, parsed.y_window), (None, None));
+
+        for view_stage in [f64::NAN, f64::NEG_INFINITY, -1.] {
+            let unknown_stage = ViewLink {
+                view_stage,
+                ..link()
+            };
+            let parsed = ViewLink::from_fragment(&unknown_stage.to_fragment()).unwrap();
+            assert!(parsed.view_stage.is_infinite() && parsed.view_stage > 0.);
+        }
+
+        let no_field = ViewLink {
+            field_strength: 0.,
+            ..link()
+        };
+        assert!(ViewLink::from_fragment(&no_field.to_fragment()).is_err());
+        assert!(ViewLink::from_fragment("#not%20a%20view").is_err());
+        assert!(ViewLink::from_fragment("#%E").is_err());
+
+        let mut crowded = link();
+        crowded.peak.splitters = vec![Splitter::new(1, 5.); Protonolysis::MAX_SPLITTERS + 1];
+        assert!(ViewLink::from_fragment(&crowded.to_fragment()).is_err());
+
+        let mut many_neighbors = link();
+        many_neighbors.peak.splitters = vec![Splitter::new(u32::MAX, 7.)];
+        assert!(ViewLink::from_fragment(&many_neighbors.to_fragment()).is_err());
+        let mut no_width = link();
+        no_width.peak.fwhm = f64::NAN;
+        assert!(ViewLink::from_fragment(&no_width.to_fragment()).is_err());
+    }
+}